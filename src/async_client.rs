@@ -0,0 +1,712 @@
+//! # async_client
+//!
+//! a non-blocking client for use inside an async runtime, without a dedicated thread for the
+//! blocking one. Only the network round-trips are async: beacons are verified with the same
+//! synchronous `verify_beacon` once they have arrived. Sleeping, e.g. in `watch`, uses tokio's
+//! timer, so it needs a tokio runtime. The current time is read from a `Clock`, the system
+//! clock by default, but the clock's `sleep` is never called. `watch` fetches each round on a
+//! `PollSchedule`, like the blocking `Watcher`.
+//!
+
+use crate::chain_info::ChainInfo;
+use crate::clock::{Clock, SystemClock};
+use crate::delay::PublishDelayEstimator;
+use crate::http::tls_failure;
+use crate::retry::jittered;
+use crate::url::{DefaultUrlStrategy, UrlStrategy};
+use crate::verify::{verify_beacon, Beacon, VerificationError, VerifiedBeacon};
+use crate::watch::PollSchedule;
+use crate::DrandClientError::{InvalidChainInfo, InvalidRound};
+use crate::{round_for_time, time_for_round, DrandClientError, TransportError};
+use futures_util::stream::{self, Stream, StreamExt};
+use reqwest::{Client, StatusCode};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::time::Instant;
+
+// how long `watch` and `wait_for_round` wait before fetching a round again after a failure
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+// the delay between the first two polls of `await_round`, doubled after each poll up to a
+// period
+const FIRST_POLL_DELAY: Duration = Duration::from_millis(250);
+
+/// the async counterpart of `Transport`. Implementations can be written as
+/// `async fn fetch`, as long as the future is `Send`.
+pub trait AsyncTransport {
+    fn fetch(&self, url: &str) -> impl Future<Output = Result<String, TransportError>> + Send;
+}
+
+/// an `AsyncTransport` using a non-blocking `reqwest::Client`
+#[derive(Debug, Clone, Default)]
+pub struct AsyncHttpTransport {
+    client: Client,
+}
+
+impl AsyncHttpTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// an async HTTP transport using an already configured `client`
+    pub fn with_client(client: Client) -> Self {
+        AsyncHttpTransport { client }
+    }
+}
+
+impl AsyncTransport for AsyncHttpTransport {
+    async fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        let res = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| match tls_failure(&e) {
+                Some(message) => TransportError::Tls { message },
+                None => TransportError::Unexpected { status_code: None },
+            })?;
+
+        match res.status() {
+            StatusCode::OK => res.text().await.map_err(|_| TransportError::Unexpected {
+                status_code: Some(StatusCode::OK.as_u16()),
+            }),
+
+            status => Err(TransportError::from_http_status(status.as_u16())),
+        }
+    }
+}
+
+/// create a new instance of the async client with an HTTP transport for a given `base_url`,
+/// like `new_http_client`
+pub async fn new_async_http_client(
+    base_url: impl Into<String>,
+) -> Result<AsyncDrandClient<AsyncHttpTransport>, DrandClientError> {
+    let base_url = base_url.into();
+    let transport = AsyncHttpTransport::new();
+    let chain_info = fetch_chain_info_async(&transport, &base_url).await?;
+    Ok(AsyncDrandClient::new(transport, base_url, chain_info))
+}
+
+/// fetch the chain info for a given URL, like `fetch_chain_info`
+pub async fn fetch_chain_info_async<T: AsyncTransport>(
+    transport: &T,
+    base_url: &str,
+) -> Result<ChainInfo, DrandClientError> {
+    let body = transport
+        .fetch(&DefaultUrlStrategy.info_url(base_url))
+        .await?;
+    let chain_info: ChainInfo = serde_json::from_str(&body).map_err(|_| InvalidChainInfo)?;
+    chain_info.validate()?;
+    Ok(chain_info)
+}
+
+/// retrieves and validates drand beacons over an `AsyncTransport`. Unlike `DrandClient`, it
+/// has no retries, caching or chain migration check.
+pub struct AsyncDrandClient<T: AsyncTransport> {
+    transport: T,
+    base_url: String,
+    chain_info: ChainInfo,
+    clock: Arc<dyn Clock + Send + Sync>,
+    latest_tolerance: u64,
+    publish_delay: Mutex<PublishDelayEstimator>,
+}
+
+impl<T: AsyncTransport> AsyncDrandClient<T> {
+    /// a client for the chain described by `chain_info`, which must already be trusted
+    pub fn new(transport: T, base_url: impl Into<String>, chain_info: ChainInfo) -> Self {
+        AsyncDrandClient {
+            transport,
+            base_url: base_url.into(),
+            chain_info,
+            clock: Arc::new(SystemClock),
+            latest_tolerance: 1,
+            publish_delay: Mutex::new(PublishDelayEstimator::new()),
+        }
+    }
+
+    /// how many rounds behind the current one `latest_randomness` accepts, like
+    /// `DrandClientBuilder::latest_tolerance`. Defaults to one.
+    pub fn with_latest_tolerance(mut self, rounds: u64) -> Self {
+        self.latest_tolerance = rounds;
+        self
+    }
+
+    /// read the current time from `clock` instead of the system clock, like
+    /// `DrandClient::with_clock`. Sleeps still use tokio's timer, so with a
+    /// `testing::FixedClock` also pause tokio's time, e.g. with `start_paused`.
+    pub fn with_clock(mut self, clock: impl Clock + Send + Sync + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// the chain info the client verifies beacons against
+    pub fn chain_info(&self) -> &ChainInfo {
+        &self.chain_info
+    }
+
+    /// fetch the latest available randomness beacon
+    pub async fn latest_randomness(&self) -> Result<VerifiedBeacon, DrandClientError> {
+        let expected_round = round_for_time(&self.chain_info, self.clock.now())?;
+        let beacon = self
+            .fetch_beacon(&DefaultUrlStrategy.latest_url(&self.base_url))
+            .await?;
+
+        // it could take some time to aggregate beacons, so we tolerate a round early for latest
+        if beacon.round_number < expected_round.saturating_sub(self.latest_tolerance) {
+            return Err(DrandClientError::InvalidBeacon);
+        }
+        self.observe_publish_delay(beacon.round_number, self.clock.now());
+        Ok(beacon)
+    }
+
+    /// fetch a randomness beacon for a specific round
+    pub async fn randomness(&self, round_number: u64) -> Result<VerifiedBeacon, DrandClientError> {
+        if round_number == 0 {
+            return Err(InvalidRound);
+        }
+        let beacon = self
+            .fetch_beacon(&DefaultUrlStrategy.round_url(&self.base_url, round_number))
+            .await?;
+        if beacon.round_number != round_number {
+            return Err(DrandClientError::InvalidBeacon);
+        }
+        Ok(beacon)
+    }
+
+    /// fetch and verify the beacons of rounds `start..=end` in order, with up to
+    /// `concurrency` requests in flight at once. A failed round is yielded as an error and
+    /// the rest are still fetched. A `start` of 0 yields only `InvalidRound`, and a `start`
+    /// past `end` nothing at all.
+    pub fn fetch_range_concurrent(
+        &self,
+        start: u64,
+        end: u64,
+        concurrency: usize,
+    ) -> impl Stream<Item = Result<VerifiedBeacon, DrandClientError>> + '_ {
+        let invalid = (start == 0).then_some(Err(InvalidRound));
+        let rounds = if start == 0 { 1..=0 } else { start..=end };
+        stream::iter(invalid).chain(
+            stream::iter(rounds)
+                .map(move |round| self.randomness(round))
+                .buffered(concurrency.max(1)),
+        )
+    }
+
+    /// how long after its scheduled time the relay typically publishes a round, like
+    /// `DrandClient::publish_delay_estimate`, from `latest_randomness` calls and `watch`
+    /// fetches. `None` until a new round has been seen appearing.
+    pub fn publish_delay_estimate(&self) -> Option<Duration> {
+        self.publish_delay
+            .lock()
+            .ok()
+            .and_then(|estimator| estimator.estimate())
+    }
+
+    /// sleep until `round` is due, then fetch its beacon, trying once more a second later if
+    /// the relay hasn't published it or can't be reached. A round in the past is fetched
+    /// straight away; round 0 fails with `InvalidRound`.
+    pub async fn wait_for_round(&self, round: u64) -> Result<VerifiedBeacon, DrandClientError> {
+        self.sleep_until_round(round, Duration::ZERO).await?;
+        match self.randomness(round).await {
+            Err(DrandClientError::NotResponding) => {
+                tokio::time::sleep(RETRY_DELAY).await;
+                self.randomness(round).await
+            }
+            result => result,
+        }
+    }
+
+    /// sleep until `round` is due, then poll with jittered exponential backoff until its
+    /// verified beacon is available. Polling continues for as long as the relay doesn't
+    /// respond with the round; other failures are returned. The future holds no state
+    /// outside itself, so it is cancel-safe and can be raced against a timeout, e.g. with
+    /// `tokio::select!` or `tokio::time::timeout`.
+    pub async fn await_round(&self, round: u64) -> Result<VerifiedBeacon, DrandClientError> {
+        self.sleep_until_round(round, Duration::ZERO).await?;
+        let max_delay = self.chain_info.period_as_duration();
+        let mut delay = FIRST_POLL_DELAY;
+        loop {
+            match self.randomness(round).await {
+                Err(DrandClientError::NotResponding) => {
+                    tokio::time::sleep(jittered(delay)).await;
+                    delay = (delay * 2).min(max_delay);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// an endless stream of beacons, starting with the current round. Each round is fetched
+    /// on the default `PollSchedule`: shortly after it is due, going by the publish delay
+    /// estimate, with a short retry burst if it isn't published yet. A fetch that still fails
+    /// is yielded as an error and the same round fetched again a second later. A consumer
+    /// that falls behind gets the rounds it missed in order, fetched without sleeping. The
+    /// stream is `Send` when `T` is `Sync`.
+    pub fn watch(&self) -> impl Stream<Item = Result<VerifiedBeacon, DrandClientError>> + '_ {
+        self.watch_with(PollSchedule::default())
+    }
+
+    /// like `watch`, fetching each round on `poll_schedule`
+    pub fn watch_with(
+        &self,
+        poll_schedule: PollSchedule,
+    ) -> impl Stream<Item = Result<VerifiedBeacon, DrandClientError>> + '_ {
+        stream::unfold((None, false), move |(next_round, failed)| async move {
+            if failed {
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+            let round = match next_round {
+                Some(round) => round,
+                None => match round_for_time(&self.chain_info, self.clock.now()) {
+                    Ok(round) => round,
+                    Err(e) => return Some((Err(e), (None, true))),
+                },
+            };
+            let offset = poll_schedule.offset(self.publish_delay_estimate());
+            if let Err(e) = self.sleep_until_round(round, offset).await {
+                return Some((Err(e), (Some(round), true)));
+            }
+
+            let result = self.fetch_on_schedule(round, &poll_schedule).await;
+            let state = match result {
+                Ok(_) => {
+                    self.observe_publish_delay(round, self.clock.now());
+                    (Some(round + 1), false)
+                }
+                Err(_) => (Some(round), true),
+            };
+            Some((result, state))
+        })
+    }
+
+    /// sleep until `offset` after the scheduled time of `round` by the client's clock, or not
+    /// at all if that has passed
+    async fn sleep_until_round(
+        &self,
+        round: u64,
+        offset: Duration,
+    ) -> Result<(), DrandClientError> {
+        let scheduled = time_for_round(&self.chain_info, round)? + offset;
+        if let Ok(wait) = scheduled.duration_since(self.clock.now()) {
+            tokio::time::sleep_until(Instant::now() + wait).await;
+        }
+        Ok(())
+    }
+
+    // the first fetch and the retry burst of `poll_schedule`, as in the blocking `Watcher`
+    async fn fetch_on_schedule(
+        &self,
+        round: u64,
+        poll_schedule: &PollSchedule,
+    ) -> Result<VerifiedBeacon, DrandClientError> {
+        let mut retries = 0;
+        loop {
+            match self.randomness(round).await {
+                Err(DrandClientError::NotResponding) if retries < poll_schedule.retry_attempts => {
+                    retries += 1;
+                    tokio::time::sleep(poll_schedule.retry_interval).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn observe_publish_delay(&self, round_number: u64, observed_at: SystemTime) {
+        if let Ok(mut estimator) = self.publish_delay.lock() {
+            estimator.observe(&self.chain_info, round_number, observed_at);
+        }
+    }
+
+    async fn fetch_beacon(&self, url: &str) -> Result<VerifiedBeacon, DrandClientError> {
+        let body = self.transport.fetch(url).await?;
+        let beacon =
+            serde_json::from_str::<Beacon>(&body).map_err(|_| DrandClientError::InvalidBeacon)?;
+
+        verify_beacon(
+            &self.chain_info.scheme_id,
+            &self.chain_info.public_key,
+            beacon,
+        )
+        .map_err(|e| match e {
+            VerificationError::VerificationDisabled => DrandClientError::VerificationDisabled,
+            e => DrandClientError::FailedVerification(e),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::async_client::{
+        fetch_chain_info_async, AsyncDrandClient, AsyncHttpTransport, AsyncTransport,
+    };
+    use crate::chain_info::ChainInfo;
+    use crate::clock::Clock;
+    use crate::testing::FixedClock;
+    use crate::testkit::{TestChain, TestTransport};
+    use crate::verify::SchemeID::PedersenBlsUnchained;
+    use crate::verify::VerificationError::SignatureFailedVerification;
+    use crate::watch::PollSchedule;
+    use crate::{time_for_round, DrandClientError, Transport, TransportError};
+    use futures_util::StreamExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use tokio::time::Instant;
+
+    // serves a `TestTransport`'s responses through the async interface
+    struct Ready(TestTransport);
+
+    impl AsyncTransport for Ready {
+        async fn fetch(&self, url: &str) -> Result<String, TransportError> {
+            self.0.fetch(url)
+        }
+    }
+
+    #[tokio::test]
+    async fn rounds_are_fetched_and_verified() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let transport = Ready(chain.transport(3));
+        let info = fetch_chain_info_async(&transport, "api.drand.sh").await?;
+        let client = AsyncDrandClient::new(transport, "api.drand.sh", info);
+
+        assert_eq!(client.randomness(2).await?, chain.sign_round(2));
+        assert_eq!(
+            client.randomness(0).await,
+            Err(DrandClientError::InvalidRound)
+        );
+        assert_eq!(
+            client.randomness(4).await,
+            Err(DrandClientError::NotResponding)
+        );
+        assert_eq!(
+            client.randomness(3).await?.into_inner(),
+            chain.sign_round(3)
+        );
+        Ok(())
+    }
+
+    // counts how many fetches are in flight at once, each yielding before it completes
+    struct Concurrent {
+        transport: TestTransport,
+        in_flight: AtomicUsize,
+        most_in_flight: AtomicUsize,
+    }
+
+    impl AsyncTransport for Concurrent {
+        async fn fetch(&self, url: &str) -> Result<String, TransportError> {
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.most_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            self.transport.fetch(url)
+        }
+    }
+
+    #[tokio::test]
+    async fn ranges_are_fetched_in_order_with_bounded_concurrency() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let transport = Concurrent {
+            transport: chain.transport(10),
+            in_flight: AtomicUsize::new(0),
+            most_in_flight: AtomicUsize::new(0),
+        };
+        let client = AsyncDrandClient::new(transport, "api.drand.sh", chain.chain_info().clone());
+
+        let beacons: Vec<_> = client.fetch_range_concurrent(2, 11, 3).collect().await;
+        let expected: Vec<_> = (2..=10)
+            .map(|round| Ok(chain.verified_round(round)))
+            .collect();
+        assert_eq!(beacons[..9], expected);
+        assert_eq!(beacons[9], Err(DrandClientError::NotResponding));
+        assert_eq!(client.transport.most_in_flight.load(Ordering::SeqCst), 3);
+
+        let beacons: Vec<_> = client.fetch_range_concurrent(0, 5, 3).collect().await;
+        assert_eq!(beacons, [Err(DrandClientError::InvalidRound)]);
+        assert_eq!(client.fetch_range_concurrent(5, 4, 3).count().await, 0);
+    }
+    #[tokio::test]
+    async fn forged_beacons_fail_verification() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let other = TestChain::with_seed(PedersenBlsUnchained, 30, 1595431050, b"other");
+        let transport = Ready(chain.transport(3).serve_latest(&other.sign_round(3)));
+        let client = AsyncDrandClient::new(transport, "api.drand.sh", chain.chain_info().clone());
+
+        // beacons are verified before they are checked for staleness
+        assert_eq!(
+            client.latest_randomness().await,
+            Err(DrandClientError::FailedVerification(
+                SignatureFailedVerification
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn stale_latest_beacons_are_rejected() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = AsyncDrandClient::new(
+            Ready(chain.transport(3)),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+
+        assert_eq!(
+            client.latest_randomness().await,
+            Err(DrandClientError::InvalidBeacon)
+        );
+    }
+
+    #[tokio::test]
+    async fn time_is_read_from_the_clients_clock() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let clock = Arc::new(FixedClock::at_round(chain.chain_info(), 3)?);
+        let client = AsyncDrandClient::new(
+            Ready(chain.transport(3)),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        )
+        .with_clock(clock.clone());
+
+        assert_eq!(client.latest_randomness().await?, chain.sign_round(3));
+        let first: Vec<_> = client.watch().take(1).collect().await;
+        assert_eq!(first, [Ok(chain.verified_round(3))]);
+
+        clock.advance(chain.chain_info().period_as_duration() * 2);
+        assert_eq!(
+            client.latest_randomness().await,
+            Err(DrandClientError::InvalidBeacon)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn latest_tolerance_sets_how_far_behind_latest_may_be() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = |round, tolerance| {
+            AsyncDrandClient::new(
+                Ready(chain.transport(3)),
+                "api.drand.sh",
+                chain.chain_info().clone(),
+            )
+            .with_clock(FixedClock::at_round(chain.chain_info(), round).unwrap())
+            .with_latest_tolerance(tolerance)
+        };
+
+        assert_eq!(client(5, 2).latest_randomness().await?, chain.sign_round(3));
+        assert_eq!(
+            client(5, 1).latest_randomness().await,
+            Err(DrandClientError::InvalidBeacon)
+        );
+        assert_eq!(
+            client(3, u64::MAX).latest_randomness().await?,
+            chain.sign_round(3)
+        );
+        Ok(())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn watch_yields_every_round_and_carries_on_after_failures() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        // a long period, so the clock can't move into round 2 before the stream starts
+        let chain = TestChain::new(PedersenBlsUnchained, 3600, now - 10);
+        let transport = Flaky {
+            inner: Ready(chain.transport(3)),
+            failures: AtomicUsize::new(1),
+        };
+        let client = AsyncDrandClient::new(transport, "api.drand.sh", chain.chain_info().clone());
+
+        // paused time skips the sleeps until rounds 2 and 3. Without retries the first
+        // failure reaches the stream.
+        let no_retries = PollSchedule {
+            retry_attempts: 0,
+            ..PollSchedule::default()
+        };
+        let beacons: Vec<_> = client.watch_with(no_retries).take(4).collect().await;
+        assert_eq!(
+            beacons,
+            [
+                Err(DrandClientError::NotResponding),
+                Ok(chain.verified_round(1)),
+                Ok(chain.verified_round(2)),
+                Ok(chain.verified_round(3))
+            ]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn watch_takes_one_or_two_requests_per_round() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        // five seconds into round 10, moving with tokio's paused time
+        let start = time_for_round(chain.chain_info(), 10).unwrap() + Duration::from_secs(5);
+        let clock = TokioClock {
+            start,
+            started: Instant::now(),
+        };
+        let transport = Publishing {
+            inner: chain.transport(140),
+            chain_info: chain.chain_info().clone(),
+            clock,
+            rounds_requested: Mutex::new(Vec::new()),
+        };
+        let client = AsyncDrandClient::new(transport, "api.drand.sh", chain.chain_info().clone())
+            .with_clock(clock);
+
+        let beacons: Vec<_> = client.watch().take(11).collect().await;
+        let expected: Vec<_> = (10..=20)
+            .map(|round| Ok(chain.verified_round(round)))
+            .collect();
+        assert_eq!(beacons, expected);
+
+        // round 11 is fetched before it is published and once more; from then on the publish
+        // delay estimate puts the first fetch after publication
+        let requested = client.transport.rounds_requested.lock().unwrap();
+        let requests_for = |round| requested.iter().filter(|r| **r == round).count();
+        assert_eq!(requests_for(10), 1);
+        assert_eq!(requests_for(11), 2);
+        for round in 12..=20 {
+            assert_eq!(requests_for(round), 1, "round {round}");
+        }
+        assert_eq!(
+            client.publish_delay_estimate(),
+            Some(Duration::from_millis(500))
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn wait_for_round_sleeps_until_the_round_and_retries_once() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let chain = TestChain::new(PedersenBlsUnchained, 3600, now - 10);
+        let client = |failures| {
+            let transport = Flaky {
+                inner: Ready(chain.transport(3)),
+                failures: AtomicUsize::new(failures),
+            };
+            AsyncDrandClient::new(transport, "api.drand.sh", chain.chain_info().clone())
+        };
+
+        let started = Instant::now();
+        assert_eq!(
+            client(1).wait_for_round(2).await,
+            Ok(chain.verified_round(2))
+        );
+        assert!(started.elapsed() >= Duration::from_secs(3590));
+        assert_eq!(
+            client(2).wait_for_round(1).await,
+            Err(DrandClientError::NotResponding)
+        );
+        assert_eq!(
+            client(0).wait_for_round(0).await,
+            Err(DrandClientError::InvalidRound)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn await_round_polls_until_the_round_is_published() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let chain = TestChain::new(PedersenBlsUnchained, 3600, now - 10);
+        let transport = Flaky {
+            inner: Ready(chain.transport(3)),
+            failures: AtomicUsize::new(5),
+        };
+        let client = AsyncDrandClient::new(transport, "api.drand.sh", chain.chain_info().clone());
+
+        assert_eq!(client.await_round(2).await, Ok(chain.verified_round(2)));
+        assert_eq!(client.transport.failures.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn await_round_can_be_raced_against_a_timeout() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let transport = Flaky {
+            inner: Ready(chain.transport(3)),
+            failures: AtomicUsize::new(usize::MAX),
+        };
+        let client = AsyncDrandClient::new(transport, "api.drand.sh", chain.chain_info().clone());
+
+        tokio::select! {
+            result = client.await_round(2) => panic!("round 2 was never published: {result:?}"),
+            _ = tokio::time::sleep(Duration::from_secs(60)) => {}
+        }
+        // the cancelled future left nothing behind, so the client can be used again
+        client.transport.failures.store(0, Ordering::SeqCst);
+        assert_eq!(client.await_round(2).await, Ok(chain.verified_round(2)));
+    }
+
+    #[test]
+    fn watch_is_send() {
+        fn assert_send<S: Send>(_: &S) {}
+
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = AsyncDrandClient::new(
+            AsyncHttpTransport::new(),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+        assert_send(&client.watch());
+    }
+
+    // system time that moves with tokio's clock, so paused time moves it too
+    #[derive(Clone, Copy)]
+    struct TokioClock {
+        start: SystemTime,
+        started: Instant,
+    }
+
+    impl Clock for TokioClock {
+        fn now(&self) -> SystemTime {
+            self.start + self.started.elapsed()
+        }
+
+        fn sleep(&self, _duration: Duration) {
+            unreachable!("the async client sleeps on tokio's timer")
+        }
+    }
+
+    // serves each round 300ms after its scheduled time by `clock`, recording the rounds
+    // requested
+    struct Publishing {
+        inner: TestTransport,
+        chain_info: ChainInfo,
+        clock: TokioClock,
+        rounds_requested: Mutex<Vec<u64>>,
+    }
+
+    impl AsyncTransport for Publishing {
+        async fn fetch(&self, url: &str) -> Result<String, TransportError> {
+            let round: u64 = url.rsplit('/').next().unwrap().parse().unwrap();
+            self.rounds_requested.lock().unwrap().push(round);
+            let published =
+                time_for_round(&self.chain_info, round).unwrap() + Duration::from_millis(300);
+            if self.clock.now() < published {
+                return Err(TransportError::NotFound);
+            }
+            self.inner.fetch(url)
+        }
+    }
+
+    // fails the first `failures` requests as if the relay couldn't be reached
+    struct Flaky {
+        inner: Ready,
+        failures: AtomicUsize,
+    }
+
+    impl AsyncTransport for Flaky {
+        async fn fetch(&self, url: &str) -> Result<String, TransportError> {
+            let failures = self.failures.load(Ordering::SeqCst);
+            if failures > 0 {
+                self.failures.store(failures - 1, Ordering::SeqCst);
+                return Err(TransportError::Unexpected { status_code: None });
+            }
+            self.inner.fetch(url).await
+        }
+    }
+}
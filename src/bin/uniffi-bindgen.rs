@@ -0,0 +1,5 @@
+//! generates the Kotlin and Swift bindings from a `cdylib` build, see the `ffi` module
+
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}
@@ -0,0 +1,370 @@
+//! # builder
+//!
+//! configurable construction of a `DrandClient`, starting from `DrandClient::builder`. By
+//! default the client uses an `HttpTransport` and fetches the chain info from the relay,
+//! like `new_http_client`.
+//!
+
+use crate::chain_info::{ChainHash, ChainInfo};
+use crate::clock::Clock;
+use crate::http::{HttpTransport, HttpTransportBuilder};
+use crate::url::DefaultUrlStrategy;
+use crate::DrandClientError::InvalidChainInfo;
+use crate::{fetch_chain_info_from, DrandClient, DrandClientError, Transport, VerificationPolicy};
+use reqwest::Proxy;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// builds a `DrandClient`. `T` is the transport, or an `HttpTransportBuilder` until a
+/// custom transport is set.
+pub struct DrandClientBuilder<T = HttpTransportBuilder> {
+    transport: T,
+    options: ClientOptions,
+}
+
+// everything but the transport
+struct ClientOptions {
+    base_url: String,
+    chain_info: Option<ChainInfo>,
+    chain_hash: Option<String>,
+    verification: VerificationPolicy,
+    latest_tolerance: u64,
+    latest_cache_disabled: bool,
+    clock: Option<Arc<dyn Clock + Send + Sync>>,
+}
+
+impl DrandClient<HttpTransport> {
+    /// start building a client for `base_url`
+    pub fn builder(base_url: impl Into<String>) -> DrandClientBuilder {
+        DrandClientBuilder {
+            transport: HttpTransportBuilder::new(),
+            options: ClientOptions {
+                base_url: base_url.into(),
+                chain_info: None,
+                chain_hash: None,
+                verification: VerificationPolicy::Full,
+                latest_tolerance: 1,
+                latest_cache_disabled: false,
+                clock: None,
+            },
+        }
+    }
+}
+
+impl<T> DrandClientBuilder<T> {
+    /// use `chain_info` instead of fetching it from the relay. It must already be trusted,
+    /// e.g. because it comes from `pin_chain!`.
+    pub fn chain_info(mut self, chain_info: ChainInfo) -> Self {
+        self.options.chain_info = Some(chain_info);
+        self
+    }
+
+    /// select the chain with the hex-encoded hash `chain_hash` on a relay serving several, by
+    /// requesting `{base_url}/{chain_hash}/...`. Building fails with `ChainHashMismatch` if
+    /// the chain info has a different hash, or `InvalidChainInfo` if `chain_hash` isn't hex.
+    /// A `base_url` that already ends in a chain hash is checked against it without this.
+    pub fn chain_hash(mut self, chain_hash: impl Into<String>) -> Self {
+        self.options.chain_hash = Some(chain_hash.into());
+        self
+    }
+
+    /// which checks the `_unverified` methods run on fetched beacons. Defaults to
+    /// `VerificationPolicy::Full`; e.g. `VerificationPolicy::RandomnessHashOnly` skips the
+    /// pairing on constrained devices, and `VerificationPolicy::None` skips every check.
+    pub fn verification(mut self, verification: VerificationPolicy) -> Self {
+        self.options.verification = verification;
+        self
+    }
+
+    /// how many rounds behind the current one `latest_randomness` accepts. Defaults to one,
+    /// as it takes the relay some time to aggregate a round.
+    pub fn latest_tolerance(mut self, rounds: u64) -> Self {
+        self.options.latest_tolerance = rounds;
+        self
+    }
+
+    /// pass `true` to make `latest_randomness` always ask the relay, rather than returning
+    /// the beacon it fetched last for up to one period
+    pub fn disable_latest_cache(mut self, disabled: bool) -> Self {
+        self.options.latest_cache_disabled = disabled;
+        self
+    }
+
+    /// read the current time from `clock` instead of the system clock, e.g. a
+    /// `testing::FixedClock` in tests
+    pub fn clock(mut self, clock: impl Clock + Send + Sync + 'static) -> Self {
+        self.options.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// use `transport` instead of an `HttpTransport`. HTTP settings made so far are dropped.
+    pub fn transport<U: Transport + Send>(self, transport: U) -> DrandClientBuilder<U> {
+        DrandClientBuilder {
+            transport,
+            options: self.options,
+        }
+    }
+}
+
+impl DrandClientBuilder {
+    /// fail requests that take longer than `timeout` in total
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.transport = self.transport.timeout(timeout);
+        self
+    }
+
+    /// give up on connecting to the relay after `timeout`
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.transport = self.transport.connect_timeout(timeout);
+        self
+    }
+
+    /// send every request through the proxy at `url`. Fails with `InvalidProxy` if `url`
+    /// can't be parsed.
+    pub fn proxy(mut self, url: &str) -> Result<Self, DrandClientError> {
+        let proxy = Proxy::all(url).map_err(|_| DrandClientError::InvalidProxy)?;
+        self.transport = self.transport.proxy(proxy);
+        Ok(self)
+    }
+
+    /// the `User-Agent` header to send
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.transport = self.transport.user_agent(user_agent);
+        self
+    }
+
+    /// configure the HTTP transport further, e.g. with a root certificate
+    pub fn http(
+        mut self,
+        configure: impl FnOnce(HttpTransportBuilder) -> HttpTransportBuilder,
+    ) -> Self {
+        self.transport = configure(self.transport);
+        self
+    }
+
+    /// build the client, fetching the chain info unless it was provided
+    pub fn build(self) -> Result<DrandClient<HttpTransport>, DrandClientError> {
+        self.options.build(self.transport.build())
+    }
+}
+
+impl<T: Transport + Send> DrandClientBuilder<T> {
+    /// build the client, fetching the chain info over the custom transport unless it was
+    /// provided
+    pub fn build(self) -> Result<DrandClient<T>, DrandClientError> {
+        self.options.build(self.transport)
+    }
+}
+
+impl ClientOptions {
+    fn build<T: Transport + Send>(self, transport: T) -> Result<DrandClient<T>, DrandClientError> {
+        let (base_url, expected_hash) = match &self.chain_hash {
+            Some(chain_hash) => (
+                format!("{}/{chain_hash}", self.base_url.trim_end_matches('/')),
+                Some(hex::decode(chain_hash).map_err(|_| InvalidChainInfo)?),
+            ),
+            None => {
+                let expected_hash = hash_in_url(&self.base_url);
+                (self.base_url, expected_hash)
+            }
+        };
+        let chain_info = match self.chain_info {
+            Some(chain_info) => chain_info,
+            None => fetch_chain_info_from(&transport, &base_url, &DefaultUrlStrategy, None)?,
+        };
+        if let Some(expected) = expected_hash {
+            if expected != chain_info.chain_hash {
+                return Err(DrandClientError::ChainHashMismatch {
+                    expected,
+                    actual: chain_info.chain_hash,
+                });
+            }
+        }
+
+        let mut client = DrandClient::new(transport, base_url, chain_info)
+            .disable_latest_cache(self.latest_cache_disabled);
+        client.verification = self.verification;
+        client.latest_tolerance = self.latest_tolerance;
+        if let Some(clock) = self.clock {
+            client.clock = clock;
+        }
+        Ok(client)
+    }
+}
+
+// the chain hash a URL like `https://api.drand.sh/{chain_hash}` selects, if it has one
+fn hash_in_url(base_url: &str) -> Option<Vec<u8>> {
+    let (_, last_segment) = base_url.trim_end_matches('/').rsplit_once('/')?;
+    let hash: ChainHash = last_segment.parse().ok()?;
+    Some(hash.as_bytes().to_vec())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::testing::FixedClock;
+    use crate::testkit::TestChain;
+    use crate::verify::SchemeID::PedersenBlsUnchained;
+    use crate::verify::VerificationError::InvalidRandomness;
+    use crate::{DrandClient, DrandClientError, VerificationPolicy};
+    use std::time::Duration;
+
+    #[test]
+    fn chain_info_is_fetched_over_a_custom_transport() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::builder("api.drand.sh")
+            .timeout(Duration::from_secs(5))
+            .transport(chain.transport(2))
+            .build()?;
+
+        assert_eq!(client.chain_info(), chain.chain_info());
+        assert_eq!(client.randomness(2)?, chain.sign_round(2));
+        Ok(())
+    }
+
+    #[test]
+    fn chain_hash_selects_and_pins_the_chain() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let hash = hex::encode(&chain.chain_info().chain_hash);
+        let client = DrandClient::builder("api.drand.sh/")
+            .transport(chain.transport(2))
+            .chain_hash(&hash)
+            .build()?;
+        assert_eq!(client.base_url, format!("api.drand.sh/{hash}"));
+        assert_eq!(client.randomness(2)?, chain.sign_round(2));
+
+        assert!(matches!(
+            DrandClient::builder("api.drand.sh")
+                .transport(chain.transport(2))
+                .chain_hash("00".repeat(32))
+                .build(),
+            Err(DrandClientError::ChainHashMismatch { expected, .. }) if expected == [0; 32]
+        ));
+        assert!(matches!(
+            DrandClient::builder("api.drand.sh")
+                .transport(chain.transport(2))
+                .chain_hash("zz")
+                .build(),
+            Err(DrandClientError::InvalidChainInfo)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn a_chain_hash_in_the_url_is_checked() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let hash = hex::encode(&chain.chain_info().chain_hash);
+        let client = DrandClient::builder(format!("api.drand.sh/{hash}/"))
+            .transport(chain.transport(2))
+            .build()?;
+        assert_eq!(client.chain_info(), chain.chain_info());
+
+        let other = TestChain::with_seed(PedersenBlsUnchained, 30, 1595431050, b"other");
+        let other = hex::encode(&other.chain_info().chain_hash);
+        assert!(matches!(
+            DrandClient::builder(format!("api.drand.sh/{other}"))
+                .transport(chain.transport(2))
+                .build(),
+            Err(DrandClientError::ChainHashMismatch { actual, .. })
+                if actual == chain.chain_info().chain_hash
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn proxy_urls_are_validated() {
+        assert!(DrandClient::builder("api.drand.sh")
+            .proxy("http://127.0.0.1:3128")
+            .is_ok());
+        assert!(matches!(
+            DrandClient::builder("api.drand.sh").proxy("not a proxy"),
+            Err(DrandClientError::InvalidProxy)
+        ));
+    }
+
+    #[test]
+    fn the_policy_sets_the_checks_of_the_unverified_methods() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let mut corrupted = chain.sign_round(2);
+        corrupted.randomness[0] ^= 1;
+        let client = DrandClient::builder("api.drand.sh")
+            .transport(chain.transport(2).serve_latest(&corrupted))
+            .chain_info(chain.chain_info().clone())
+            .verification(VerificationPolicy::RandomnessHashOnly)
+            .build()?;
+
+        assert_eq!(
+            client.latest_randomness_unverified(),
+            Err(DrandClientError::FailedVerification(InvalidRandomness))
+        );
+        assert_eq!(client.randomness_unverified(1)?, chain.sign_round(1));
+
+        let unchecked = DrandClient::builder("api.drand.sh")
+            .transport(chain.transport(2).serve_latest(&corrupted))
+            .chain_info(chain.chain_info().clone())
+            .verification(VerificationPolicy::None)
+            .build()?;
+        assert_eq!(unchecked.latest_randomness_unverified()?, corrupted);
+
+        let full = DrandClient::builder("api.drand.sh")
+            .transport(chain.transport(2).serve_latest(&corrupted))
+            .chain_info(chain.chain_info().clone())
+            .build()?;
+        assert_eq!(full.verification, VerificationPolicy::Full);
+        Ok(())
+    }
+
+    #[test]
+    fn latest_tolerance_sets_how_far_behind_latest_may_be() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let latest = |tolerance, latest_round| {
+            DrandClient::builder("api.drand.sh")
+                .transport(chain.transport(latest_round))
+                .chain_info(chain.chain_info().clone())
+                .clock(FixedClock::at_round(chain.chain_info(), 10).unwrap())
+                .latest_tolerance(tolerance)
+                .build()
+                .unwrap()
+                .latest_randomness()
+                .map(|beacon| beacon.round_number)
+        };
+
+        assert_eq!(latest(0, 10), Ok(10));
+        assert_eq!(latest(0, 9), Err(DrandClientError::InvalidBeacon));
+        assert_eq!(latest(1, 9), Ok(9));
+        assert_eq!(latest(1, 8), Err(DrandClientError::InvalidBeacon));
+        assert_eq!(latest(5, 5), Ok(5));
+        assert_eq!(latest(5, 4), Err(DrandClientError::InvalidBeacon));
+        assert_eq!(latest(u64::MAX, 1), Ok(1));
+    }
+
+    #[test]
+    fn the_latest_cache_can_be_disabled() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let build = |disabled| {
+            DrandClient::builder("api.drand.sh")
+                .transport(chain.transport(2))
+                .chain_info(chain.chain_info().clone())
+                .disable_latest_cache(disabled)
+                .build()
+        };
+
+        assert!(build(false)?.latest_cache.is_some());
+        assert!(build(true)?.latest_cache.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn stale_latest_beacons_are_rejected_by_default() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::builder("api.drand.sh")
+            .transport(chain.transport(2))
+            .build()
+            .unwrap();
+
+        // round 2 is years old
+        assert_eq!(
+            client.latest_randomness(),
+            Err(DrandClientError::InvalidBeacon)
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! # bundle
+//!
+//! a self-contained JSON artifact holding a beacon together with the chain info needed to
+//! verify it offline, e.g. for handing to an auditor
+//!
+
+use crate::chain_info::ChainInfo;
+use crate::verify::{verify_beacon_ref, Beacon, VerificationError};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// the `format` every bundle carries
+pub const BUNDLE_FORMAT: &str = "drand-beacon-bundle";
+/// the newest bundle version this crate can read and the one it writes
+pub const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Bundle {
+    pub format: String,
+    pub version: u32,
+    pub chain_info: ChainInfo,
+    pub beacon: Beacon,
+}
+
+#[derive(Error, Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum BundleError {
+    #[error("invalid bundle JSON: {0}")]
+    InvalidJson(String),
+    #[error("not a beacon bundle: unknown format {0:?}")]
+    UnknownFormat(String),
+    #[error("unsupported bundle version {0}")]
+    UnsupportedVersion(u32),
+    #[error("the chain info has a zero period or genesis time")]
+    InvalidChainInfo,
+    #[error("the chain hash {} does not match the chain info, which hashes to {}", hex::encode(.claimed), hex::encode(.computed))]
+    ChainHashMismatch { claimed: Vec<u8>, computed: Vec<u8> },
+    #[error("beacon failed verification: {0}")]
+    FailedVerification(VerificationError),
+}
+
+impl Bundle {
+    pub fn new(chain_info: &ChainInfo, beacon: &Beacon) -> Self {
+        Bundle {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            chain_info: chain_info.clone(),
+            beacon: beacon.clone(),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, BundleError> {
+        serde_json::to_string_pretty(self).map_err(|e| BundleError::InvalidJson(e.to_string()))
+    }
+
+    /// parse a bundle, rejecting other formats and versions newer than `BUNDLE_VERSION`.
+    /// The contents are not verified until `verify` is called.
+    pub fn from_json(json: &str) -> Result<Self, BundleError> {
+        let bundle: Bundle =
+            serde_json::from_str(json).map_err(|e| BundleError::InvalidJson(e.to_string()))?;
+        if bundle.format != BUNDLE_FORMAT {
+            return Err(BundleError::UnknownFormat(bundle.format));
+        }
+        if bundle.version == 0 || bundle.version > BUNDLE_VERSION {
+            return Err(BundleError::UnsupportedVersion(bundle.version));
+        }
+        Ok(bundle)
+    }
+
+    /// check that the embedded chain info hashes to its chain hash, then verify the beacon
+    /// against it
+    pub fn verify(&self) -> Result<(), BundleError> {
+        let info = &self.chain_info;
+        if info.period_seconds == 0 || info.genesis_time == 0 {
+            return Err(BundleError::InvalidChainInfo);
+        }
+
+        let computed = info.compute_chain_hash();
+        if computed != info.chain_hash {
+            return Err(BundleError::ChainHashMismatch {
+                claimed: info.chain_hash.clone(),
+                computed,
+            });
+        }
+
+        verify_beacon_ref(&info.scheme_id, &info.public_key, &self.beacon)
+            .map_err(BundleError::FailedVerification)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bundle::{Bundle, BundleError};
+    use crate::testkit::{mainnet_info, mainnet_round_two};
+    use crate::verify::VerificationError;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn bundle_round_trips_and_verifies() -> Result<(), BundleError> {
+        let bundle = Bundle::new(&mainnet_info(), &mainnet_round_two());
+        let parsed = Bundle::from_json(&bundle.to_json()?)?;
+
+        assert_eq!(parsed, bundle);
+        parsed.verify()
+    }
+
+    #[test]
+    fn tampering_with_any_field_fails_verification() {
+        let json = Bundle::new(&mainnet_info(), &mainnet_round_two())
+            .to_json()
+            .unwrap();
+        let tampered_fields = [
+            ("/format", json!("something-else")),
+            ("/version", json!(2)),
+            ("/chain_info/scheme_id", json!("pedersen-bls-unchained")),
+            ("/chain_info/public_key", json!("8d91ae0f4e3cd277cfc46aba26680232b0d5bb4444602cdb23442d62e17f43cdffb1104909e535430c10a6a1ce680a65")),
+            ("/chain_info/chain_hash", json!("52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971")),
+            ("/chain_info/group_hash", json!("f477d5c89f21a17c863a7f937c6a6d15859414d2be09cd448d4279af331c5d3e")),
+            ("/chain_info/genesis_time", json!(1595431051)),
+            ("/chain_info/period_seconds", json!(3)),
+            ("/chain_info/metadata/beacon_id", json!("fastnet")),
+            ("/beacon/round_number", json!(3)),
+            ("/beacon/randomness", json!("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796")),
+            ("/beacon/signature", json!("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539")),
+            ("/beacon/previous_signature", json!("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539")),
+        ];
+
+        for (pointer, value) in tampered_fields {
+            let mut tampered: Value = serde_json::from_str(&json).unwrap();
+            *tampered.pointer_mut(pointer).unwrap() = value;
+            let result = Bundle::from_json(&tampered.to_string()).and_then(|b| b.verify());
+
+            assert!(result.is_err(), "tampering with {pointer} was not detected");
+        }
+    }
+
+    #[test]
+    fn tampered_chain_info_reports_hash_mismatch() {
+        let mut bundle = Bundle::new(&mainnet_info(), &mainnet_round_two());
+        bundle.chain_info.genesis_time += 1;
+
+        assert!(matches!(
+            bundle.verify(),
+            Err(BundleError::ChainHashMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn tampered_beacon_reports_verification_error() {
+        let mut bundle = Bundle::new(&mainnet_info(), &mainnet_round_two());
+        bundle.beacon.round_number = 3;
+
+        assert_eq!(
+            bundle.verify(),
+            Err(BundleError::FailedVerification(
+                VerificationError::SignatureFailedVerification
+            ))
+        );
+    }
+}
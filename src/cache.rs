@@ -0,0 +1,244 @@
+//! # cache
+//!
+//! a cache of the latest beacon, so repeated `latest_randomness` calls within one drand
+//! period don't each hit the relay, and an optional cache of verified beacons by round
+//!
+
+use crate::verify::Beacon;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+/// the most recent beacon returned by `latest_randomness`, together with the chain it belongs
+/// to. An entry is only served for the same chain hash and until a full period has passed
+/// since it was fetched. Times come from the client's `Clock`, and an entry stamped later
+/// than `now`, e.g. after the clock was set back, counts as expired.
+#[derive(Debug, Clone)]
+pub struct LatestBeaconCache {
+    pub last_beacon: Option<Beacon>,
+    pub fetched_at: SystemTime,
+    chain_hash: Vec<u8>,
+}
+
+impl LatestBeaconCache {
+    pub fn new(chain_hash: &[u8], beacon: Beacon, fetched_at: SystemTime) -> Self {
+        LatestBeaconCache {
+            last_beacon: Some(beacon),
+            fetched_at,
+            chain_hash: chain_hash.to_vec(),
+        }
+    }
+
+    /// the cached beacon if it was fetched for `chain_hash` less than `period` before `now`
+    pub fn get(&self, chain_hash: &[u8], period: Duration, now: SystemTime) -> Option<&Beacon> {
+        let expired = now
+            .duration_since(self.fetched_at)
+            .map_or(true, |age| age >= period);
+        if self.chain_hash != chain_hash || expired {
+            return None;
+        }
+        self.last_beacon.as_ref()
+    }
+}
+
+/// up to `capacity` verified beacons by round, evicting the least recently used. A past
+/// round's beacon never changes, so entries don't expire. Lookups, inserts and evictions are
+/// O(1): the entries form a doubly linked list in recency order, stored in a `Vec` and
+/// indexed by round.
+#[derive(Debug, Clone)]
+pub struct RoundCache {
+    capacity: usize,
+    // the position of each cached round in `entries`
+    slots: HashMap<u64, usize>,
+    entries: Vec<Entry>,
+    // the least and the most recently used entries
+    oldest: Option<usize>,
+    newest: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    beacon: Beacon,
+    older: Option<usize>,
+    newer: Option<usize>,
+}
+
+impl RoundCache {
+    pub fn new(capacity: usize) -> Self {
+        RoundCache {
+            capacity,
+            slots: HashMap::new(),
+            entries: Vec::new(),
+            oldest: None,
+            newest: None,
+        }
+    }
+
+    /// the cached beacon of `round`, marking it as recently used
+    pub fn get(&mut self, round: u64) -> Option<&Beacon> {
+        let slot = *self.slots.get(&round)?;
+        self.touch(slot);
+        Some(&self.entries[slot].beacon)
+    }
+
+    /// drop every cached beacon, keeping the capacity
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.entries.clear();
+        self.oldest = None;
+        self.newest = None;
+    }
+
+    pub fn insert(&mut self, beacon: Beacon) {
+        if self.capacity == 0 {
+            return;
+        }
+        let round = beacon.round_number;
+        if let Some(&slot) = self.slots.get(&round) {
+            self.entries[slot].beacon = beacon;
+            self.touch(slot);
+            return;
+        }
+
+        let entry = Entry {
+            beacon,
+            older: None,
+            newer: None,
+        };
+        let slot = match self.oldest {
+            // reuse the evicted entry's slot, so `entries` never outgrows the capacity
+            Some(oldest) if self.entries.len() >= self.capacity => {
+                self.unlink(oldest);
+                self.slots.remove(&self.entries[oldest].beacon.round_number);
+                self.entries[oldest] = entry;
+                oldest
+            }
+            _ => {
+                self.entries.push(entry);
+                self.entries.len() - 1
+            }
+        };
+        self.slots.insert(round, slot);
+        self.push_newest(slot);
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    fn touch(&mut self, slot: usize) {
+        if self.newest != Some(slot) {
+            self.unlink(slot);
+            self.push_newest(slot);
+        }
+    }
+
+    // take the entry at `slot` out of the recency list
+    fn unlink(&mut self, slot: usize) {
+        let Entry { older, newer, .. } = self.entries[slot];
+        match older {
+            Some(older) => self.entries[older].newer = newer,
+            None => self.oldest = newer,
+        }
+        match newer {
+            Some(newer) => self.entries[newer].older = older,
+            None => self.newest = older,
+        }
+    }
+
+    fn push_newest(&mut self, slot: usize) {
+        self.entries[slot].older = self.newest;
+        self.entries[slot].newer = None;
+        match self.newest {
+            Some(newest) => self.entries[newest].newer = Some(slot),
+            None => self.oldest = Some(slot),
+        }
+        self.newest = Some(slot);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cache::{LatestBeaconCache, RoundCache};
+    use crate::verify::Beacon;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    const PERIOD: Duration = Duration::from_secs(30);
+
+    #[test]
+    fn fresh_entry_is_served() {
+        let cache = LatestBeaconCache::new(b"chain", beacon(), UNIX_EPOCH);
+        assert_eq!(
+            cache.get(b"chain", PERIOD, UNIX_EPOCH + Duration::from_secs(29)),
+            Some(&beacon())
+        );
+    }
+
+    #[test]
+    fn entry_expires_after_a_period() {
+        let cache = LatestBeaconCache::new(b"chain", beacon(), UNIX_EPOCH);
+        assert_eq!(cache.get(b"chain", PERIOD, UNIX_EPOCH + PERIOD), None);
+    }
+
+    #[test]
+    fn entry_from_the_future_is_expired() {
+        let cache = LatestBeaconCache::new(b"chain", beacon(), UNIX_EPOCH + PERIOD);
+        assert_eq!(cache.get(b"chain", PERIOD, UNIX_EPOCH), None);
+    }
+
+    #[test]
+    fn entry_is_keyed_by_chain_hash() {
+        let cache = LatestBeaconCache::new(b"chain", beacon(), UNIX_EPOCH);
+        assert_eq!(cache.get(b"other chain", PERIOD, UNIX_EPOCH), None);
+    }
+
+    #[test]
+    fn round_cache_evicts_the_least_recently_used() {
+        let mut cache = RoundCache::new(2);
+        cache.insert(round(1));
+        cache.insert(round(2));
+        assert_eq!(cache.get(1), Some(&round(1)));
+
+        cache.insert(round(3));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(2), None);
+        assert_eq!(cache.get(1), Some(&round(1)));
+        assert_eq!(cache.get(3), Some(&round(3)));
+
+        // replacing an entry refreshes it without growing the cache
+        cache.insert(round(1));
+        cache.insert(round(4));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(3), None);
+        assert_eq!(cache.get(1), Some(&round(1)));
+        assert_eq!(cache.get(4), Some(&round(4)));
+
+        cache.clear();
+        assert!(cache.is_empty());
+        cache.insert(round(5));
+        assert_eq!(cache.get(5), Some(&round(5)));
+
+        let mut disabled = RoundCache::new(0);
+        disabled.insert(round(1));
+        assert!(disabled.is_empty());
+    }
+
+    fn round(round_number: u64) -> Beacon {
+        Beacon {
+            round_number,
+            ..beacon()
+        }
+    }
+
+    fn beacon() -> Beacon {
+        Beacon {
+            round_number: 2,
+            randomness: Vec::new(),
+            signature: Vec::new(),
+            previous_signature: Vec::new(),
+        }
+    }
+}
@@ -1,24 +1,266 @@
 use crate::verify::SchemeID;
-use serde::Deserialize;
+use crate::DrandClientError;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ChainInfo {
-    #[serde(alias = "schemeID")]
+    #[cfg_attr(feature = "serde", serde(alias = "schemeID"))]
     pub scheme_id: SchemeID,
-    #[serde(with = "hex")]
+    #[cfg_attr(feature = "serde", serde(with = "hex"))]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "String", regex(pattern = "^([0-9a-fA-F]{2})*$"))
+    )]
     pub public_key: Vec<u8>,
-    #[serde(with = "hex", alias = "hash")]
+    #[cfg_attr(feature = "serde", serde(with = "hex", alias = "hash"))]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "String", regex(pattern = "^([0-9a-fA-F]{2})*$"))
+    )]
     pub chain_hash: Vec<u8>,
-    #[serde(with = "hex", alias = "groupHash")]
+    #[cfg_attr(feature = "serde", serde(with = "hex", alias = "groupHash"))]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "String", regex(pattern = "^([0-9a-fA-F]{2})*$"))
+    )]
     pub group_hash: Vec<u8>,
     pub genesis_time: u64,
-    #[serde(alias = "period")]
+    #[cfg_attr(feature = "serde", serde(alias = "period"))]
     pub period_seconds: usize,
     pub metadata: ChainInfoMetadata,
 }
 
-#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct ChainInfoMetadata {
-    #[serde(alias = "beaconID")]
+    #[cfg_attr(feature = "serde", serde(alias = "beaconID"))]
     pub beacon_id: String,
 }
+
+/// the 32-byte hash identifying a chain, shown and parsed as lowercase hex the way relays
+/// use it in URLs
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct ChainHash(pub [u8; 32]);
+
+impl ChainHash {
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// whether this is the hash of `chain_info`, as claimed by its `chain_hash`
+    pub fn matches(&self, chain_info: &ChainInfo) -> bool {
+        chain_info.chain_hash == self.0
+    }
+}
+
+impl fmt::Display for ChainHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl FromStr for ChainHash {
+    type Err = hex::FromHexError;
+
+    /// parse 64 hex digits, in either case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bytes = [0; 32];
+        hex::decode_to_slice(s, &mut bytes)?;
+        Ok(ChainHash(bytes))
+    }
+}
+
+impl ChainInfo {
+    /// the time between two rounds
+    pub fn period_as_duration(&self) -> Duration {
+        Duration::from_secs(self.period_seconds as u64)
+    }
+
+    /// the time of round 1. Fails with `InvalidChainInfo` if the genesis time is too far in
+    /// the future for a `SystemTime`.
+    pub fn genesis_time_as_system_time(&self) -> Result<SystemTime, DrandClientError> {
+        UNIX_EPOCH
+            .checked_add(Duration::from_secs(self.genesis_time))
+            .ok_or(DrandClientError::InvalidChainInfo)
+    }
+
+    /// compute the chain hash from the chain parameters the same way drand nodes do:
+    /// `SHA-256(period || genesis_time || public_key || group_hash || beacon_id)`, with the
+    /// integers big-endian and the beacon ID omitted for the default beacon
+    pub fn compute_chain_hash(&self) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update((self.period_seconds as u32).to_be_bytes());
+        hasher.update((self.genesis_time as i64).to_be_bytes());
+        hasher.update(&self.public_key);
+        hasher.update(&self.group_hash);
+        if !self.metadata.beacon_id.is_empty() && self.metadata.beacon_id != "default" {
+            hasher.update(self.metadata.beacon_id.as_bytes());
+        }
+        hasher.finalize().to_vec()
+    }
+
+    /// check that the chain info is self-consistent: its chain hash must be the one computed
+    /// from its parameters, its period and genesis time non-zero, and its genesis time
+    /// representable as a `SystemTime`. Fails with `InvalidChainInfo` otherwise.
+    pub fn validate(&self) -> Result<(), DrandClientError> {
+        // a zero period would make every round calculation divide by zero
+        if self.period_seconds == 0 || self.genesis_time == 0 {
+            return Err(DrandClientError::InvalidChainInfo);
+        }
+        self.genesis_time_as_system_time()?;
+        if self.compute_chain_hash() != self.chain_hash {
+            return Err(DrandClientError::InvalidChainInfo);
+        }
+        Ok(())
+    }
+}
+
+/// e.g. `quicknet: bls-unchained-g1-rfc9380, a round every 3s since 1692803367 (Unix time)`
+impl fmt::Display for ChainInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}, a round every {}s since {} (Unix time)",
+            self.metadata.beacon_id, self.scheme_id, self.period_seconds, self.genesis_time
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chain_info::{ChainHash, ChainInfo, ChainInfoMetadata};
+    use crate::testkit::mainnet_info;
+    use crate::verify::SchemeID;
+    use crate::DrandClientError;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn default_chain_hash_matches() {
+        let info = mainnet_info();
+
+        assert_eq!(info.compute_chain_hash(), info.chain_hash);
+    }
+
+    #[test]
+    fn quicknet_chain_hash_includes_beacon_id() {
+        let info = ChainInfo {
+            scheme_id: SchemeID::UnchainedOnG1RFC9380,
+            public_key: hex::decode("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a").unwrap(),
+            chain_hash: hex::decode("52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971").unwrap(),
+            group_hash: hex::decode("f477d5c89f21a17c863a7f937c6a6d15859414d2be09cd448d4279af331c5d3e").unwrap(),
+            genesis_time: 1692803367,
+            period_seconds: 3,
+            metadata: ChainInfoMetadata {
+                beacon_id: "quicknet".to_string(),
+            },
+        };
+
+        assert_eq!(info.compute_chain_hash(), info.chain_hash);
+    }
+
+    #[test]
+    fn period_and_genesis_are_available_as_times() {
+        let info = mainnet_info();
+
+        assert_eq!(info.period_as_duration(), Duration::from_secs(30));
+        assert_eq!(
+            info.genesis_time_as_system_time(),
+            Ok(UNIX_EPOCH + Duration::from_secs(1595431050))
+        );
+        assert_eq!(
+            info.to_string(),
+            "default: pedersen-bls-chained, a round every 30s since 1595431050 (Unix time)"
+        );
+    }
+
+    #[test]
+    fn chain_hash_must_match_the_parameters() {
+        let mut info = mainnet_info();
+        assert_eq!(info.validate(), Ok(()));
+
+        info.group_hash[0] ^= 1;
+        assert_eq!(info.validate(), Err(DrandClientError::InvalidChainInfo));
+
+        info.chain_hash = info.compute_chain_hash();
+        assert_eq!(info.validate(), Ok(()));
+        info.period_seconds = 0;
+        assert_eq!(info.validate(), Err(DrandClientError::InvalidChainInfo));
+    }
+
+    #[test]
+    fn genesis_times_past_the_end_of_system_time_are_rejected() {
+        let mut info = mainnet_info();
+        info.genesis_time = u64::MAX;
+        // a relay can send a matching hash along with the genesis time
+        info.chain_hash = info.compute_chain_hash();
+
+        assert_eq!(
+            info.genesis_time_as_system_time(),
+            Err(DrandClientError::InvalidChainInfo)
+        );
+        assert_eq!(info.validate(), Err(DrandClientError::InvalidChainInfo));
+    }
+
+    #[test]
+    fn chain_hashes_round_trip_through_hex() {
+        let info = mainnet_info();
+        let hex = "8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce";
+        let hash: ChainHash = hex.to_uppercase().parse().unwrap();
+
+        assert_eq!(hash.to_string(), hex);
+        assert!(hash.matches(&info));
+        assert_eq!(
+            "8990e7".parse::<ChainHash>(),
+            Err(hex::FromHexError::InvalidStringLength)
+        );
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn json_schema_matches_wire_format() {
+        let schema = serde_json::to_value(schemars::schema_for!(ChainInfo)).unwrap();
+
+        let mut properties: Vec<&String> =
+            schema["properties"].as_object().unwrap().keys().collect();
+        properties.sort();
+        assert_eq!(
+            properties,
+            [
+                "chain_hash",
+                "genesis_time",
+                "group_hash",
+                "metadata",
+                "period_seconds",
+                "public_key",
+                "scheme_id"
+            ]
+        );
+        assert_eq!(schema["required"].as_array().unwrap().len(), 7);
+        assert_eq!(schema["properties"]["public_key"]["type"], "string");
+        assert_eq!(
+            schema["properties"]["public_key"]["pattern"],
+            "^([0-9a-fA-F]{2})*$"
+        );
+        assert_eq!(
+            schema["definitions"]["SchemeID"]["enum"],
+            serde_json::json!([
+                "pedersen-bls-chained",
+                "pedersen-bls-unchained",
+                "bls-unchained-g1-rfc9380",
+                "bls-bn254-unchained-on-g1"
+            ])
+        );
+        assert_eq!(
+            schema["definitions"]["ChainInfoMetadata"]["required"],
+            serde_json::json!(["beacon_id"])
+        );
+    }
+}
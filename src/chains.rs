@@ -0,0 +1,101 @@
+//! # chains
+//!
+//! the League of Entropy chains most users want, pinned at compile time, and clients that
+//! check a relay's `/info` against them instead of trusting it
+//!
+
+#[cfg(feature = "http")]
+use crate::http::{new_http_transport, HttpTransport};
+use crate::pin_chain;
+use crate::pinned::PinnedChainInfo;
+#[cfg(feature = "serde")]
+use crate::{fetch_chain_info, DrandClient, DrandClientError, Transport};
+
+/// the relay the well-known chain constructors connect to
+pub const DEFAULT_RELAY: &str = "https://api.drand.sh";
+
+// mainnet's `/info`, as the relays serve it
+pub(crate) const MAINNET_INFO_JSON: &str = r#"{"public_key":"868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31","period":30,"genesis_time":1595431050,"hash":"8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce","groupHash":"176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a","schemeID":"pedersen-bls-chained","metadata":{"beaconID":"default"}}"#;
+
+/// the League of Entropy's original chained chain, with a 30 second period
+pub const MAINNET: PinnedChainInfo = pin_chain!(MAINNET_INFO_JSON);
+
+/// the League of Entropy's unchained chain with signatures on G1, with a 3 second period
+pub const QUICKNET: PinnedChainInfo = pin_chain!(
+    r#"{"public_key":"83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a","period":3,"genesis_time":1692803367,"hash":"52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971","groupHash":"f477d5c89f21a17c863a7f937c6a6d15859414d2be09cd448d4279af331c5d3e","schemeID":"bls-unchained-g1-rfc9380","metadata":{"beaconID":"quicknet"}}"#
+);
+
+#[cfg(feature = "http")]
+impl DrandClient<HttpTransport> {
+    /// a client for the League of Entropy mainnet on `DEFAULT_RELAY`. Fails with
+    /// `ChainInfoMismatch` if the relay's chain info differs from `MAINNET`.
+    pub fn mainnet() -> Result<Self, DrandClientError> {
+        connect_pinned(new_http_transport(), DEFAULT_RELAY, &MAINNET)
+    }
+
+    /// a client for quicknet on `DEFAULT_RELAY`. Fails with `ChainInfoMismatch` if the
+    /// relay's chain info differs from `QUICKNET`.
+    pub fn quicknet() -> Result<Self, DrandClientError> {
+        connect_pinned(new_http_transport(), DEFAULT_RELAY, &QUICKNET)
+    }
+}
+
+/// a client for `pinned` on the relay at `base_url`, addressing the chain by its hash
+#[cfg(feature = "serde")]
+fn connect_pinned<T: Transport + Send>(
+    transport: T,
+    base_url: &str,
+    pinned: &PinnedChainInfo,
+) -> Result<DrandClient<T>, DrandClientError> {
+    let base_url = format!("{base_url}/{}", hex::encode(pinned.chain_hash()));
+    let chain_info = pinned.to_chain_info();
+    if fetch_chain_info(&transport, &base_url)? != chain_info {
+        return Err(DrandClientError::ChainInfoMismatch);
+    }
+    Ok(DrandClient::with_chain_info(
+        transport, base_url, chain_info,
+    ))
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test {
+    use crate::chains::{connect_pinned, MAINNET, QUICKNET};
+    use crate::{DrandClientError, Transport, TransportError};
+
+    const INFO_URL: &str =
+        "https://relay/8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce/info";
+
+    struct InfoTransport(String);
+
+    impl Transport for InfoTransport {
+        fn fetch(&self, url: &str) -> Result<String, TransportError> {
+            match url {
+                INFO_URL => Ok(self.0.clone()),
+                _ => Err(TransportError::NotFound),
+            }
+        }
+    }
+
+    #[test]
+    fn matching_chain_info_is_accepted() -> Result<(), DrandClientError> {
+        let json = serde_json::to_string(&MAINNET.to_chain_info()).unwrap();
+        let client = connect_pinned(InfoTransport(json), "https://relay", &MAINNET)?;
+
+        assert_eq!(client.chain_info(), &MAINNET.to_chain_info());
+        assert_eq!(client.base_url, INFO_URL.trim_end_matches("/info"));
+        Ok(())
+    }
+
+    #[test]
+    fn a_relay_serving_other_chain_info_is_rejected() {
+        let mut info = MAINNET.to_chain_info();
+        info.public_key = QUICKNET.to_chain_info().public_key;
+        info.chain_hash = info.compute_chain_hash();
+        let json = serde_json::to_string(&info).unwrap();
+
+        assert!(matches!(
+            connect_pinned(InfoTransport(json), "https://relay", &MAINNET),
+            Err(DrandClientError::ChainInfoMismatch)
+        ));
+    }
+}
@@ -0,0 +1,336 @@
+//! # chaos
+//!
+//! a transport decorator that injects failures, for testing how code built on the client
+//! copes with an unreliable relay. Faults come from a fixed schedule, then from a seeded
+//! random generator, so a failing run can be reproduced from its seed. Every injected fault
+//! is recorded.
+//!
+
+use crate::clock::{Clock, SystemClock};
+use crate::{Transport, TransportError};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// a failure the transport can inject
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Fault {
+    /// fail with `TransportError::NotFound`, without calling the inner transport
+    NotFound,
+    /// fail with an HTTP 500 `TransportError::Unexpected`, without calling the inner transport
+    Unexpected,
+    /// sleep for the configured timeout, then fail like a request that got no response
+    Timeout,
+    /// cut the inner transport's response short
+    TruncatedBody,
+    /// change one hex digit in the inner transport's response, so it parses but no longer
+    /// verifies
+    CorruptedHex,
+}
+
+/// a fault injected into the request with index `request`, counting from zero
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct InjectedFault {
+    pub request: usize,
+    pub url: String,
+    pub fault: Fault,
+}
+
+/// wraps a transport, injecting faults into its responses
+pub struct ChaosTransport<T: Transport, C: Clock = SystemClock> {
+    inner: T,
+    clock: C,
+    timeout: Duration,
+    probabilities: Vec<(Fault, f64)>,
+    state: Mutex<ChaosState>,
+}
+
+struct ChaosState {
+    rng: SplitMix64,
+    schedule: VecDeque<Option<Fault>>,
+    requests: usize,
+    injected: Vec<InjectedFault>,
+}
+
+impl<T: Transport> ChaosTransport<T> {
+    /// a transport that passes every request through until faults are configured
+    pub fn new(inner: T, seed: u64) -> Self {
+        ChaosTransport::with_clock(inner, seed, SystemClock)
+    }
+}
+
+impl<T: Transport, C: Clock> ChaosTransport<T, C> {
+    /// like `new`, sleeping on `clock` for timeouts
+    pub fn with_clock(inner: T, seed: u64, clock: C) -> Self {
+        ChaosTransport {
+            inner,
+            clock,
+            timeout: Duration::from_secs(10),
+            probabilities: Vec::new(),
+            state: Mutex::new(ChaosState {
+                rng: SplitMix64(seed),
+                schedule: VecDeque::new(),
+                requests: 0,
+                injected: Vec::new(),
+            }),
+        }
+    }
+
+    /// inject `fault` into each request with probability `probability`, once the schedule
+    /// has run out. The probabilities of all faults should add up to at most 1.
+    pub fn with_probability(mut self, fault: Fault, probability: f64) -> Self {
+        self.probabilities.push((fault, probability));
+        self
+    }
+
+    /// inject the faults of `schedule` into the next requests in order, with `None` passing
+    /// a request through
+    pub fn with_schedule(self, schedule: impl IntoIterator<Item = Option<Fault>>) -> Self {
+        self.lock().schedule.extend(schedule);
+        self
+    }
+
+    /// how long `Fault::Timeout` sleeps before failing. Defaults to 10 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// every fault injected so far, in order
+    pub fn injected(&self) -> Vec<InjectedFault> {
+        self.lock().injected.clone()
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ChaosState> {
+        // a panic elsewhere can't leave the state inconsistent
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<T: Transport, C: Clock> Transport for ChaosTransport<T, C> {
+    fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        let (fault, corruption) = {
+            let mut state = self.lock();
+            let request = state.requests;
+            state.requests += 1;
+            let fault = match state.schedule.pop_front() {
+                Some(fault) => fault,
+                None => {
+                    let draw = state.rng.next_f64();
+                    let mut cumulative = 0.0;
+                    self.probabilities.iter().find_map(|&(fault, probability)| {
+                        cumulative += probability;
+                        (draw < cumulative).then_some(fault)
+                    })
+                }
+            };
+            if let Some(fault) = fault {
+                state.injected.push(InjectedFault {
+                    request,
+                    url: url.to_string(),
+                    fault,
+                });
+            }
+            (fault, state.rng.next_u64())
+        };
+
+        match fault {
+            None => self.inner.fetch(url),
+            Some(Fault::NotFound) => Err(TransportError::NotFound),
+            Some(Fault::Unexpected) => Err(TransportError::Unexpected {
+                status_code: Some(500),
+            }),
+            Some(Fault::Timeout) => {
+                self.clock.sleep(self.timeout);
+                Err(TransportError::Unexpected { status_code: None })
+            }
+            Some(Fault::TruncatedBody) => self.inner.fetch(url).map(|mut body| {
+                let mut len = (corruption % (body.len() as u64).max(1)) as usize;
+                while !body.is_char_boundary(len) {
+                    len -= 1;
+                }
+                body.truncate(len);
+                body
+            }),
+            Some(Fault::CorruptedHex) => self
+                .inner
+                .fetch(url)
+                .map(|body| corrupt_hex(&body, corruption)),
+        }
+    }
+}
+
+/// change one hex digit of a hex string of at least 32 digits in `body`, chosen by `choice`
+fn corrupt_hex(body: &str, choice: u64) -> String {
+    let bytes = body.as_bytes();
+    let mut digits = Vec::new();
+    let mut run_start = 0;
+    for i in 0..=bytes.len() {
+        if i < bytes.len() && bytes[i].is_ascii_hexdigit() {
+            continue;
+        }
+        if i - run_start >= 32 {
+            digits.extend(run_start..i);
+        }
+        run_start = i + 1;
+    }
+    if digits.is_empty() {
+        return body.to_string();
+    }
+
+    let mut corrupted = bytes.to_vec();
+    let i = digits[(choice % digits.len() as u64) as usize];
+    corrupted[i] = if corrupted[i] == b'0' { b'1' } else { b'0' };
+    String::from_utf8(corrupted).expect("only an ASCII digit was replaced")
+}
+
+/// the SplitMix64 generator, which is small and plenty for picking faults
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// a uniform float in `[0, 1)`
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chaos::{corrupt_hex, ChaosTransport, Fault, InjectedFault};
+    use crate::clock::Clock;
+    use crate::{Transport, TransportError};
+    use std::cell::RefCell;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    const BODY: &str = r#"{"round":2,"randomness":"e8fee7dac6eb2b89df97d631cfccedbada7d5d05495bb546eef462e4145fdf8f"}"#;
+
+    struct FixedTransport;
+
+    impl Transport for FixedTransport {
+        fn fetch(&self, _url: &str) -> Result<String, TransportError> {
+            Ok(BODY.to_string())
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingClock {
+        sleeps: RefCell<Vec<Duration>>,
+    }
+
+    impl Clock for RecordingClock {
+        fn now(&self) -> SystemTime {
+            UNIX_EPOCH
+        }
+
+        fn sleep(&self, duration: Duration) {
+            self.sleeps.borrow_mut().push(duration);
+        }
+    }
+
+    #[test]
+    fn the_same_seed_injects_the_same_faults() {
+        let run = |seed| {
+            let transport = ChaosTransport::new(FixedTransport, seed)
+                .with_probability(Fault::NotFound, 0.2)
+                .with_probability(Fault::TruncatedBody, 0.2)
+                .with_probability(Fault::CorruptedHex, 0.2);
+            let bodies: Vec<_> = (0..50)
+                .map(|i| transport.fetch(&format!("/public/{i}")).ok())
+                .collect();
+            (bodies, transport.injected())
+        };
+
+        let (bodies, injected) = run(7);
+        assert_eq!(run(7), (bodies.clone(), injected.clone()));
+        assert_ne!(run(8).1, injected);
+        // roughly 60% of 50 requests
+        assert!((15..=45).contains(&injected.len()));
+        assert_eq!(
+            bodies
+                .iter()
+                .filter(|body| body.as_deref() == Some(BODY))
+                .count(),
+            50 - injected.len()
+        );
+    }
+
+    #[test]
+    fn the_schedule_comes_first_and_each_fault_does_what_it_says() {
+        let clock = RecordingClock::default();
+        let transport = ChaosTransport::with_clock(FixedTransport, 1, &clock)
+            .with_timeout(Duration::from_secs(3))
+            .with_schedule([
+                Some(Fault::NotFound),
+                Some(Fault::Unexpected),
+                Some(Fault::Timeout),
+                None,
+                Some(Fault::TruncatedBody),
+                Some(Fault::CorruptedHex),
+            ]);
+
+        assert!(matches!(
+            transport.fetch("a"),
+            Err(TransportError::NotFound)
+        ));
+        assert!(matches!(
+            transport.fetch("b"),
+            Err(TransportError::Unexpected {
+                status_code: Some(500)
+            })
+        ));
+        assert!(matches!(
+            transport.fetch("c"),
+            Err(TransportError::Unexpected { status_code: None })
+        ));
+        assert_eq!(*clock.sleeps.borrow(), [Duration::from_secs(3)]);
+        assert_eq!(transport.fetch("d").unwrap(), BODY);
+        let truncated = transport.fetch("e").unwrap();
+        assert!(truncated.len() < BODY.len() && BODY.starts_with(&truncated));
+        let corrupted = transport.fetch("f").unwrap();
+        assert_eq!(corrupted.len(), BODY.len());
+        assert_ne!(corrupted, BODY);
+        // no probabilities are configured, so requests pass through after the schedule
+        assert_eq!(transport.fetch("g").unwrap(), BODY);
+
+        let injected = transport.injected();
+        assert_eq!(injected.len(), 5);
+        assert_eq!(
+            injected[2],
+            InjectedFault {
+                request: 2,
+                url: "c".to_string(),
+                fault: Fault::Timeout
+            }
+        );
+        assert_eq!(injected[3].request, 4);
+    }
+
+    #[test]
+    fn only_long_hex_strings_are_corrupted() {
+        for choice in 0..64 {
+            let corrupted = corrupt_hex(BODY, choice);
+            assert!(corrupted.starts_with(r#"{"round":2,"randomness":""#));
+            assert_eq!(
+                corrupted
+                    .bytes()
+                    .zip(BODY.bytes())
+                    .filter(|(a, b)| a != b)
+                    .count(),
+                1
+            );
+        }
+        assert_eq!(corrupt_hex(r#"{"round":2}"#, 0), r#"{"round":2}"#);
+    }
+}
@@ -0,0 +1,143 @@
+//! # collection
+//!
+//! a typed, verified local view of a drand chain for applications that store beacons
+//!
+
+use crate::chain_info::ChainInfo;
+use crate::verify::{verify_beacon, Beacon};
+use crate::DrandClientError;
+use std::collections::BTreeMap;
+
+/// a set of beacons for a single chain, ordered by round number. Every beacon is verified
+/// against the chain's scheme and public key before it is stored.
+pub struct BeaconCollection {
+    chain_info: ChainInfo,
+    beacons: BTreeMap<u64, Beacon>,
+}
+
+impl BeaconCollection {
+    /// create an empty collection for the chain described by `chain_info`
+    pub fn new(chain_info: ChainInfo) -> Self {
+        BeaconCollection {
+            chain_info,
+            beacons: BTreeMap::new(),
+        }
+    }
+
+    /// the chain info used to verify beacons in this collection
+    pub fn chain_info(&self) -> &ChainInfo {
+        &self.chain_info
+    }
+
+    /// verify a beacon and insert it into the collection, replacing any beacon already
+    /// stored for the same round
+    pub fn insert(&mut self, beacon: Beacon) -> Result<(), DrandClientError> {
+        let beacon = verify_beacon(
+            &self.chain_info.scheme_id,
+            &self.chain_info.public_key,
+            beacon,
+        )
+        .map_err(DrandClientError::FailedVerification)?
+        .into_inner();
+        self.beacons.insert(beacon.round_number, beacon);
+        Ok(())
+    }
+
+    /// the beacon for a given round, if it has been stored
+    pub fn get(&self, round: u64) -> Option<&Beacon> {
+        self.beacons.get(&round)
+    }
+
+    /// the beacon with the highest round number
+    pub fn latest(&self) -> Option<&Beacon> {
+        self.beacons.values().next_back()
+    }
+
+    /// the stored beacons from `start` to `end` (inclusive) in round order
+    pub fn range(&self, start: u64, end: u64) -> impl Iterator<Item = &Beacon> {
+        self.beacons
+            .range(start..)
+            .take_while(move |(round, _)| **round <= end)
+            .map(|(_, beacon)| beacon)
+    }
+
+    /// the rounds between the lowest and highest stored rounds that have no beacon
+    pub fn missing_rounds(&self) -> Vec<u64> {
+        let (first, last) = match (self.beacons.keys().next(), self.beacons.keys().next_back()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return Vec::new(),
+        };
+
+        (first..=last)
+            .filter(|round| !self.beacons.contains_key(round))
+            .collect()
+    }
+
+    /// the number of stored beacons
+    pub fn len(&self) -> usize {
+        self.beacons.len()
+    }
+
+    /// whether the collection holds no beacons
+    pub fn is_empty(&self) -> bool {
+        self.beacons.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::collection::BeaconCollection;
+    use crate::testkit::{mainnet_info, mainnet_round_two};
+    use crate::verify::VerificationError;
+    use crate::DrandClientError;
+
+    #[test]
+    fn insert_verified_beacon_succeeds() -> Result<(), DrandClientError> {
+        let mut collection = BeaconCollection::new(mainnet_info());
+        collection.insert(mainnet_round_two())?;
+
+        assert_eq!(collection.len(), 1);
+        assert_eq!(collection.get(2), Some(&mainnet_round_two()));
+        assert_eq!(collection.latest(), Some(&mainnet_round_two()));
+        Ok(())
+    }
+
+    #[test]
+    fn insert_invalid_beacon_fails() {
+        let mut collection = BeaconCollection::new(mainnet_info());
+        let mut beacon = mainnet_round_two();
+        beacon.round_number = 3;
+
+        assert_eq!(
+            collection.insert(beacon),
+            Err(DrandClientError::FailedVerification(
+                VerificationError::SignatureFailedVerification
+            ))
+        );
+        assert!(collection.is_empty());
+    }
+
+    #[test]
+    fn range_and_missing_rounds_follow_round_order() {
+        let mut collection = BeaconCollection::new(mainnet_info());
+        // bypass verification so we can build a chain with gaps
+        for round in [2, 3, 5, 8] {
+            let mut beacon = mainnet_round_two();
+            beacon.round_number = round;
+            collection.beacons.insert(round, beacon);
+        }
+
+        let rounds: Vec<u64> = collection.range(3, 7).map(|b| b.round_number).collect();
+        assert_eq!(rounds, vec![3, 5]);
+        assert_eq!(collection.range(7, 3).count(), 0);
+        assert_eq!(collection.missing_rounds(), vec![4, 6, 7]);
+        assert_eq!(collection.latest().map(|b| b.round_number), Some(8));
+    }
+
+    #[test]
+    fn empty_collection_has_no_missing_rounds() {
+        let collection = BeaconCollection::new(mainnet_info());
+        assert!(collection.latest().is_none());
+        assert!(collection.missing_rounds().is_empty());
+    }
+}
@@ -0,0 +1,8 @@
+//! # compat
+//!
+//! adapters for other drand crates, so beacons can be passed between them without manual
+//! byte shuffling
+//!
+
+#[cfg(feature = "drand-verify")]
+pub mod drand_verify;
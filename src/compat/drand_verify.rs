@@ -0,0 +1,214 @@
+//! # drand_verify
+//!
+//! verification through the [`drand-verify`](https://crates.io/crates/drand-verify) crate,
+//! which CosmWasm contracts commonly use. `DrandVerifyKey` wraps its `G1Pubkey` (for the
+//! pedersen schemes) and `G2PubkeyRfc` (for `bls-unchained-g1-rfc9380`).
+//!
+//! Semantic differences from `verify_beacon`, which the adapter papers over:
+//!
+//! - drand-verify only checks the signature: the randomness has to be compared against
+//!   `derive_randomness(signature)` separately. It is derived the same way, as
+//!   `SHA-256(signature)`, and `DrandVerifyKey::verify` checks it.
+//! - drand-verify hashes whatever `previous_signature` it is given into the message, so an
+//!   unchained beacon that carries one fails to verify. The adapter passes an empty
+//!   previous signature for unchained schemes, which is what they sign.
+//! - drand-verify accepts the point at infinity as a public key, under which the infinity
+//!   signature verifies for every round. The adapter rejects it like `verify_beacon` does.
+//! - an invalid signature point is an error in drand-verify rather than `Ok(false)`; both
+//!   become `SignatureFailedVerification`.
+//! - the bn254 scheme has no drand-verify equivalent and is `UnsupportedScheme`.
+//!
+
+use crate::chain_info::ChainInfo;
+use crate::verify::{Beacon, SchemeID, VerificationError};
+use ::drand_verify::{derive_randomness, G1Pubkey, G2PubkeyRfc, Pubkey};
+
+/// a public key in drand-verify's representation, together with whether its scheme is chained
+#[derive(Debug, Clone)]
+pub struct DrandVerifyKey {
+    key: Key,
+    chained: bool,
+}
+
+#[derive(Debug, Clone)]
+enum Key {
+    G1(G1Pubkey),
+    G2Rfc(G2PubkeyRfc),
+}
+
+impl DrandVerifyKey {
+    pub fn new(scheme_id: &SchemeID, public_key: &[u8]) -> Result<Self, VerificationError> {
+        // compressed points at infinity have the compression and infinity bits set, and
+        // nothing else
+        if public_key.first() == Some(&0xc0) && public_key[1..].iter().all(|&b| b == 0) {
+            return Err(VerificationError::InvalidPublicKey);
+        }
+
+        let (key, chained) = match scheme_id {
+            SchemeID::PedersenBlsChained => (Key::G1(g1_pubkey(public_key)?), true),
+            SchemeID::PedersenBlsUnchained => (Key::G1(g1_pubkey(public_key)?), false),
+            SchemeID::UnchainedOnG1RFC9380 => (
+                Key::G2Rfc(
+                    G2PubkeyRfc::from_variable(public_key)
+                        .map_err(|_| VerificationError::InvalidPublicKey)?,
+                ),
+                false,
+            ),
+            SchemeID::Bn254UnchainedOnG1 => return Err(VerificationError::UnsupportedScheme),
+        };
+        Ok(DrandVerifyKey { key, chained })
+    }
+
+    /// verify `beacon` with drand-verify, checking the same things as `verify_beacon`
+    pub fn verify(&self, beacon: &Beacon) -> Result<(), VerificationError> {
+        if derive_randomness(&beacon.signature).as_slice() != beacon.randomness {
+            return Err(VerificationError::InvalidRandomness);
+        }
+        if beacon.signature.is_empty() {
+            return Err(VerificationError::EmptySignature);
+        }
+        if self.chained && beacon.previous_signature.is_empty() {
+            return Err(VerificationError::ChainedBeaconNeedsPreviousSignature);
+        }
+
+        let previous_signature: &[u8] = if self.chained {
+            &beacon.previous_signature
+        } else {
+            &[]
+        };
+        let verified = match &self.key {
+            Key::G1(key) => key.verify(beacon.round_number, previous_signature, &beacon.signature),
+            Key::G2Rfc(key) => {
+                key.verify(beacon.round_number, previous_signature, &beacon.signature)
+            }
+        };
+        match verified {
+            Ok(true) => Ok(()),
+            _ => Err(VerificationError::SignatureFailedVerification),
+        }
+    }
+}
+
+impl TryFrom<&ChainInfo> for DrandVerifyKey {
+    type Error = VerificationError;
+
+    fn try_from(info: &ChainInfo) -> Result<Self, Self::Error> {
+        DrandVerifyKey::new(&info.scheme_id, &info.public_key)
+    }
+}
+
+fn g1_pubkey(public_key: &[u8]) -> Result<G1Pubkey, VerificationError> {
+    G1Pubkey::from_variable(public_key).map_err(|_| VerificationError::InvalidPublicKey)
+}
+
+#[cfg(all(test, any(feature = "energon", feature = "verify-slim")))]
+mod test {
+    use crate::compat::drand_verify::DrandVerifyKey;
+    use crate::testkit::{mainnet_info, mainnet_round_two};
+    use crate::verify::{verify_beacon, Beacon, SchemeID, VerificationError};
+
+    #[test]
+    fn both_crates_agree_on_every_fixture() {
+        for (scheme_id, public_key, beacon) in fixtures() {
+            let ours = verify_beacon(&scheme_id, &public_key, beacon.clone());
+            let theirs =
+                DrandVerifyKey::new(&scheme_id, &public_key).and_then(|key| key.verify(&beacon));
+
+            assert_eq!(
+                ours.is_ok(),
+                theirs.is_ok(),
+                "{scheme_id:?} round {}",
+                beacon.round_number
+            );
+        }
+    }
+
+    #[test]
+    fn valid_fixtures_verify_with_drand_verify() -> Result<(), VerificationError> {
+        for (scheme_id, public_key, beacon) in valid_fixtures() {
+            DrandVerifyKey::new(&scheme_id, &public_key)?.verify(&beacon)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn unchained_beacons_carrying_a_previous_signature_verify() -> Result<(), VerificationError> {
+        let (scheme_id, public_key, mut beacon) = valid_fixtures().remove(1);
+        beacon.previous_signature = beacon.signature.clone();
+
+        verify_beacon(&scheme_id, &public_key, beacon.clone())?;
+        DrandVerifyKey::new(&scheme_id, &public_key)?.verify(&beacon)
+    }
+
+    #[test]
+    fn infinity_public_key_is_rejected() {
+        let mut infinity = vec![0u8; 48];
+        infinity[0] = 0xc0;
+        assert_eq!(
+            DrandVerifyKey::new(&SchemeID::PedersenBlsUnchained, &infinity).unwrap_err(),
+            VerificationError::InvalidPublicKey
+        );
+    }
+
+    #[test]
+    fn bn254_is_unsupported() {
+        assert_eq!(
+            DrandVerifyKey::new(&SchemeID::Bn254UnchainedOnG1, &[0u8; 128]).unwrap_err(),
+            VerificationError::UnsupportedScheme
+        );
+    }
+
+    /// the valid fixtures plus a wrong round, a corrupted signature and a wrong key for each
+    fn fixtures() -> Vec<(SchemeID, Vec<u8>, Beacon)> {
+        let valid = valid_fixtures();
+        let mut fixtures = valid.clone();
+        for (scheme_id, public_key, beacon) in &valid {
+            let mut wrong_round = beacon.clone();
+            wrong_round.round_number += 1;
+            fixtures.push((scheme_id.clone(), public_key.clone(), wrong_round));
+
+            let mut corrupted = beacon.clone();
+            corrupted.signature[10] ^= 1;
+            fixtures.push((scheme_id.clone(), public_key.clone(), corrupted));
+
+            let mut wrong_key = public_key.clone();
+            wrong_key[10] ^= 1;
+            fixtures.push((scheme_id.clone(), wrong_key, beacon.clone()));
+        }
+        fixtures
+    }
+
+    fn valid_fixtures() -> Vec<(SchemeID, Vec<u8>, Beacon)> {
+        vec![
+            (
+                SchemeID::PedersenBlsChained,
+                mainnet_info().public_key,
+                mainnet_round_two(),
+            ),
+            (
+                SchemeID::PedersenBlsUnchained,
+                dehexify("8d91ae0f4e3cd277cfc46aba26680232b0d5bb4444602cdb23442d62e17f43cdffb1104909e535430c10a6a1ce680a65"),
+                Beacon {
+                    round_number: 397092,
+                    randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
+                    signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
+                    previous_signature: Vec::new(),
+                },
+            ),
+            (
+                SchemeID::UnchainedOnG1RFC9380,
+                dehexify("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a"),
+                Beacon {
+                    round_number: 1000,
+                    randomness: dehexify("fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"),
+                    signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
+                    previous_signature: Vec::new(),
+                },
+            ),
+        ]
+    }
+
+    fn dehexify(s: &str) -> Vec<u8> {
+        hex::decode(s).unwrap()
+    }
+}
@@ -0,0 +1,152 @@
+//! # delay
+//!
+//! estimation of how long after its scheduled time a relay publishes each round
+//!
+
+use crate::chain_info::ChainInfo;
+use crate::time_for_round;
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime};
+
+const WINDOW: usize = 9;
+
+/// a smoothed estimate of the relay's publish delay: the median of the most recent samples.
+/// A sample is only taken when a round is seen right after its predecessor, so the observer
+/// was watching the transition rather than catching up. Samples of a full period or more
+/// are discarded as polling lag, and the median keeps single outliers from moving the
+/// estimate.
+#[derive(Debug, Default, Clone)]
+pub struct PublishDelayEstimator {
+    samples: VecDeque<Duration>,
+    last_round: Option<u64>,
+}
+
+impl PublishDelayEstimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// record that `round` was the latest round when observed at `observed_at`
+    pub fn observe(&mut self, chain_info: &ChainInfo, round: u64, observed_at: SystemTime) {
+        if round == 0 || matches!(self.last_round, Some(last) if round <= last) {
+            return;
+        }
+
+        if self.last_round == Some(round - 1) {
+            let period = chain_info.period_as_duration();
+            let scheduled = time_for_round(chain_info, round).ok();
+
+            if let Some(delay) = scheduled.and_then(|s| observed_at.duration_since(s).ok()) {
+                if delay < period {
+                    if self.samples.len() == WINDOW {
+                        self.samples.pop_front();
+                    }
+                    self.samples.push_back(delay);
+                }
+            }
+        }
+
+        self.last_round = Some(round);
+    }
+
+    /// the current estimate, or `None` until a round transition has been observed
+    pub fn estimate(&self) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort();
+        // for an odd number of samples both indices point at the middle sample
+        let len = sorted.len();
+        Some((sorted[(len - 1) / 2] + sorted[len / 2]) / 2)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chain_info::{ChainInfo, ChainInfoMetadata};
+    use crate::delay::PublishDelayEstimator;
+    use crate::time_for_round;
+    use crate::verify::SchemeID;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn no_estimate_without_a_transition() {
+        let info = chain_info();
+        let mut estimator = PublishDelayEstimator::new();
+        assert_eq!(estimator.estimate(), None);
+
+        estimator.observe(&info, 10, scheduled(&info, 10) + Duration::from_millis(300));
+        assert_eq!(estimator.estimate(), None);
+
+        // skipping a round means the transition wasn't watched
+        estimator.observe(&info, 12, scheduled(&info, 12) + Duration::from_millis(300));
+        assert_eq!(estimator.estimate(), None);
+    }
+
+    #[test]
+    fn estimate_converges() {
+        let info = chain_info();
+        let mut estimator = PublishDelayEstimator::new();
+        for round in 1..=20 {
+            let jitter = Duration::from_millis(round % 3 * 10);
+            estimator.observe(
+                &info,
+                round,
+                scheduled(&info, round) + Duration::from_millis(290) + jitter,
+            );
+        }
+
+        let estimate = estimator.estimate().unwrap();
+        assert!(estimate >= Duration::from_millis(290), "{estimate:?}");
+        assert!(estimate <= Duration::from_millis(310), "{estimate:?}");
+    }
+
+    #[test]
+    fn estimate_is_robust_to_outliers() {
+        let info = chain_info();
+        let mut estimator = PublishDelayEstimator::new();
+        for round in 1..=20 {
+            let delay = match round {
+                // a slow response and a fetch that lagged a whole period
+                7 => Duration::from_secs(9),
+                13 => Duration::from_secs(45),
+                _ => Duration::from_millis(300),
+            };
+            estimator.observe(&info, round, scheduled(&info, round) + delay);
+        }
+
+        assert_eq!(estimator.estimate(), Some(Duration::from_millis(300)));
+    }
+
+    #[test]
+    fn repeated_and_older_rounds_are_ignored() {
+        let info = chain_info();
+        let mut estimator = PublishDelayEstimator::new();
+        estimator.observe(&info, 1, scheduled(&info, 1));
+        estimator.observe(&info, 2, scheduled(&info, 2) + Duration::from_millis(200));
+        estimator.observe(&info, 2, scheduled(&info, 2) + Duration::from_secs(20));
+        estimator.observe(&info, 1, scheduled(&info, 2) + Duration::from_secs(20));
+
+        assert_eq!(estimator.estimate(), Some(Duration::from_millis(200)));
+    }
+
+    fn scheduled(info: &ChainInfo, round: u64) -> SystemTime {
+        time_for_round(info, round).unwrap()
+    }
+
+    fn chain_info() -> ChainInfo {
+        ChainInfo {
+            scheme_id: SchemeID::PedersenBlsChained,
+            public_key: Vec::new(),
+            chain_hash: Vec::new(),
+            group_hash: Vec::new(),
+            genesis_time: 1595431050,
+            period_seconds: 30,
+            metadata: ChainInfoMetadata {
+                beacon_id: "default".to_string(),
+            },
+        }
+    }
+}
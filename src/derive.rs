@@ -0,0 +1,91 @@
+//! # derive
+//!
+//! helpers for deriving application randomness from a beacon combined with user entropy.
+//!
+//! `user_entropy` must be committed to (e.g. published or hashed into a contract) before the
+//! beacon's round is emitted. Entropy chosen after the randomness is known can be picked to
+//! steer the output, which defeats the purpose of mixing it in.
+//!
+
+use crate::verify::Beacon;
+use hkdf::Hkdf;
+use sha2::{Digest, Sha256};
+
+/// mix the beacon's randomness with user entropy as `SHA-256(randomness || user_entropy)`
+pub fn mix_randomness(beacon: &Beacon, user_entropy: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(&beacon.randomness);
+    hasher.update(user_entropy);
+    hasher.finalize().into()
+}
+
+/// derive 32 bytes with HKDF-SHA-256, using the beacon's randomness as input key material
+/// and `len(user_entropy) || user_entropy || len(context) || context` as the salt, each
+/// length a big-endian u64 so no two pairs of inputs share a salt. Different `context`s
+/// yield independent outputs for the same beacon and entropy.
+pub fn mix_randomness_hkdf(beacon: &Beacon, user_entropy: &[u8], context: &[u8]) -> [u8; 32] {
+    let mut salt = Vec::with_capacity(16 + user_entropy.len() + context.len());
+    for part in [user_entropy, context] {
+        salt.extend_from_slice(&(part.len() as u64).to_be_bytes());
+        salt.extend_from_slice(part);
+    }
+    let hkdf = Hkdf::<Sha256>::new(Some(&salt), &beacon.randomness);
+    let mut output = [0u8; 32];
+    hkdf.expand(&[], &mut output)
+        .expect("32 bytes is a valid output length for HKDF-SHA-256");
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use crate::derive::{mix_randomness, mix_randomness_hkdf};
+    use crate::verify::Beacon;
+    use sha2::{Digest, Sha256};
+
+    #[test]
+    fn mix_randomness_hashes_randomness_then_entropy() {
+        let beacon = beacon();
+        let expected: [u8; 32] =
+            Sha256::digest([beacon.randomness.as_slice(), b"entropy".as_slice()].concat()).into();
+
+        assert_eq!(mix_randomness(&beacon, b"entropy"), expected);
+        assert_ne!(mix_randomness(&beacon, b"other entropy"), expected);
+    }
+
+    #[test]
+    fn mix_randomness_hkdf_depends_on_every_input() {
+        let beacon = beacon();
+        let output = mix_randomness_hkdf(&beacon, b"entropy", b"lottery");
+
+        assert_eq!(output, mix_randomness_hkdf(&beacon, b"entropy", b"lottery"));
+        assert_ne!(output, mix_randomness_hkdf(&beacon, b"entropy", b"keys"));
+        assert_ne!(output, mix_randomness_hkdf(&beacon, b"other", b"lottery"));
+        assert_ne!(output, mix_randomness(&beacon, b"entropylottery"));
+    }
+
+    #[test]
+    fn mix_randomness_hkdf_keeps_entropy_and_context_apart() {
+        let beacon = beacon();
+
+        assert_ne!(
+            mix_randomness_hkdf(&beacon, b"ab", b"c"),
+            mix_randomness_hkdf(&beacon, b"a", b"bc")
+        );
+        assert_ne!(
+            mix_randomness_hkdf(&beacon, b"entropy", b""),
+            mix_randomness_hkdf(&beacon, b"", b"entropy")
+        );
+    }
+
+    fn beacon() -> Beacon {
+        Beacon {
+            round_number: 2,
+            randomness: hex::decode(
+                "e8fee7dac6eb2b89df97d631cfccedbada7d5d05495bb546eef462e4145fdf8f",
+            )
+            .unwrap(),
+            signature: Vec::new(),
+            previous_signature: Vec::new(),
+        }
+    }
+}
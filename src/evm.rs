@@ -0,0 +1,188 @@
+//! # evm
+//!
+//! helpers for handing `bls-bn254-unchained-on-g1` (evmnet) beacons to EVM smart contracts.
+//! The encodings match the `(uint64 round, uint256[2] signature)` arguments expected by
+//! the drand Solidity verifier.
+//!
+
+use crate::verify::{Beacon, VerificationError};
+use sha3::{Digest, Keccak256};
+
+/// the BN254 base field modulus, big-endian
+const BN254_FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+
+/// a beacon in the representation used on-chain: the round and the G1 signature split into
+/// its `x` and `y` coordinates, each a big-endian `uint256`
+#[derive(Debug, PartialEq, Clone)]
+pub struct EvmBeacon {
+    pub round: u64,
+    pub signature: [[u8; 32]; 2],
+}
+
+impl Beacon {
+    /// convert an evmnet beacon into its on-chain representation. The signature must be an
+    /// uncompressed BN254 G1 point (64 bytes) with both coordinates inside the base field,
+    /// otherwise this fails with `CoordinateOutsideField`.
+    pub fn to_evm_calldata(&self) -> Result<EvmBeacon, VerificationError> {
+        if self.signature.len() != 64 {
+            return Err(VerificationError::InvalidSignatureLength);
+        }
+
+        let mut x = [0u8; 32];
+        let mut y = [0u8; 32];
+        x.copy_from_slice(&self.signature[..32]);
+        y.copy_from_slice(&self.signature[32..]);
+
+        // big-endian byte arrays of equal length compare in numeric order
+        if x >= BN254_FIELD_MODULUS || y >= BN254_FIELD_MODULUS {
+            return Err(VerificationError::CoordinateOutsideField);
+        }
+
+        Ok(EvmBeacon {
+            round: self.round_number,
+            signature: [x, y],
+        })
+    }
+}
+
+impl EvmBeacon {
+    /// the ABI encoding of `(uint64 round, uint256[2] signature)`: three 32-byte words
+    pub fn abi_encode(&self) -> Vec<u8> {
+        let mut encoded = vec![0u8; 24];
+        encoded.extend_from_slice(&self.round.to_be_bytes());
+        encoded.extend_from_slice(&self.signature[0]);
+        encoded.extend_from_slice(&self.signature[1]);
+        encoded
+    }
+
+    /// the ABI encoding as a `0x`-prefixed hex string, ready to append to a function selector
+    pub fn to_calldata_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.abi_encode()))
+    }
+
+    /// the signature coordinates as `0x`-prefixed hex strings, e.g. for passing as `uint256`
+    /// literals to a contract call
+    pub fn signature_hex(&self) -> [String; 2] {
+        [
+            format!("0x{}", hex::encode(self.signature[0])),
+            format!("0x{}", hex::encode(self.signature[1])),
+        ]
+    }
+}
+
+/// the message signed for a given round, computed as the contract would with
+/// `keccak256(abi.encodePacked(uint64(round)))`
+pub fn evm_message_hash(round: u64) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&Keccak256::digest(round.to_be_bytes()));
+    hash
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // the expected calldata is the Solidity ABI encoding of `(uint64, uint256[2])` for
+    // `evmnet_beacon`: every value padded to a 32-byte word, reproducible with
+    // `cast abi-encode "f(uint64,uint256[2])" 1000 "[<x>,<y>]"` from the `signature_hex` values
+    #[test]
+    fn evmnet_beacon_encodes_to_calldata() {
+        let beacon = evmnet_beacon();
+        let evm_beacon = beacon.to_evm_calldata().unwrap();
+
+        assert_eq!(evm_beacon.round, 1000);
+        assert_eq!(
+            evm_beacon.signature_hex(),
+            [
+                "0x06fd5996329504d3a56b482d9222bf7205857d0a9559ddd216ca31a286f6a8cc".to_string(),
+                "0x0a120f021aac2f13553fb164f62bc3a5ca32c76dea88a777b39bcf3cac5fdbd6".to_string(),
+            ]
+        );
+        assert_eq!(
+            evm_beacon.to_calldata_hex(),
+            "0x00000000000000000000000000000000000000000000000000000000000003e806fd5996329504d3a56b482d9222bf7205857d0a9559ddd216ca31a286f6a8cc0a120f021aac2f13553fb164f62bc3a5ca32c76dea88a777b39bcf3cac5fdbd6"
+        );
+    }
+
+    // Solidity's `keccak256(abi.encodePacked(uint64(round)))` hashes the round's 8 big-endian
+    // bytes, so these match e.g. `printf '\x00\x00\x00\x00\x00\x00\x03\xe8' | openssl dgst -keccak-256`
+    #[test]
+    fn message_hash_matches_keccak_of_round() {
+        assert_eq!(
+            hex::encode(evm_message_hash(1)),
+            "6c31fc15422ebad28aaf9089c306702f67540b53c7eea8b7d2941044b027100f"
+        );
+        assert_eq!(
+            hex::encode(evm_message_hash(1000)),
+            "f479a7bd3819aa63bbe476777c509fd59e626fac3d37221509ba4fd41b1459b6"
+        );
+    }
+
+    #[test]
+    fn wrong_signature_length_fails() {
+        let mut beacon = evmnet_beacon();
+        beacon.signature.pop();
+
+        assert_eq!(
+            beacon.to_evm_calldata(),
+            Err(VerificationError::InvalidSignatureLength)
+        );
+    }
+
+    #[test]
+    fn coordinate_outside_field_fails() {
+        let mut beacon = evmnet_beacon();
+        beacon.signature[..32].copy_from_slice(&BN254_FIELD_MODULUS);
+
+        assert_eq!(
+            beacon.to_evm_calldata(),
+            Err(VerificationError::CoordinateOutsideField)
+        );
+    }
+
+    #[test]
+    fn largest_field_element_is_accepted() {
+        let mut beacon = evmnet_beacon();
+        let mut largest = BN254_FIELD_MODULUS;
+        largest[31] -= 1;
+        beacon.signature[32..].copy_from_slice(&largest);
+
+        assert_eq!(
+            beacon
+                .to_evm_calldata()
+                .map(|evm_beacon| evm_beacon.signature[1]),
+            Ok(largest)
+        );
+    }
+
+    // the fixture must be a real evmnet beacon, not just well-formed bytes
+    #[cfg(feature = "energon")]
+    #[test]
+    fn fixture_verifies_against_the_evmnet_key() {
+        let public_key = hex::decode(EVMNET_PUBLIC_KEY).unwrap();
+
+        assert!(crate::verify::verify_beacon(
+            &crate::verify::SchemeID::Bn254UnchainedOnG1,
+            &public_key,
+            evmnet_beacon()
+        )
+        .is_ok());
+    }
+
+    // the group key from the evmnet chain info and its round 1000, both as served by the
+    // drand relays, e.g. api.drand.sh, for the beacon id `evmnet`. The test above checks that
+    // the beacon verifies against the key.
+    const EVMNET_PUBLIC_KEY: &str = "07e1d1d335df83fa98462005690372c643340060d205306a9aa8106b6bd0b3820557ec32c2ad488e4d4f6008f89a346f18492092ccc0d594610de2732c8b808f0095685ae3a85ba243747b1b2f426049010f6b73a0cf1d389351d5aaaa1047f6297d3a4f9749b33eb2d904c9d9ebf17224150ddd7abd7567a9bec6c74480ee0b";
+
+    fn evmnet_beacon() -> Beacon {
+        Beacon {
+            round_number: 1000,
+            randomness: hex::decode("0e6745667465a6f9dce5d5f994656955080be14c469ff17fc4fc588c925a8504").unwrap(),
+            signature: hex::decode("06fd5996329504d3a56b482d9222bf7205857d0a9559ddd216ca31a286f6a8cc0a120f021aac2f13553fb164f62bc3a5ca32c76dea88a777b39bcf3cac5fdbd6").unwrap(),
+            previous_signature: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! # ffi
+//!
+//! a small object model for Kotlin and Swift, exported with uniffi's proc-macros. Bindings
+//! are generated from a `cdylib` build with the bundled `uniffi-bindgen` binary:
+//!
+//! ```sh
+//! cargo rustc --release --lib --features uniffi --crate-type cdylib
+//! cargo run --features uniffi --bin uniffi-bindgen -- generate --library \
+//!     target/release/libdrand_client_rs.so --language kotlin --out-dir out
+//! ```
+//!
+//! Hex arguments accept either case, and every failure surfaces as a `DrandError`, which
+//! becomes `DrandException` in Kotlin and a thrown `DrandError` in Swift.
+//!
+
+use crate::http::HttpTransport;
+use crate::verify::{verify_beacon, Beacon, SchemeID, VerifiedBeacon};
+use crate::{new_http_client, DrandClient};
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, uniffi::Error)]
+pub enum DrandError {
+    #[error("unknown scheme {scheme:?}")]
+    UnknownScheme { scheme: String },
+    #[error("{field} is not valid hex")]
+    InvalidHex { field: String },
+    #[error("beacon failed verification: {reason}")]
+    FailedVerification { reason: String },
+    #[error("request failed: {reason}")]
+    RequestFailed { reason: String },
+}
+
+/// a beacon with its byte fields hex-encoded
+#[derive(Debug, PartialEq, Clone, uniffi::Record)]
+pub struct DrandBeacon {
+    pub round: u64,
+    pub randomness_hex: String,
+    pub signature_hex: String,
+    pub previous_signature_hex: String,
+}
+
+impl From<Beacon> for DrandBeacon {
+    fn from(beacon: Beacon) -> Self {
+        DrandBeacon {
+            round: beacon.round_number,
+            randomness_hex: hex::encode(beacon.randomness),
+            signature_hex: hex::encode(beacon.signature),
+            previous_signature_hex: hex::encode(beacon.previous_signature),
+        }
+    }
+}
+
+/// verifies beacons of a single chain on-device
+#[derive(Debug, uniffi::Object)]
+pub struct DrandVerifier {
+    scheme_id: SchemeID,
+    public_key: Vec<u8>,
+}
+
+#[uniffi::export]
+impl DrandVerifier {
+    /// `scheme` is the chain info's scheme ID, e.g. `"bls-unchained-g1-rfc9380"`
+    #[uniffi::constructor]
+    pub fn new(scheme: String, public_key_hex: String) -> Result<Arc<Self>, DrandError> {
+        let scheme_id = scheme
+            .parse()
+            .map_err(|_| DrandError::UnknownScheme { scheme })?;
+        Ok(Arc::new(DrandVerifier {
+            scheme_id,
+            public_key: decode("public_key_hex", &public_key_hex)?,
+        }))
+    }
+
+    /// `previous_signature_hex` is empty for unchained schemes
+    pub fn verify(
+        &self,
+        round: u64,
+        signature_hex: String,
+        previous_signature_hex: String,
+        randomness_hex: String,
+    ) -> Result<(), DrandError> {
+        let beacon = Beacon {
+            round_number: round,
+            randomness: decode("randomness_hex", &randomness_hex)?,
+            signature: decode("signature_hex", &signature_hex)?,
+            previous_signature: decode("previous_signature_hex", &previous_signature_hex)?,
+        };
+        verify_beacon(&self.scheme_id, &self.public_key, beacon)
+            .map(|_| ())
+            .map_err(|e| DrandError::FailedVerification {
+                reason: e.to_string(),
+            })
+    }
+}
+
+/// fetches verified beacons from a relay over HTTP
+#[derive(uniffi::Object)]
+pub struct DrandHttpClient {
+    client: DrandClient<HttpTransport>,
+}
+
+#[uniffi::export]
+impl DrandHttpClient {
+    #[uniffi::constructor]
+    pub fn new(base_url: String) -> Result<Arc<Self>, DrandError> {
+        let client = new_http_client(base_url).map_err(request_failed)?;
+        Ok(Arc::new(DrandHttpClient { client }))
+    }
+
+    pub fn latest(&self) -> Result<DrandBeacon, DrandError> {
+        self.client
+            .latest_randomness()
+            .map(VerifiedBeacon::into_inner)
+            .map(DrandBeacon::from)
+            .map_err(request_failed)
+    }
+
+    pub fn round(&self, round: u64) -> Result<DrandBeacon, DrandError> {
+        self.client
+            .randomness(round)
+            .map(VerifiedBeacon::into_inner)
+            .map(DrandBeacon::from)
+            .map_err(request_failed)
+    }
+}
+
+fn decode(field: &str, hex: &str) -> Result<Vec<u8>, DrandError> {
+    hex::decode(hex).map_err(|_| DrandError::InvalidHex {
+        field: field.to_string(),
+    })
+}
+
+fn request_failed(e: crate::DrandClientError) -> DrandError {
+    DrandError::RequestFailed {
+        reason: e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ffi::{DrandError, DrandVerifier};
+
+    const PUBLIC_KEY: &str = "83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a";
+    const SIGNATURE: &str = "b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39";
+    const RANDOMNESS: &str = "fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd";
+
+    #[cfg(any(feature = "energon", feature = "verify-slim"))]
+    #[test]
+    fn verifier_accepts_a_quicknet_beacon() -> Result<(), DrandError> {
+        let verifier = DrandVerifier::new(
+            "bls-unchained-g1-rfc9380".to_string(),
+            PUBLIC_KEY.to_uppercase(),
+        )?;
+        verifier.verify(
+            1000,
+            SIGNATURE.to_string(),
+            String::new(),
+            RANDOMNESS.to_string(),
+        )
+    }
+
+    #[test]
+    fn verifier_errors_map_to_drand_errors() {
+        assert_eq!(
+            DrandVerifier::new("bls-unknown".to_string(), PUBLIC_KEY.to_string()).unwrap_err(),
+            DrandError::UnknownScheme {
+                scheme: "bls-unknown".to_string()
+            }
+        );
+        assert_eq!(
+            DrandVerifier::new("bls-unchained-g1-rfc9380".to_string(), "zz".to_string())
+                .unwrap_err(),
+            DrandError::InvalidHex {
+                field: "public_key_hex".to_string()
+            }
+        );
+
+        let verifier = DrandVerifier::new(
+            "bls-unchained-g1-rfc9380".to_string(),
+            PUBLIC_KEY.to_string(),
+        )
+        .unwrap();
+        assert!(matches!(
+            verifier.verify(
+                1001,
+                SIGNATURE.to_string(),
+                String::new(),
+                RANDOMNESS.to_string()
+            ),
+            Err(DrandError::FailedVerification { .. })
+        ));
+    }
+
+    #[cfg(feature = "uniffi-bindgen-test")]
+    #[test]
+    fn kotlin_and_swift_bindings_generate() {
+        // the proc-macros embed the interface metadata in every artifact, including this
+        // test binary, so bindings can be generated from it like from the cdylib
+        let library = std::env::current_exe().unwrap();
+        let library = camino::Utf8PathBuf::from_path_buf(library).unwrap();
+        let out_dir =
+            camino::Utf8PathBuf::from_path_buf(std::env::temp_dir().join("drand-client-rs-uniffi"))
+                .unwrap();
+
+        uniffi_bindgen::library_mode::generate_bindings(
+            &library,
+            Some("drand_client_rs".to_string()),
+            &uniffi_bindgen::bindings::KotlinBindingGenerator,
+            &uniffi_bindgen::EmptyCrateConfigSupplier,
+            None,
+            &out_dir,
+            false,
+        )
+        .unwrap();
+        uniffi_bindgen::library_mode::generate_bindings(
+            &library,
+            Some("drand_client_rs".to_string()),
+            &uniffi_bindgen::bindings::SwiftBindingGenerator,
+            &uniffi_bindgen::EmptyCrateConfigSupplier,
+            None,
+            &out_dir,
+            false,
+        )
+        .unwrap();
+
+        let kotlin =
+            std::fs::read_to_string(out_dir.join("uniffi/drand_client_rs/drand_client_rs.kt"))
+                .unwrap();
+        assert!(kotlin.contains("class DrandVerifier"));
+        assert!(kotlin.contains("class DrandHttpClient"));
+        assert!(kotlin.contains("DrandException"));
+        let swift = std::fs::read_to_string(out_dir.join("drand_client_rs.swift")).unwrap();
+        assert!(swift.contains("class DrandVerifier"));
+        assert!(swift.contains("enum DrandError"));
+    }
+}
@@ -0,0 +1,195 @@
+//! # filecoin
+//!
+//! mapping between Filecoin epochs and drand rounds, following the rules Lotus uses since
+//! network version 16 (`maxBeaconRoundV2` in `chain/beacon/drand`).
+//!
+//! The beacon for an epoch is the latest round available at the *start of the previous
+//! epoch*, `filecoin_genesis + (epoch - 1) * 30`, which leaves a full epoch for the round to
+//! propagate. Epochs whose timestamp is before the drand genesis use round 1.
+//!
+//! The mapping only depends on the epoch number, so null rounds (epochs without a block)
+//! don't shift it: a block that follows null rounds simply spans more drand rounds, see
+//! `rounds_since_parent`.
+//!
+
+use crate::chain_info::ChainInfo;
+use crate::DrandClientError;
+use std::ops::RangeInclusive;
+
+/// the duration of a Filecoin epoch, on mainnet and calibration net alike
+pub const EPOCH_DURATION_SECONDS: u64 = 30;
+
+/// the drand round Lotus uses for `epoch`
+pub fn round_for_epoch(
+    chain_info: &ChainInfo,
+    filecoin_genesis: u64,
+    epoch: u64,
+) -> Result<u64, DrandClientError> {
+    let period = period(chain_info)?;
+    // the start of the previous epoch, which Lotus lets wrap for epoch 0 with a genesis
+    // before the first 30 seconds of the Unix epoch; we clamp it to 0 instead
+    let latest_timestamp =
+        (epoch * EPOCH_DURATION_SECONDS + filecoin_genesis).saturating_sub(EPOCH_DURATION_SECONDS);
+    if latest_timestamp < chain_info.genesis_time {
+        return Ok(1);
+    }
+
+    // round 1 starts at genesis, so we add 1
+    Ok((latest_timestamp - chain_info.genesis_time) / period + 1)
+}
+
+/// the first epoch whose drand round is `round` or later: the inverse of `round_for_epoch`.
+/// With a drand period shorter than an epoch, most rounds are skipped by the mapping, and
+/// every round up to `round_for_epoch(.., 0)` maps to epoch 0.
+pub fn epoch_for_round(
+    chain_info: &ChainInfo,
+    filecoin_genesis: u64,
+    round: u64,
+) -> Result<u64, DrandClientError> {
+    let period = period(chain_info)?;
+    // every epoch before the drand genesis is clamped to round 1
+    if round <= 1 {
+        return Ok(0);
+    }
+
+    // the start of `round`, and the epoch has to start one epoch later than that
+    let round_start = chain_info.genesis_time + (round - 1) * period;
+    let earliest_start = round_start + EPOCH_DURATION_SECONDS;
+    if earliest_start <= filecoin_genesis {
+        return Ok(0);
+    }
+
+    Ok((earliest_start - filecoin_genesis).div_ceil(EPOCH_DURATION_SECONDS))
+}
+
+/// the drand rounds a block at `epoch` covers when its parent is at `parent_epoch`, i.e.
+/// every round after the parent's. Null rounds between the two widen the range; an empty
+/// range means the block carries no new beacon.
+pub fn rounds_since_parent(
+    chain_info: &ChainInfo,
+    filecoin_genesis: u64,
+    parent_epoch: u64,
+    epoch: u64,
+) -> Result<RangeInclusive<u64>, DrandClientError> {
+    let parent_round = round_for_epoch(chain_info, filecoin_genesis, parent_epoch)?;
+    let round = round_for_epoch(chain_info, filecoin_genesis, epoch)?;
+    Ok(parent_round + 1..=round)
+}
+
+fn period(chain_info: &ChainInfo) -> Result<u64, DrandClientError> {
+    match chain_info.period_seconds {
+        0 => Err(DrandClientError::InvalidChainInfo),
+        period => Ok(period as u64),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chain_info::{ChainInfo, ChainInfoMetadata};
+    use crate::filecoin::{epoch_for_round, round_for_epoch, rounds_since_parent};
+    use crate::verify::SchemeID;
+    use crate::DrandClientError;
+
+    const FILECOIN_MAINNET_GENESIS: u64 = 1598306400;
+
+    #[test]
+    fn mainnet_epochs_map_to_default_chain_rounds() -> Result<(), DrandClientError> {
+        let info = chain_info(1595431050, 30);
+        for (epoch, round) in [(0, 95845), (1, 95846), (51000, 146845), (1000000, 1095845)] {
+            assert_eq!(
+                round_for_epoch(&info, FILECOIN_MAINNET_GENESIS, epoch)?,
+                round,
+                "epoch {epoch}"
+            );
+            assert_eq!(
+                epoch_for_round(&info, FILECOIN_MAINNET_GENESIS, round)?,
+                epoch,
+                "round {round}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn mainnet_epochs_map_to_quicknet_rounds() -> Result<(), DrandClientError> {
+        let info = chain_info(1692803367, 3);
+        // the first epoch on quicknet
+        assert_eq!(
+            round_for_epoch(&info, FILECOIN_MAINNET_GENESIS, 3855480)?,
+            7055802
+        );
+        assert_eq!(
+            round_for_epoch(&info, FILECOIN_MAINNET_GENESIS, 3855481)?,
+            7055812
+        );
+        // rounds in between belong to the next epoch
+        assert_eq!(
+            epoch_for_round(&info, FILECOIN_MAINNET_GENESIS, 7055802)?,
+            3855480
+        );
+        assert_eq!(
+            epoch_for_round(&info, FILECOIN_MAINNET_GENESIS, 7055803)?,
+            3855481
+        );
+        assert_eq!(
+            epoch_for_round(&info, FILECOIN_MAINNET_GENESIS, 7055812)?,
+            3855481
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn epochs_before_drand_genesis_use_round_one() -> Result<(), DrandClientError> {
+        let info = chain_info(1692803367, 3);
+        assert_eq!(round_for_epoch(&info, FILECOIN_MAINNET_GENESIS, 0)?, 1);
+        assert_eq!(epoch_for_round(&info, FILECOIN_MAINNET_GENESIS, 1)?, 0);
+
+        // a chain starting exactly one epoch after the Filecoin genesis
+        let info = chain_info(FILECOIN_MAINNET_GENESIS + 30, 30);
+        assert_eq!(round_for_epoch(&info, FILECOIN_MAINNET_GENESIS, 1)?, 1);
+        assert_eq!(round_for_epoch(&info, FILECOIN_MAINNET_GENESIS, 2)?, 1);
+        assert_eq!(round_for_epoch(&info, FILECOIN_MAINNET_GENESIS, 3)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn null_rounds_widen_the_rounds_covered() -> Result<(), DrandClientError> {
+        let info = chain_info(1692803367, 3);
+        let genesis = FILECOIN_MAINNET_GENESIS;
+
+        assert_eq!(
+            rounds_since_parent(&info, genesis, 3855480, 3855481)?,
+            7055803..=7055812
+        );
+        // two null rounds between the parent and the block
+        assert_eq!(
+            rounds_since_parent(&info, genesis, 3855480, 3855483)?,
+            7055803..=7055832
+        );
+        // both epochs are before the drand genesis
+        assert!(rounds_since_parent(&info, genesis, 0, 1)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn zero_period_is_invalid() {
+        assert_eq!(
+            round_for_epoch(&chain_info(1595431050, 0), FILECOIN_MAINNET_GENESIS, 1),
+            Err(DrandClientError::InvalidChainInfo)
+        );
+    }
+
+    fn chain_info(genesis_time: u64, period_seconds: usize) -> ChainInfo {
+        ChainInfo {
+            scheme_id: SchemeID::PedersenBlsChained,
+            public_key: Vec::new(),
+            chain_hash: Vec::new(),
+            group_hash: Vec::new(),
+            genesis_time,
+            period_seconds,
+            metadata: ChainInfoMetadata {
+                beacon_id: "default".to_string(),
+            },
+        }
+    }
+}
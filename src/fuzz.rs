@@ -0,0 +1,166 @@
+//! # fuzz
+//!
+//! `arbitrary::Arbitrary` implementations for the wire types, for fuzzing code that handles
+//! beacons and chain info. The generators are biased toward the shapes that reach the
+//! interesting branches: byte fields of the exact lengths the schemes use, one byte off,
+//! empty or of any length, randomness that matches the signature, and boundary rounds.
+//!
+
+use crate::chain_info::{ChainInfo, ChainInfoMetadata};
+use crate::verify::{Beacon, SchemeID};
+use arbitrary::{Arbitrary, Result, Unstructured};
+use sha2::{Digest, Sha256};
+
+/// the public key sizes of the known schemes
+const PUBLIC_KEY_LENGTHS: [usize; 3] = [48, 96, 128];
+/// the signature sizes of the known schemes
+const SIGNATURE_LENGTHS: [usize; 3] = [48, 64, 96];
+const HASH_LENGTH: usize = 32;
+
+impl<'a> Arbitrary<'a> for SchemeID {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(u.choose(&[
+            SchemeID::PedersenBlsChained,
+            SchemeID::PedersenBlsUnchained,
+            SchemeID::UnchainedOnG1RFC9380,
+            SchemeID::Bn254UnchainedOnG1,
+        ])?
+        .clone())
+    }
+}
+
+impl<'a> Arbitrary<'a> for Beacon {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let round_number = round(u)?;
+        let signature = bytes(u, &SIGNATURE_LENGTHS)?;
+        let previous_signature = bytes(u, &SIGNATURE_LENGTHS)?;
+        // randomness that doesn't hash from the signature is rejected before any curve
+        // arithmetic, so most beacons get a matching one
+        let randomness = if u.ratio(3, 4)? {
+            Sha256::digest(&signature).to_vec()
+        } else {
+            bytes(u, &[HASH_LENGTH])?
+        };
+
+        Ok(Beacon {
+            round_number,
+            randomness,
+            signature,
+            previous_signature,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for ChainInfoMetadata {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let beacon_id = match u.int_in_range(0..=3)? {
+            0 => "default".to_string(),
+            1 => "quicknet".to_string(),
+            2 => String::new(),
+            _ => String::arbitrary(u)?,
+        };
+        Ok(ChainInfoMetadata { beacon_id })
+    }
+}
+
+impl<'a> Arbitrary<'a> for ChainInfo {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(ChainInfo {
+            scheme_id: SchemeID::arbitrary(u)?,
+            public_key: bytes(u, &PUBLIC_KEY_LENGTHS)?,
+            chain_hash: bytes(u, &[HASH_LENGTH])?,
+            group_hash: bytes(u, &[HASH_LENGTH])?,
+            genesis_time: *u.choose(&[0, 1, 1595431050, 1692803367, u64::MAX])?,
+            period_seconds: match u.int_in_range(0..=3)? {
+                0 => 0,
+                1 => 3,
+                2 => 30,
+                _ => usize::arbitrary(u)?,
+            },
+            metadata: ChainInfoMetadata::arbitrary(u)?,
+        })
+    }
+}
+
+fn round(u: &mut Unstructured) -> Result<u64> {
+    match u.int_in_range(0..=3)? {
+        0 => Ok(0),
+        1 => Ok(1),
+        2 => Ok(u64::MAX),
+        _ => u64::arbitrary(u),
+    }
+}
+
+/// bytes that are one of the `lengths`, one byte off from one, empty, or any length
+fn bytes(u: &mut Unstructured, lengths: &[usize]) -> Result<Vec<u8>> {
+    let len = match u.int_in_range(0..=3)? {
+        0 => *u.choose(lengths)?,
+        1 => {
+            let len = *u.choose(lengths)?;
+            if bool::arbitrary(u)? {
+                len + 1
+            } else {
+                len - 1
+            }
+        }
+        2 => 0,
+        _ => u.arbitrary_len::<u8>()?,
+    };
+    Ok(u.bytes(len)?.to_vec())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chain_info::ChainInfo;
+    use crate::fuzz::SIGNATURE_LENGTHS;
+    use crate::round_for_epoch_seconds;
+    use crate::verify::{verify_beacon, verify_beacon_autodetect, Beacon, SchemeID};
+    use arbitrary::{Arbitrary, Unstructured};
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use sha2::{Digest, Sha256};
+
+    const ALL_SCHEMES: [SchemeID; 4] = [
+        SchemeID::PedersenBlsChained,
+        SchemeID::PedersenBlsUnchained,
+        SchemeID::UnchainedOnG1RFC9380,
+        SchemeID::Bn254UnchainedOnG1,
+    ];
+
+    proptest! {
+        #[test]
+        fn verify_beacon_never_panics(data in vec(any::<u8>(), 0..1024)) {
+            let mut u = Unstructured::new(&data);
+            if let (Ok(info), Ok(beacon)) = (ChainInfo::arbitrary(&mut u), Beacon::arbitrary(&mut u)) {
+                for scheme_id in &ALL_SCHEMES {
+                    let _ = verify_beacon(scheme_id, &info.public_key, beacon.clone());
+                }
+                let _ = verify_beacon_autodetect(&info.public_key, &beacon);
+            }
+        }
+
+        #[test]
+        fn round_for_epoch_seconds_never_panics(data in vec(any::<u8>(), 0..256), epoch_seconds: u64) {
+            let mut u = Unstructured::new(&data);
+            if let Ok(info) = ChainInfo::arbitrary(&mut u) {
+                let _ = round_for_epoch_seconds(&info, epoch_seconds);
+            }
+        }
+    }
+
+    #[test]
+    fn generated_beacons_are_biased_toward_valid_shapes() {
+        let data: Vec<u8> = (0..=255).cycle().take(1 << 16).collect();
+        let mut u = Unstructured::new(&data);
+        let beacons: Vec<Beacon> = (0..64)
+            .filter_map(|_| Beacon::arbitrary(&mut u).ok())
+            .collect();
+
+        assert!(beacons
+            .iter()
+            .any(|beacon| Sha256::digest(&beacon.signature).to_vec() == beacon.randomness));
+        assert!(beacons
+            .iter()
+            .any(|beacon| SIGNATURE_LENGTHS.contains(&beacon.signature.len())));
+    }
+}
@@ -5,34 +5,837 @@
 
 use crate::{Transport, TransportError};
 use reqwest::blocking::Client;
+use reqwest::{Certificate, Proxy};
+use std::error::Error;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
 
 use reqwest::StatusCode;
 
+// re-exported from their old home here
+pub use crate::retry::{retry, RetryConfig};
+
+/// a clone shares the connection pool, but rebuilds its client on its own
+#[derive(Clone)]
 pub struct HttpTransport {
-    pub client: Client,
+    client: SelfHealing<Client, reqwest::Error>,
+    retry: Option<RetryConfig>,
+}
+
+impl HttpTransport {
+    /// an HTTP transport using clients created by `factory`, or the factory's error if the
+    /// first client can't be created. The factory is called again every time self-healing
+    /// rebuilds the client, and a rebuild that fails keeps the previous client.
+    pub fn with_client_factory(
+        factory: impl Fn() -> Result<Client, reqwest::Error> + Send + Sync + 'static,
+    ) -> Result<Self, reqwest::Error> {
+        Ok(HttpTransport {
+            client: SelfHealing::new(factory)?,
+            retry: None,
+        })
+    }
+
+    /// retry requests that fail with `TransportError::Unexpected` according to `config`,
+    /// backing off exponentially with jitter. A `NotFound` is never retried. By default
+    /// failures are returned straight away.
+    pub fn with_retry(mut self, config: RetryConfig) -> Self {
+        self.retry = Some(config);
+        self
+    }
+
+    /// rebuild the inner `Client`, with a fresh connection pool and DNS cache, after
+    /// persistent connection failures. By default the client is never rebuilt.
+    pub fn with_self_healing(mut self, config: SelfHealingConfig) -> Self {
+        self.client = self.client.with_config(config);
+        self
+    }
+
+    /// call `observer` every time the inner client is rebuilt
+    pub fn on_client_rebuild(
+        mut self,
+        observer: impl Fn(&RebuildEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.client = self.client.on_rebuild(observer);
+        self
+    }
+
+    /// an HTTP transport that fails requests taking longer than `timeout` in total
+    pub fn with_timeout(timeout: Duration) -> Self {
+        HttpTransportBuilder::new().timeout(timeout).build()
+    }
+
+    /// an HTTP transport that gives up on connecting to a relay after `timeout`, with the
+    /// default timeout for the whole request
+    pub fn with_connect_timeout(timeout: Duration) -> Self {
+        HttpTransportBuilder::new().connect_timeout(timeout).build()
+    }
+
+    // a single attempt, reporting its outcome for self-healing
+    fn fetch_once(&self, url: &str) -> Result<String, TransportError> {
+        let result = fetch_with(&self.client.client(), url);
+        match &result {
+            // no response at all, which is what a broken pool or stale DNS entry looks like
+            Err(TransportError::Unexpected { status_code: None }) => {
+                self.client.record_failure();
+            }
+            _ => self.client.record_success(),
+        }
+        result
+    }
 }
 
 impl Transport for HttpTransport {
     fn fetch(&self, url: &str) -> Result<String, TransportError> {
-        let res = self
-            .client
-            .get(url)
-            .send()
-            .map_err(|_| TransportError::Unexpected)?;
+        match &self.retry {
+            Some(config) => retry(config, || self.fetch_once(url)),
+            None => self.fetch_once(url),
+        }
+    }
+}
+
+fn fetch_with(client: &Client, url: &str) -> Result<String, TransportError> {
+    let res = client.get(url).send().map_err(|e| match tls_failure(&e) {
+        Some(message) => TransportError::Tls { message },
+        None => TransportError::Unexpected { status_code: None },
+    })?;
 
-        match res.status() {
-            StatusCode::OK => res.text().map_err(|_| TransportError::Unexpected),
+    match res.status() {
+        StatusCode::OK => res.text().map_err(|_| TransportError::Unexpected {
+            status_code: Some(StatusCode::OK.as_u16()),
+        }),
 
-            StatusCode::NOT_FOUND => Err(TransportError::NotFound),
+        status => Err(TransportError::from_http_status(status.as_u16())),
+    }
+}
 
-            _ => Err(TransportError::Unexpected),
+/// the message of a TLS failure somewhere in `e`'s source chain. reqwest doesn't expose
+/// the TLS backend's error type, so its message is all there is to go on.
+pub(crate) fn tls_failure(e: &reqwest::Error) -> Option<String> {
+    let mut source = e.source();
+    while let Some(cause) = source {
+        let message = cause.to_string();
+        let lowercase = message.to_lowercase();
+        if ["certificate", "tls", "ssl", "handshake"]
+            .iter()
+            .any(|needle| lowercase.contains(needle))
+        {
+            return Some(message);
         }
+        source = cause.source();
     }
+    None
 }
 
-/// a simple implementation of the `Transport` trait using `reqwest` for HTTP endpoints
+/// how long a request may take in total unless configured otherwise
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// a simple implementation of the `Transport` trait using `reqwest` for HTTP endpoints, with
+/// a `DEFAULT_REQUEST_TIMEOUT` for each request
 pub fn new_http_transport() -> HttpTransport {
-    HttpTransport {
-        client: Client::new(),
+    HttpTransportBuilder::new().build()
+}
+
+/// the timeouts of an HTTP transport. `None` keeps the default: `DEFAULT_REQUEST_TIMEOUT`
+/// for the whole request, and `reqwest`'s for connecting.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HttpTransportConfig {
+    pub connect_timeout: Option<Duration>,
+    pub request_timeout: Option<Duration>,
+}
+
+/// an HTTP transport like `new_http_transport`, with the timeouts in `config`
+pub fn new_http_transport_with_config(config: HttpTransportConfig) -> HttpTransport {
+    HttpTransportBuilder::from(config).build()
+}
+
+/// configures the `reqwest` client behind an `HttpTransport`
+#[derive(Debug, Clone)]
+pub struct HttpTransportBuilder {
+    gzip: bool,
+    root_certificates: Vec<Certificate>,
+    accept_invalid_certs: bool,
+    timeout: Duration,
+    connect_timeout: Option<Duration>,
+    user_agent: Option<String>,
+    proxy: Option<Proxy>,
+}
+
+impl Default for HttpTransportBuilder {
+    fn default() -> Self {
+        HttpTransportBuilder {
+            gzip: true,
+            root_certificates: Vec::new(),
+            accept_invalid_certs: false,
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            connect_timeout: None,
+            user_agent: None,
+            proxy: None,
+        }
+    }
+}
+
+impl From<HttpTransportConfig> for HttpTransportBuilder {
+    fn from(config: HttpTransportConfig) -> Self {
+        let builder = HttpTransportBuilder::new();
+        let builder = match config.request_timeout {
+            Some(timeout) => builder.timeout(timeout),
+            None => builder,
+        };
+        match config.connect_timeout {
+            Some(timeout) => builder.connect_timeout(timeout),
+            None => builder,
+        }
+    }
+}
+
+impl HttpTransportBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ask relays for gzip-compressed responses and transparently decompress them. Enabled
+    /// by default; requires `reqwest`'s `gzip` feature, which this crate turns on.
+    pub fn enable_gzip(mut self, enabled: bool) -> Self {
+        self.gzip = enabled;
+        self
+    }
+
+    /// fail requests that take longer than `timeout` in total. Defaults to
+    /// `DEFAULT_REQUEST_TIMEOUT`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// give up on connecting to a relay after `timeout`. Defaults to `reqwest`'s, which only
+    /// bounds the connection by the request timeout.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// send `user_agent` as the `User-Agent` header, so relay operators can tell clients apart
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// send every request through `proxy`
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// trust `pem_or_der`, a PEM or DER encoded CA certificate, in addition to the system's
+    /// roots, e.g. for a proxy that re-signs TLS with an internal CA. Fails with
+    /// `TransportError::Tls` if the certificate can't be parsed.
+    pub fn add_root_certificate(mut self, pem_or_der: &[u8]) -> Result<Self, TransportError> {
+        let is_pem = pem_or_der.trim_ascii_start().starts_with(b"-----BEGIN");
+        let certificate = if is_pem {
+            Certificate::from_pem(pem_or_der)
+        } else {
+            Certificate::from_der(pem_or_der)
+        }
+        .map_err(|e| TransportError::Tls {
+            message: e.to_string(),
+        })?;
+        self.root_certificates.push(certificate);
+        Ok(self)
+    }
+
+    /// DANGER: accept any certificate, including self-signed, expired and mismatched ones,
+    /// which lets anyone on the path impersonate the relay. Beacons are still verified, but
+    /// chain info fetched over such a transport can't be trusted. Only for lab environments.
+    pub fn danger_accept_invalid_certs(mut self) -> Self {
+        self.accept_invalid_certs = true;
+        self
+    }
+
+    /// # Panics
+    /// like `reqwest::blocking::Client::new`, if the TLS backend cannot be initialised for the
+    /// first client. Later rebuilds by self-healing keep the previous client instead.
+    pub fn build(self) -> HttpTransport {
+        HttpTransport::with_client_factory(move || {
+            let builder = self
+                .root_certificates
+                .iter()
+                .fold(Client::builder(), |builder, certificate| {
+                    builder.add_root_certificate(certificate.clone())
+                });
+            let builder = builder.timeout(self.timeout);
+            let builder = match self.connect_timeout {
+                Some(timeout) => builder.connect_timeout(timeout),
+                None => builder,
+            };
+            let builder = match &self.user_agent {
+                Some(user_agent) => builder.user_agent(user_agent.as_str()),
+                None => builder,
+            };
+            let builder = match &self.proxy {
+                Some(proxy) => builder.proxy(proxy.clone()),
+                None => builder,
+            };
+            builder
+                .gzip(self.gzip)
+                .danger_accept_invalid_certs(self.accept_invalid_certs)
+                .build()
+        })
+        .expect("failed to initialise the HTTP client")
+    }
+}
+
+/// when to rebuild a client that keeps failing to connect
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelfHealingConfig {
+    /// the number of consecutive connection failures after which the client is rebuilt
+    pub failure_threshold: usize,
+    /// the minimum time between two rebuilds, so a relay that is simply down doesn't cause a
+    /// rebuild every few requests
+    pub min_rebuild_interval: Duration,
+}
+
+impl Default for SelfHealingConfig {
+    fn default() -> Self {
+        SelfHealingConfig {
+            failure_threshold: 5,
+            min_rebuild_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+/// reported to the observer every time a client is rebuilt
+#[derive(Debug, Clone, PartialEq)]
+pub struct RebuildEvent {
+    /// the consecutive failures that triggered the rebuild
+    pub consecutive_failures: usize,
+    /// the number of rebuilds so far, including this one
+    pub total_rebuilds: usize,
+}
+
+/// a client that is recreated from its factory after repeated failures, according to a
+/// `SelfHealingConfig`. Callers report the outcome of each request with `record_success`
+/// and `record_failure`. If the factory fails with an `E`, the previous client is kept.
+pub struct SelfHealing<C, E> {
+    current: RwLock<C>,
+    factory: Arc<dyn Fn() -> Result<C, E> + Send + Sync>,
+    config: Option<SelfHealingConfig>,
+    observer: Option<Arc<dyn Fn(&RebuildEvent) + Send + Sync>>,
+    consecutive_failures: AtomicUsize,
+    total_rebuilds: AtomicUsize,
+    last_rebuild: Mutex<Option<Instant>>,
+}
+
+impl<C: Clone, E> SelfHealing<C, E> {
+    /// create the first client from `factory`, or fail with its error. Without a config it
+    /// is never rebuilt.
+    pub fn new(factory: impl Fn() -> Result<C, E> + Send + Sync + 'static) -> Result<Self, E> {
+        Ok(SelfHealing {
+            current: RwLock::new(factory()?),
+            factory: Arc::new(factory),
+            config: None,
+            observer: None,
+            consecutive_failures: AtomicUsize::new(0),
+            total_rebuilds: AtomicUsize::new(0),
+            last_rebuild: Mutex::new(None),
+        })
+    }
+
+    pub fn with_config(mut self, config: SelfHealingConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    pub fn on_rebuild(mut self, observer: impl Fn(&RebuildEvent) + Send + Sync + 'static) -> Self {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// the current client
+    pub fn client(&self) -> C {
+        match self.current.read() {
+            Ok(client) => client.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    /// the number of times the client has been rebuilt
+    pub fn rebuilds(&self) -> usize {
+        self.total_rebuilds.load(Ordering::SeqCst)
+    }
+
+    pub fn record_success(&self) {
+        self.consecutive_failures.store(0, Ordering::SeqCst);
+    }
+
+    /// count a failure, rebuilding the client if the threshold is reached and the last
+    /// rebuild was long enough ago. A rebuild whose factory fails keeps the current client
+    /// and returns `None`, and the next attempt waits for `min_rebuild_interval` like after
+    /// a successful one.
+    pub fn record_failure(&self) -> Option<RebuildEvent> {
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::SeqCst) + 1;
+        let config = self.config.as_ref()?;
+        if failures < config.failure_threshold {
+            return None;
+        }
+
+        let mut last_rebuild = self.last_rebuild.lock().ok()?;
+        if matches!(*last_rebuild, Some(at) if at.elapsed() < config.min_rebuild_interval) {
+            return None;
+        }
+
+        *last_rebuild = Some(Instant::now());
+        let client = (self.factory)().ok()?;
+        match self.current.write() {
+            Ok(mut current) => *current = client,
+            Err(poisoned) => *poisoned.into_inner() = client,
+        }
+        self.consecutive_failures.store(0, Ordering::SeqCst);
+
+        let event = RebuildEvent {
+            consecutive_failures: failures,
+            total_rebuilds: self.total_rebuilds.fetch_add(1, Ordering::SeqCst) + 1,
+        };
+        if let Some(observer) = &self.observer {
+            observer(&event);
+        }
+        Some(event)
+    }
+}
+
+/// a clone starts from the current client, with the same factory, config and observer and
+/// its own failure count
+impl<C: Clone, E> Clone for SelfHealing<C, E> {
+    fn clone(&self) -> Self {
+        SelfHealing {
+            current: RwLock::new(self.client()),
+            factory: self.factory.clone(),
+            config: self.config.clone(),
+            observer: self.observer.clone(),
+            consecutive_failures: AtomicUsize::new(0),
+            total_rebuilds: AtomicUsize::new(0),
+            last_rebuild: Mutex::new(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::http::{
+        new_http_transport_with_config, HttpTransport, HttpTransportBuilder, HttpTransportConfig,
+        RebuildEvent, RetryConfig, SelfHealing, SelfHealingConfig, DEFAULT_REQUEST_TIMEOUT,
+    };
+    use crate::testkit::MAINNET_ROUND_TWO_JSON;
+    use crate::verify::Beacon;
+    use crate::{Transport, TransportError};
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use native_tls::{Identity, TlsAcceptor};
+    use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    const BEACON: &str = MAINNET_ROUND_TWO_JSON;
+
+    #[test]
+    fn gzip_responses_are_decompressed() {
+        let url = serve_gzip_once(BEACON);
+        let body = HttpTransportBuilder::new().build().fetch(&url).unwrap();
+        let beacon: Beacon = serde_json::from_str(&body).unwrap();
+
+        assert_eq!(beacon.round_number, 2);
+    }
+
+    #[test]
+    fn gzip_can_be_disabled() {
+        let url = serve_gzip_once(BEACON);
+        let body = HttpTransportBuilder::new()
+            .enable_gzip(false)
+            .build()
+            .fetch(&url)
+            .unwrap();
+
+        assert!(serde_json::from_str::<Beacon>(&body).is_err());
+    }
+
+    #[test]
+    fn unexpected_failures_are_retried() {
+        let (url, requests) = serve_failing(2, "500 Internal Server Error");
+        let transport = HttpTransportBuilder::new()
+            .build()
+            .with_retry(instant_retry(3));
+
+        assert_eq!(transport.fetch(&url).unwrap(), BEACON);
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn retries_give_up_after_max_attempts() {
+        let (url, requests) = serve_failing(3, "502 Bad Gateway");
+        let transport = HttpTransportBuilder::new()
+            .build()
+            .with_retry(instant_retry(3));
+
+        assert!(matches!(
+            transport.fetch(&url),
+            Err(TransportError::Unexpected {
+                status_code: Some(502)
+            })
+        ));
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn not_found_is_never_retried() {
+        let (url, requests) = serve_failing(1, "404 Not Found");
+        let transport = HttpTransportBuilder::new()
+            .build()
+            .with_retry(instant_retry(3));
+
+        assert!(matches!(
+            transport.fetch(&url),
+            Err(TransportError::NotFound)
+        ));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn slow_relays_time_out() {
+        let url = serve_silently();
+
+        let started = Instant::now();
+        let err = HttpTransport::with_timeout(Duration::from_millis(200))
+            .fetch(&url)
+            .unwrap_err();
+        assert!(
+            matches!(err, TransportError::Unexpected { status_code: None }),
+            "{err:?}"
+        );
+        assert!(started.elapsed() < DEFAULT_REQUEST_TIMEOUT);
+
+        let config = HttpTransportConfig {
+            request_timeout: Some(Duration::from_millis(200)),
+            ..HttpTransportConfig::default()
+        };
+        assert!(new_http_transport_with_config(config).fetch(&url).is_err());
+    }
+
+    #[test]
+    fn config_timeouts_default_to_the_library_ones() {
+        let builder = HttpTransportBuilder::from(HttpTransportConfig::default());
+        assert_eq!(builder.timeout, DEFAULT_REQUEST_TIMEOUT);
+        assert_eq!(builder.connect_timeout, None);
+
+        let builder = HttpTransportBuilder::from(HttpTransportConfig {
+            connect_timeout: Some(Duration::from_secs(2)),
+            request_timeout: Some(Duration::from_secs(20)),
+        });
+        assert_eq!(builder.timeout, Duration::from_secs(20));
+        assert_eq!(builder.connect_timeout, Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn certificates_signed_by_an_added_root_are_trusted() {
+        let relay = TlsRelay::start();
+
+        for certificate in [relay.ca_pem.as_bytes(), &relay.ca_der] {
+            let transport = HttpTransportBuilder::new()
+                .add_root_certificate(certificate)
+                .unwrap()
+                .build();
+            assert_eq!(transport.fetch(&relay.url).unwrap(), BEACON);
+        }
+    }
+
+    #[test]
+    fn untrusted_certificates_are_tls_errors() {
+        let relay = TlsRelay::start();
+
+        let err = HttpTransportBuilder::new()
+            .build()
+            .fetch(&relay.url)
+            .unwrap_err();
+        assert!(matches!(err, TransportError::Tls { .. }), "{err:?}");
+    }
+
+    #[test]
+    fn invalid_certificates_can_be_accepted_explicitly() {
+        let relay = TlsRelay::start();
+
+        let transport = HttpTransportBuilder::new()
+            .danger_accept_invalid_certs()
+            .build();
+        assert_eq!(transport.fetch(&relay.url).unwrap(), BEACON);
+    }
+
+    #[test]
+    fn unparseable_root_certificates_are_rejected() {
+        assert!(matches!(
+            HttpTransportBuilder::new().add_root_certificate(b"-----BEGIN CERTIFICATE-----\nzz"),
+            Err(TransportError::Tls { .. })
+        ));
+    }
+
+    #[test]
+    fn client_is_rebuilt_after_consecutive_failures() {
+        let healing = counting_client(config(3, Duration::ZERO));
+        assert_eq!(healing.client(), 0);
+
+        assert_eq!(healing.record_failure(), None);
+        assert_eq!(healing.record_failure(), None);
+        assert_eq!(
+            healing.record_failure(),
+            Some(RebuildEvent {
+                consecutive_failures: 3,
+                total_rebuilds: 1,
+            })
+        );
+        assert_eq!(healing.client(), 1);
+    }
+
+    #[test]
+    fn success_resets_the_failure_count() {
+        let healing = counting_client(config(2, Duration::ZERO));
+
+        healing.record_failure();
+        healing.record_success();
+        assert_eq!(healing.record_failure(), None);
+        assert_eq!(healing.rebuilds(), 0);
+        assert_eq!(healing.client(), 0);
+    }
+
+    #[test]
+    fn rebuilds_are_rate_limited() {
+        let healing = counting_client(config(1, Duration::from_secs(3600)));
+
+        assert!(healing.record_failure().is_some());
+        assert_eq!(healing.record_failure(), None);
+        assert_eq!(healing.record_failure(), None);
+        assert_eq!(healing.rebuilds(), 1);
+        assert_eq!(healing.client(), 1);
+    }
+
+    #[test]
+    fn clones_count_failures_on_their_own() {
+        let healing = counting_client(config(2, Duration::ZERO));
+        healing.record_failure();
+        let clone = healing.clone();
+
+        assert_eq!(clone.client(), 0);
+        assert_eq!(clone.record_failure(), None);
+        assert!(clone.record_failure().is_some());
+        assert_eq!(clone.client(), 1);
+        assert_eq!(healing.client(), 0);
+        assert_eq!(healing.rebuilds(), 0);
+    }
+
+    #[test]
+    fn client_is_never_rebuilt_without_config() {
+        let built = Arc::new(AtomicUsize::new(0));
+        let counter = built.clone();
+        let healing =
+            SelfHealing::new(move || Ok::<_, ()>(counter.fetch_add(1, Ordering::SeqCst))).unwrap();
+
+        for _ in 0..100 {
+            assert_eq!(healing.record_failure(), None);
+        }
+        assert_eq!(built.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn observer_is_notified_of_rebuilds() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let observed = events.clone();
+        let healing = counting_client(config(2, Duration::ZERO))
+            .on_rebuild(move |event| observed.lock().unwrap().push(event.clone()));
+
+        for _ in 0..4 {
+            healing.record_failure();
+        }
+        assert_eq!(
+            events.lock().unwrap().as_slice(),
+            &[
+                RebuildEvent {
+                    consecutive_failures: 2,
+                    total_rebuilds: 1,
+                },
+                RebuildEvent {
+                    consecutive_failures: 2,
+                    total_rebuilds: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_rebuilds_keep_the_current_client() {
+        let built = Arc::new(AtomicUsize::new(0));
+        let counter = built.clone();
+        // only the first and third attempts succeed
+        let healing = SelfHealing::new(move || match counter.fetch_add(1, Ordering::SeqCst) {
+            1 => Err("no TLS backend"),
+            n => Ok(n),
+        })
+        .unwrap()
+        .with_config(config(1, Duration::ZERO));
+
+        assert_eq!(healing.record_failure(), None);
+        assert_eq!(healing.client(), 0);
+        assert_eq!(healing.rebuilds(), 0);
+        assert!(healing.record_failure().is_some());
+        assert_eq!(healing.client(), 2);
+        assert_eq!(built.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn a_failing_first_client_is_an_error() {
+        assert_eq!(
+            SelfHealing::<usize, _>::new(|| Err("no TLS backend")).err(),
+            Some("no TLS backend")
+        );
+    }
+
+    // a "client" that is just the number of clients built before it
+    fn counting_client(config: SelfHealingConfig) -> SelfHealing<usize, ()> {
+        let built = AtomicUsize::new(0);
+        SelfHealing::new(move || Ok(built.fetch_add(1, Ordering::SeqCst)))
+            .unwrap()
+            .with_config(config)
+    }
+
+    // answer a single request on a local port with `body` gzip-compressed, returning its URL
+    fn serve_gzip_once(body: &str) -> String {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(body.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/public/2", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 4096];
+            assert!(stream.read(&mut request).unwrap() > 0);
+            let headers = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                compressed.len()
+            );
+            stream.write_all(headers.as_bytes()).unwrap();
+            stream.write_all(&compressed).unwrap();
+        });
+        url
+    }
+
+    // accept connections on a local port and never answer, returning its URL
+    fn serve_silently() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/public/2", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let mut open = Vec::new();
+            for stream in listener.incoming().flatten() {
+                open.push(stream);
+            }
+        });
+        url
+    }
+
+    // answer the first `failures` requests on a local port with `status`, and every later
+    // one with `BEACON`, returning the URL and a count of the requests
+    fn serve_failing(failures: usize, status: &'static str) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/public/2", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = [0u8; 4096];
+                if stream.read(&mut request).is_err() {
+                    continue;
+                }
+                let (status, body) = if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                    (status, "")
+                } else {
+                    ("200 OK", BEACON)
+                };
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        (url, requests)
+    }
+
+    fn instant_retry(max_attempts: usize) -> RetryConfig {
+        RetryConfig {
+            max_attempts,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+        }
+    }
+
+    // an HTTPS relay on a local port whose certificate is signed by a freshly generated CA,
+    // answering every request with `BEACON`
+    struct TlsRelay {
+        url: String,
+        ca_pem: String,
+        ca_der: Vec<u8>,
+    }
+
+    impl TlsRelay {
+        fn start() -> Self {
+            let mut ca_params = CertificateParams::new(Vec::new()).unwrap();
+            ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            let ca_key = KeyPair::generate().unwrap();
+            let ca = ca_params.self_signed(&ca_key).unwrap();
+
+            let relay_key = KeyPair::generate().unwrap();
+            let relay = CertificateParams::new(vec!["localhost".to_string()])
+                .unwrap()
+                .signed_by(&relay_key, &ca, &ca_key)
+                .unwrap();
+            let identity =
+                Identity::from_pkcs8(relay.pem().as_bytes(), relay_key.serialize_pem().as_bytes())
+                    .unwrap();
+            let acceptor = TlsAcceptor::new(identity).unwrap();
+
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let port = listener.local_addr().unwrap().port();
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    // clients that reject the certificate abort the handshake
+                    let Ok(mut stream) = acceptor.accept(stream) else {
+                        continue;
+                    };
+                    let mut request = [0u8; 4096];
+                    if stream.read(&mut request).is_err() {
+                        continue;
+                    }
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{BEACON}",
+                        BEACON.len()
+                    );
+                    let _ = stream.write_all(response.as_bytes());
+                    let _ = stream.shutdown();
+                }
+            });
+
+            TlsRelay {
+                url: format!("https://localhost:{port}/public/2"),
+                ca_pem: ca.pem(),
+                ca_der: ca.der().to_vec(),
+            }
+        }
+    }
+
+    fn config(failure_threshold: usize, min_rebuild_interval: Duration) -> SelfHealingConfig {
+        SelfHealingConfig {
+            failure_threshold,
+            min_rebuild_interval,
+        }
     }
 }
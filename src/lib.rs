@@ -2,38 +2,300 @@
 //!
 //! `drand_client_rs` is a small rust library for retrieving random numbers from the [drand network](https://drand.love).
 //!
+//! ## fetch-only builds
+//!
+//! with `default-features = false`, the `http` feature and neither `energon` nor
+//! `verify-slim` enabled, no pairing library is linked. Beacons and chain info still parse and the `_unverified`
+//! fetch methods work, while `verify_beacon` and the verifying client methods fail with
+//! `VerificationDisabled`.
+//!
+//! ## verification-only builds
+//!
+//! without the `serde` and `http` features, only `Beacon`, `ChainInfo` and the verification
+//! functions remain, with no JSON or HTTP dependencies. `serde` adds (de)serialization and
+//! `DrandClient` for custom transports, and `http` adds `HttpTransport` and the
+//! `new_http_client` constructors.
+//!
+//! ## thread safety
+//!
+//! `DrandClient<T>` is `Send` if `T: Send`, and every method takes `&self`, so a client with a
+//! `Sync` transport such as `HttpTransport` can be shared across threads in an `Arc`. For
+//! transports that aren't `Sync`, wrap the client in an `Arc<Mutex<DrandClient<T>>>`.
+//! A client over a `Clone` transport is `Clone` too, sharing its chain info with the
+//! original, so each rayon worker or tokio task can own one instead.
+//!
+//! ```no_run
+//! use drand_client_rs::http::HttpTransport;
+//! use drand_client_rs::{new_http_client, DrandClient};
+//! use std::sync::Arc;
+//! use std::thread;
+//!
+//! let client: Arc<DrandClient<HttpTransport>> =
+//!     Arc::new(new_http_client("https://api.drand.sh").unwrap());
+//!
+//! let handles: Vec<_> = (1..=4)
+//!     .map(|round| {
+//!         let client = Arc::clone(&client);
+//!         thread::spawn(move || client.randomness(round))
+//!     })
+//!     .collect();
+//!
+//! for handle in handles {
+//!     println!("{:?}", handle.join().unwrap());
+//! }
+//! ```
+//!
 
 extern crate core;
 
+#[cfg(feature = "async")]
+pub mod async_client;
+#[cfg(feature = "http")]
+pub mod builder;
+#[cfg(feature = "serde")]
+pub mod bundle;
+pub mod cache;
 pub mod chain_info;
+pub mod chains;
+#[cfg(any(all(test, feature = "serde"), feature = "testkit"))]
+pub mod chaos;
+pub mod clock;
+pub mod collection;
+#[cfg(feature = "drand-verify")]
+pub mod compat;
+pub mod delay;
+pub mod derive;
+#[cfg(feature = "evm")]
+pub mod evm;
+#[cfg(feature = "uniffi")]
+pub mod ffi;
+#[cfg(feature = "filecoin")]
+pub mod filecoin;
+#[cfg(any(test, feature = "arbitrary"))]
+pub mod fuzz;
+#[cfg(feature = "http")]
 pub mod http;
+#[cfg(feature = "http")]
+pub mod multi;
+#[cfg(feature = "serde")]
+pub mod multichain;
+#[cfg(feature = "borsh")]
+pub mod onchain;
+pub mod pinned;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod retry;
+#[cfg(feature = "rand")]
+pub mod rng;
+pub mod round_number;
+#[cfg(feature = "serde")]
+pub mod rounds;
+pub mod testing;
+#[cfg(any(test, feature = "testkit"))]
+pub mod testkit;
+pub mod url;
 pub mod verify;
+#[cfg(feature = "serde")]
+pub mod watch;
 
 use crate::chain_info::ChainInfo;
+#[cfg(feature = "http")]
 use crate::http::{new_http_transport, HttpTransport};
-use crate::verify::{verify_beacon, Beacon};
-use crate::DrandClientError::{InvalidChainInfo, InvalidRound};
-use std::time::{SystemTime, UNIX_EPOCH};
+use crate::verify::VerificationError;
+use crate::DrandClientError::InvalidChainInfo;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use thiserror::Error;
+#[cfg(feature = "serde")]
+use {
+    crate::cache::{LatestBeaconCache, RoundCache},
+    crate::clock::{Clock, SystemClock},
+    crate::delay::PublishDelayEstimator,
+    crate::retry::{retry, retry_if, RetryConfig},
+    crate::round_number::RoundNumber,
+    crate::url::{DefaultUrlStrategy, UrlStrategy},
+    crate::verify::{verify_beacon, verify_beacon_ref, verify_randomness, Beacon, VerifiedBeacon},
+    crate::DrandClientError::InvalidRound,
+    std::ops::RangeInclusive,
+    std::sync::atomic::{AtomicBool, Ordering},
+    std::sync::{Arc, Mutex},
+    std::thread,
+};
+
+#[cfg(feature = "async")]
+pub use crate::async_client::new_async_http_client;
+#[cfg(feature = "http")]
+pub use crate::multi::new_http_client_multi;
+#[cfg(feature = "serde")]
+pub use crate::multichain::{fetch_chains, new_client_for_beacon, resolve_beacon_id};
+#[cfg(feature = "http")]
+pub use crate::multichain::{new_http_client_for_beacon, new_http_multi_chain_client};
+pub use crate::round_number::RoundNumber;
+
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
 
+/// the most threads `DrandClient::randomness_batch` fetches with
+pub const MAX_BATCH_THREADS: usize = 8;
+
+// just the round of a beacon, skipping the hex decoding of everything else
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct RoundOnly {
+    #[serde(alias = "round")]
+    round_number: u64,
+}
+
+#[cfg(feature = "serde")]
 /// a struct encapsulating all the necessary state for retrieving and validating drand beacons.
-pub struct DrandClient<'a, T: Transport> {
+pub struct DrandClient<T: Transport + Send> {
     transport: T,
-    base_url: &'a str,
-    chain_info: ChainInfo,
+    base_url: String,
+    // shared by clones, so they don't copy the key material
+    chain_info: Arc<ChainInfo>,
+    latest_fallback: bool,
+    retry: Option<RetryConfig>,
+    publish_delay: Mutex<PublishDelayEstimator>,
+    url_strategy: Arc<dyn UrlStrategy + Send + Sync>,
+    latest_cache: Option<Mutex<Option<LatestBeaconCache>>>,
+    round_cache: Option<Mutex<RoundCache>>,
+    verification: VerificationPolicy,
+    latest_tolerance: u64,
+    clock: Arc<dyn Clock + Send + Sync>,
 }
 
+/// a clone talks to the relay over a clone of the transport and starts with a copy of the
+/// client's caches, which it then keeps on its own
+#[cfg(feature = "serde")]
+impl<T: Transport + Send + Clone> Clone for DrandClient<T> {
+    fn clone(&self) -> Self {
+        DrandClient {
+            transport: self.transport.clone(),
+            base_url: self.base_url.clone(),
+            chain_info: self.chain_info.clone(),
+            latest_fallback: self.latest_fallback,
+            retry: self.retry.clone(),
+            publish_delay: Mutex::new(copy_locked(&self.publish_delay)),
+            url_strategy: self.url_strategy.clone(),
+            latest_cache: self
+                .latest_cache
+                .as_ref()
+                .map(|cache| Mutex::new(copy_locked(cache))),
+            round_cache: self
+                .round_cache
+                .as_ref()
+                .map(|cache| Mutex::new(copy_locked(cache))),
+            verification: self.verification,
+            latest_tolerance: self.latest_tolerance,
+            clock: self.clock.clone(),
+        }
+    }
+}
+
+// every client over a thread-safe transport can be shared between threads and tasks, which
+// any state added to the client has to keep
+#[cfg(feature = "http")]
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<DrandClient<HttpTransport>>();
+};
+
+// the value behind `mutex`, even if a panicking thread poisoned it
+#[cfg(feature = "serde")]
+fn copy_locked<V: Clone>(mutex: &Mutex<V>) -> V {
+    match mutex.lock() {
+        Ok(value) => value.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+/// which checks the `_unverified` methods run on the beacons they fetch, which they return
+/// as plain `Beacon`s. `randomness`, `latest_randomness` and the methods built on them return
+/// `VerifiedBeacon`s, so they always verify fully, whatever the policy.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum VerificationPolicy {
+    /// check the signature against the chain's public key and the randomness against the
+    /// signature
+    #[default]
+    Full,
+    /// check the randomness against the signature, skipping the pairing. This catches
+    /// corrupted beacons but not forged ones, so the relay must be trusted. It also works in
+    /// builds without a pairing library.
+    RandomnessHashOnly,
+    /// no checks: beacons are returned exactly as the relay sent them
+    None,
+}
+
+#[cfg(feature = "http")]
 /// create a new instance of the client with an HTTP transport for a given `base_url`.
 /// Supported `base_url`s include: "<https://api.drand.sh>", "<https://drand.cloudflare.com>" and "<https://api.drand.secureweb3.com:6875>".
 /// A full list can be found at <https://drand.love/developer/>
-pub fn new_http_client(base_url: &str) -> Result<DrandClient<HttpTransport>, DrandClientError> {
+pub fn new_http_client(
+    base_url: impl Into<String>,
+) -> Result<DrandClient<HttpTransport>, DrandClientError> {
+    DrandClient::builder(base_url).build()
+}
+
+#[cfg(feature = "http")]
+/// create a new instance of the client like `new_http_client`, then fetch and verify round 1
+/// and check that its randomness is `expected_round1_randomness`. This pins the chain's
+/// actual output rather than just the key the relay advertises, so a relay substituting
+/// its own key and chain fails with `InvalidChainInfo`.
+pub fn new_http_client_verify_round1(
+    base_url: impl Into<String>,
+    expected_round1_randomness: &[u8],
+) -> Result<DrandClient<HttpTransport>, DrandClientError> {
+    let client = new_http_client(base_url)?;
+    client.check_round1(expected_round1_randomness)?;
+    Ok(client)
+}
+
+#[cfg(feature = "http")]
+/// create a new instance of the client like `new_http_client`, then check that the chain
+/// info's hash is `expected_hash`, failing with `ChainHashMismatch` if it isn't. The hash
+/// identifies the chain, so this catches a relay serving some other chain at `base_url`.
+pub fn new_http_client_with_chain_hash(
+    base_url: impl Into<String>,
+    expected_hash: &[u8],
+) -> Result<DrandClient<HttpTransport>, DrandClientError> {
+    let client = new_http_client(base_url)?;
+    client.check_chain_hash(expected_hash)?;
+    Ok(client)
+}
+
+#[cfg(feature = "http")]
+/// create a new instance of the client with an HTTP transport for a relay that doesn't use
+/// the standard drand URL layout. `url_strategy` is used for every request, including the
+/// chain info fetch.
+pub fn new_http_client_with_url_strategy(
+    base_url: impl Into<String>,
+    url_strategy: impl UrlStrategy + Send + Sync + 'static,
+) -> Result<DrandClient<HttpTransport>, DrandClientError> {
+    let base_url = base_url.into();
     let http_transport = new_http_transport();
-    let chain_info = fetch_chain_info(&http_transport, base_url)?;
-    Ok(DrandClient {
-        base_url,
-        transport: http_transport,
-        chain_info,
-    })
+    let chain_info = fetch_chain_info_from(&http_transport, &base_url, &url_strategy, None)?;
+    Ok(DrandClient::new(http_transport, base_url, chain_info).with_url_strategy(url_strategy))
+}
+
+#[cfg(feature = "http")]
+/// create a new instance of the client with an HTTP transport for a chain whose info is
+/// already trusted, e.g. from `pin_chain!`, without fetching it from the relay
+pub fn new_http_client_with_chain_info(
+    base_url: impl Into<String>,
+    chain_info: ChainInfo,
+) -> DrandClient<HttpTransport> {
+    DrandClient::with_chain_info(new_http_transport(), base_url, chain_info)
+}
+
+#[cfg(feature = "serde")]
+/// create a new instance of the client over any transport for a chain whose info is already
+/// trusted, e.g. one of the `chains` constants, without a round-trip to `/info`. The same as
+/// `DrandClient::with_chain_info`.
+pub fn new_with_chain_info<T: Transport + Send>(
+    transport: T,
+    base_url: impl Into<String>,
+    chain_info: ChainInfo,
+) -> DrandClient<T> {
+    DrandClient::with_chain_info(transport, base_url, chain_info)
 }
 
 /// represents a transport on which to connect to the drand network. This crate provides an
@@ -42,79 +304,808 @@ pub trait Transport {
     fn fetch(&self, url: &str) -> Result<String, TransportError>;
 }
 
-/// fetch the chain info for a given URL. The chain info contains the public key (used to
-/// verify beacons) and the genesis time (used to calculate the time for given rounds).
-pub fn fetch_chain_info(
-    transport: &HttpTransport,
+/// a transport shared between clients, e.g. by the chains of a `MultiChainClient`
+impl<T: Transport + ?Sized> Transport for std::sync::Arc<T> {
+    fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        (**self).fetch(url)
+    }
+}
+
+#[cfg(feature = "serde")]
+/// fetch the chain info for a given URL over any transport. The chain info contains the
+/// public key (used to verify beacons) and the genesis time (used to calculate the time for
+/// given rounds). Chain info that fails `ChainInfo::validate`, e.g. because its hash doesn't
+/// match its keys, is rejected as `InvalidChainInfo`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip_all, fields(url = base_url))
+)]
+pub fn fetch_chain_info<T: Transport>(
+    transport: &T,
+    base_url: &str,
+) -> Result<ChainInfo, DrandClientError> {
+    fetch_chain_info_from(transport, base_url, &DefaultUrlStrategy, None)
+}
+
+#[cfg(feature = "serde")]
+/// fetch the chain info like `fetch_chain_info`, retrying according to `config` both when
+/// the relay can't be reached and when it returns chain info that fails to parse, as a relay
+/// that is starting up or overloaded may return a truncated body. Parse failures that persist
+/// after every attempt are reported as `ChainInfoNotReady`.
+pub fn fetch_chain_info_with_retry<T: Transport>(
+    transport: &T,
+    base_url: &str,
+    config: &RetryConfig,
+) -> Result<ChainInfo, DrandClientError> {
+    fetch_chain_info_from(transport, base_url, &DefaultUrlStrategy, Some(config))
+}
+
+#[cfg(feature = "serde")]
+fn fetch_chain_info_from<T: Transport>(
+    transport: &T,
     base_url: &str,
+    url_strategy: &dyn UrlStrategy,
+    retry_config: Option<&RetryConfig>,
 ) -> Result<ChainInfo, DrandClientError> {
-    let url = format!("{base_url}/info");
-    match transport.fetch(&url) {
-        Err(_) => Err(DrandClientError::NotResponding),
-        Ok(body) => serde_json::from_str(&body).map_err(|e| {
-            println!("{}", e);
-            InvalidChainInfo
+    let url = url_strategy.info_url(base_url);
+    // `None` is a body that failed to parse
+    let attempt = || match transport.fetch(&url) {
+        Err(e) => Err(Some(e)),
+        Ok(body) => serde_json::from_str::<ChainInfo>(&body).map_err(|_e| {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(url = %url, error = %_e, "chain info failed to parse");
+            None
         }),
-    }
+    };
+    let result = match retry_config {
+        Some(config) => retry_if(
+            config,
+            |e| !matches!(e, Some(TransportError::NotFound)),
+            attempt,
+        ),
+        None => attempt(),
+    };
+    let chain_info = match result {
+        Ok(chain_info) => chain_info,
+        Err(Some(e)) => return Err(e.into()),
+        Err(None) if retry_config.is_some() => return Err(DrandClientError::ChainInfoNotReady),
+        Err(None) => return Err(InvalidChainInfo),
+    };
+
+    chain_info.validate()?;
+    Ok(chain_info)
 }
 
 /// an implementation of the logic for retrieving randomness
-impl<'a, T: Transport> DrandClient<'a, T> {
-    /// fetch the latest available randomness beacon
-    pub fn latest_randomness(&self) -> Result<Beacon, DrandClientError> {
-        let expected_round = round_for_time(&self.chain_info, SystemTime::now())?;
-        let beacon = self.fetch_beacon_tag("latest")?;
-
-        // it could take some time to aggregate beacons, so we tolerate one round early for latest
-        if beacon.round_number < expected_round - 1 {
+#[cfg(feature = "serde")]
+impl<T: Transport + Send> DrandClient<T> {
+    fn new(transport: T, base_url: impl Into<String>, chain_info: ChainInfo) -> Self {
+        DrandClient {
+            transport,
+            base_url: base_url.into(),
+            chain_info: Arc::new(chain_info),
+            latest_fallback: false,
+            retry: None,
+            publish_delay: Mutex::new(PublishDelayEstimator::new()),
+            url_strategy: Arc::new(DefaultUrlStrategy),
+            latest_cache: Some(Mutex::new(None)),
+            round_cache: None,
+            verification: VerificationPolicy::Full,
+            latest_tolerance: 1,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// a client for the chain described by `chain_info`, over any transport. Nothing is
+    /// fetched: every beacon is verified against `chain_info`, so a relay serving a different
+    /// public key from `/info` can't get its own beacons accepted.
+    pub fn with_chain_info(
+        transport: T,
+        base_url: impl Into<String>,
+        chain_info: ChainInfo,
+    ) -> Self {
+        DrandClient::new(transport, base_url, chain_info)
+    }
+
+    /// build request URLs with `url_strategy` instead of the standard drand layout
+    pub fn with_url_strategy(
+        mut self,
+        url_strategy: impl UrlStrategy + Send + Sync + 'static,
+    ) -> Self {
+        self.url_strategy = Arc::new(url_strategy);
+        self
+    }
+
+    /// read the current time from `clock` instead of the system clock, e.g. a
+    /// `testing::FixedClock` to make time-based behaviour deterministic in tests
+    pub fn with_clock(mut self, clock: impl Clock + Send + Sync + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// retry failed fetches according to `config`. By default failures are surfaced
+    /// immediately.
+    pub fn with_retry(mut self, config: RetryConfig) -> Self {
+        self.retry = Some(config);
+        self
+    }
+
+    /// when enabled, `latest_randomness` falls back to fetching the expected round (and the
+    /// one before it) directly if `/public/latest` is staler than the tolerated one round.
+    /// This helps with relays that cache `latest` behind a CDN.
+    pub fn with_latest_fallback(mut self, enabled: bool) -> Self {
+        self.latest_fallback = enabled;
+        self
+    }
+
+    /// `latest_randomness` returns the beacon it fetched last for up to one period, rather
+    /// than asking the relay again. Pass `true` to always fetch.
+    pub fn disable_latest_cache(mut self, disabled: bool) -> Self {
+        self.latest_cache = if disabled {
+            None
+        } else {
+            Some(Mutex::new(None))
+        };
+        self
+    }
+
+    /// keep up to `capacity` verified beacons in memory, so `randomness` for a round that was
+    /// fetched before, by it or by `latest_randomness`, doesn't hit the relay again. The
+    /// least recently used beacon is dropped once the cache is full. Off by default.
+    pub fn with_round_cache(mut self, capacity: usize) -> Self {
+        self.round_cache = Some(Mutex::new(RoundCache::new(capacity)));
+        self
+    }
+
+    /// fetch the relay's chain info again and verify beacons against it from now on, e.g.
+    /// after the relay's testnet was reset. If the chain hash changed, the client still
+    /// switches to the new chain, drops the cached beacons of the old one and reports the
+    /// switch as `ChainMigrated`. If the fetch fails, the client keeps its current chain info.
+    pub fn refresh_chain_info(&mut self) -> Result<(), DrandClientError> {
+        let chain_info = self.fetch_current_chain_info()?;
+        let migration = self.migration_to(&chain_info);
+        self.replace_chain_info(chain_info);
+        migration.map_or(Ok(()), Err)
+    }
+
+    /// like `refresh_chain_info`, but keeps the current chain info and fails with
+    /// `ChainMigrated` if the chain hash changed, so the application can decide whether to
+    /// re-pin to `new_info`
+    pub fn refresh_chain_info_strict(&mut self) -> Result<(), DrandClientError> {
+        let chain_info = self.fetch_current_chain_info()?;
+        if let Some(migration) = self.migration_to(&chain_info) {
+            return Err(migration);
+        }
+        self.replace_chain_info(chain_info);
+        Ok(())
+    }
+
+    fn migration_to(&self, chain_info: &ChainInfo) -> Option<DrandClientError> {
+        (chain_info.chain_hash != self.chain_info.chain_hash).then(|| {
+            DrandClientError::ChainMigrated {
+                old_hash: self.chain_info.chain_hash.clone(),
+                new_hash: chain_info.chain_hash.clone(),
+                new_info: Box::new(chain_info.clone()),
+            }
+        })
+    }
+
+    fn replace_chain_info(&mut self, chain_info: ChainInfo) {
+        if chain_info.chain_hash != self.chain_info.chain_hash {
+            if let Some(cache) = &mut self.latest_cache {
+                *cache = Mutex::new(None);
+            }
+            if let Some(Ok(cache)) = self.round_cache.as_mut().map(Mutex::get_mut) {
+                cache.clear();
+            }
+            self.publish_delay = Mutex::new(PublishDelayEstimator::new());
+        }
+        self.chain_info = Arc::new(chain_info);
+    }
+
+    /// the chain info the client verifies beacons against
+    pub fn chain_info(&self) -> &ChainInfo {
+        &self.chain_info
+    }
+
+    /// the time between two rounds of the chain
+    pub fn period(&self) -> Duration {
+        self.chain_info.period_as_duration()
+    }
+
+    /// the time of the chain's first round. Fails with `InvalidChainInfo` if it is too far in
+    /// the future for a `SystemTime`.
+    pub fn genesis_time(&self) -> Result<SystemTime, DrandClientError> {
+        self.chain_info.genesis_time_as_system_time()
+    }
+
+    /// the round that is current now, like `round_for_time` with the client's chain info
+    pub fn current_round(&self) -> Result<u64, DrandClientError> {
+        self.round_at(self.clock.now())
+    }
+
+    /// the round that was current at `time`. Times before genesis fail with
+    /// `RoundBeforeGenesis`; genesis itself is round 1.
+    pub fn round_at(&self, time: SystemTime) -> Result<u64, DrandClientError> {
+        round_for_time(&self.chain_info, time)
+    }
+
+    /// the scheduled time of `round`, like `time_for_round` with the client's chain info
+    pub fn time_of_round(&self, round: u64) -> Result<SystemTime, DrandClientError> {
+        time_for_round(&self.chain_info, round)
+    }
+
+    /// like `time_of_round`, for a round that is known not to be 0
+    pub fn time_of(&self, round: RoundNumber) -> Result<SystemTime, DrandClientError> {
+        self.time_of_round(round.get())
+    }
+
+    /// the number of the next round to be emitted, like `next_round_after` with the current
+    /// time
+    pub fn next_round_number(&self) -> Result<u64, DrandClientError> {
+        next_round_after(&self.chain_info, self.clock.now())
+    }
+
+    /// how long until the next round is emitted, e.g. to sleep between polls. Before genesis
+    /// this is the time until round 1; exactly on a round boundary it is a full period.
+    pub fn duration_until_next_round(&self) -> Result<Duration, DrandClientError> {
+        duration_until_next_round_at(&self.chain_info, self.clock.now())
+    }
+
+    /// whether `beacon`'s round was scheduled at most `tolerance` ago by the client's clock,
+    /// e.g. to reject beacons older than two periods. A round scheduled in the future counts
+    /// as fresh, as a clock running slightly behind the chain would otherwise reject the
+    /// newest beacon.
+    pub fn is_fresh(&self, beacon: &Beacon, tolerance: Duration) -> bool {
+        match beacon.age_at(&self.chain_info, self.clock.now()) {
+            Ok(age) => age <= tolerance,
+            Err(_) => time_for_round(&self.chain_info, beacon.round_number).is_ok(),
+        }
+    }
+
+    /// the name of the chain the client is connected to, e.g. `"default"` or `"quicknet"`
+    pub fn beacon_id(&self) -> &str {
+        &self.chain_info.metadata.beacon_id
+    }
+
+    /// whether the client is connected to the relay's default chain
+    pub fn is_default_chain(&self) -> bool {
+        self.beacon_id() == "default"
+    }
+
+    /// fetch the latest available randomness beacon. Unless disabled with
+    /// `disable_latest_cache`, a beacon fetched less than one period ago is returned without
+    /// another request.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(url = %self.base_url))
+    )]
+    pub fn latest_randomness(&self) -> Result<VerifiedBeacon, DrandClientError> {
+        let expected_round = round_for_time(&self.chain_info, self.clock.now())?;
+        if let Some(beacon) = self.cached_latest(expected_round) {
+            return Ok(beacon);
+        }
+
+        let beacon = self.fetch_beacon(&self.url_strategy.latest_url(&self.base_url))?;
+
+        // it could take some time to aggregate beacons, so we tolerate a round early for latest
+        if beacon.round_number < expected_round.saturating_sub(self.latest_tolerance) {
+            if self.latest_fallback {
+                let beacon = self.fetch_expected_round(expected_round)?;
+                self.cache_latest(&beacon);
+                return Ok(beacon);
+            }
             return Err(DrandClientError::InvalidBeacon);
         }
 
+        self.observe_publish_delay(beacon.round_number, self.clock.now());
+
+        self.cache_latest(&beacon);
         Ok(beacon)
     }
 
+    // a cached beacon is subject to the same staleness check as a fetched one. Only verified
+    // beacons are cached.
+    fn cached_latest(&self, expected_round: u64) -> Option<VerifiedBeacon> {
+        let cache = self.latest_cache.as_ref()?.lock().ok()?;
+        let period = self.chain_info.period_as_duration();
+        cache
+            .as_ref()?
+            .get(&self.chain_info.chain_hash, period, self.clock.now())
+            .filter(|beacon| {
+                beacon.round_number >= expected_round.saturating_sub(self.latest_tolerance)
+            })
+            .cloned()
+            .map(VerifiedBeacon::assume_verified)
+    }
+
+    fn cache_latest(&self, beacon: &VerifiedBeacon) {
+        if let Some(Ok(mut cache)) = self.latest_cache.as_ref().map(|cache| cache.lock()) {
+            *cache = Some(LatestBeaconCache::new(
+                &self.chain_info.chain_hash,
+                beacon.as_ref().clone(),
+                self.clock.now(),
+            ));
+        }
+        self.cache_round(beacon);
+    }
+
+    fn cached_round(&self, round_number: u64) -> Option<VerifiedBeacon> {
+        let mut cache = self.round_cache.as_ref()?.lock().ok()?;
+        cache
+            .get(round_number)
+            .cloned()
+            .map(VerifiedBeacon::assume_verified)
+    }
+
+    fn cache_round(&self, beacon: &VerifiedBeacon) {
+        if let Some(Ok(mut cache)) = self.round_cache.as_ref().map(|cache| cache.lock()) {
+            cache.insert(beacon.as_ref().clone());
+        }
+    }
+
+    /// fetch the latest beacon only if its round is newer than `last_known_round`, returning
+    /// `None` otherwise. The round number is checked before the signature, so polling an
+    /// unchanged chain doesn't pay for BLS verification. Like in `latest_randomness`, a beacon
+    /// more than `latest_tolerance` rounds behind the current round fails with `InvalidBeacon`.
+    pub fn latest_randomness_if_new(
+        &self,
+        last_known_round: u64,
+    ) -> Result<Option<VerifiedBeacon>, DrandClientError> {
+        let expected_round = round_for_time(&self.chain_info, self.clock.now())?;
+        let beacon = self.fetch_unverified_beacon(&self.url_strategy.latest_url(&self.base_url))?;
+        if beacon.round_number < expected_round.saturating_sub(self.latest_tolerance) {
+            return Err(DrandClientError::InvalidBeacon);
+        }
+        if beacon.round_number <= last_known_round {
+            return Ok(None);
+        }
+
+        let beacon = self.verify_fetched(beacon)?;
+        self.observe_publish_delay(beacon.round_number, self.clock.now());
+        Ok(Some(beacon))
+    }
+
+    /// the round number of the latest beacon, WITHOUT verifying its signature. Only the
+    /// `round` field is parsed, and it must be within `latest_tolerance` of the current round
+    /// like in `latest_randomness`. Good enough for showing progress, but the relay could
+    /// report any recent round, so don't use it for anything security-critical.
+    pub fn latest_round_number(&self) -> Result<u64, DrandClientError> {
+        let expected_round = round_for_time(&self.chain_info, self.clock.now())?;
+        let body = self.fetch(&self.url_strategy.latest_url(&self.base_url))?;
+        let round_number = serde_json::from_str::<RoundOnly>(&body)
+            .map_err(|_| DrandClientError::InvalidBeacon)?
+            .round_number;
+        if round_number < expected_round.saturating_sub(self.latest_tolerance) {
+            return Err(DrandClientError::InvalidBeacon);
+        }
+        Ok(round_number)
+    }
+
+    /// how long after its scheduled time the relay typically publishes a round, estimated
+    /// from `latest_randomness` calls and `Watcher` fetches that observed a new round
+    /// appearing. `None` until such a transition has been seen.
+    pub fn publish_delay_estimate(&self) -> Option<Duration> {
+        self.publish_delay
+            .lock()
+            .ok()
+            .and_then(|estimator| estimator.estimate())
+    }
+
+    pub(crate) fn observe_publish_delay(&self, round_number: u64, observed_at: SystemTime) {
+        if let Ok(mut estimator) = self.publish_delay.lock() {
+            estimator.observe(&self.chain_info, round_number, observed_at);
+        }
+    }
+
+    fn check_round1(&self, expected_randomness: &[u8]) -> Result<(), DrandClientError> {
+        if self.randomness(1)?.randomness != expected_randomness {
+            return Err(InvalidChainInfo);
+        }
+        Ok(())
+    }
+
+    fn check_chain_hash(&self, expected_hash: &[u8]) -> Result<(), DrandClientError> {
+        if self.chain_info.chain_hash != expected_hash {
+            return Err(DrandClientError::ChainHashMismatch {
+                expected: expected_hash.to_vec(),
+                actual: self.chain_info.chain_hash.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    fn fetch_expected_round(
+        &self,
+        expected_round: u64,
+    ) -> Result<VerifiedBeacon, DrandClientError> {
+        (expected_round.saturating_sub(1)..=expected_round)
+            .rev()
+            .find_map(|round_number| self.randomness(round_number).ok())
+            .ok_or(DrandClientError::InvalidBeacon)
+    }
+
     /// fetch a randomness beacon for a specific round
-    pub fn randomness(&self, round_number: u64) -> Result<Beacon, DrandClientError> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(url = %self.base_url, round = round_number)
+        )
+    )]
+    pub fn randomness(&self, round_number: u64) -> Result<VerifiedBeacon, DrandClientError> {
         if round_number == 0 {
             Err(InvalidRound)
+        } else if let Some(beacon) = self.cached_round(round_number) {
+            Ok(beacon)
         } else {
-            let beacon = self.fetch_beacon_tag(&format!("{round_number}"))?;
-            if beacon.round_number != round_number {
-                return Err(DrandClientError::InvalidBeacon);
+            self.fetch_round(round_number)
+        }
+    }
+
+    // `randomness` for a round other than 0, always asking the relay and caching the result.
+    // Fetches whose timing feeds the publish delay estimate go through here, as a cache hit
+    // says nothing about when the relay published the round.
+    pub(crate) fn fetch_round(
+        &self,
+        round_number: u64,
+    ) -> Result<VerifiedBeacon, DrandClientError> {
+        let beacon =
+            self.fetch_beacon(&self.url_strategy.round_url(&self.base_url, round_number))?;
+        if beacon.round_number != round_number {
+            return Err(DrandClientError::InvalidBeacon);
+        }
+        self.cache_round(&beacon);
+        Ok(beacon)
+    }
+
+    /// like `randomness`, for a round that is known not to be 0
+    pub fn randomness_for(&self, round: RoundNumber) -> Result<VerifiedBeacon, DrandClientError> {
+        self.randomness(round.get())
+    }
+
+    /// fetch and verify the beacon of the round that was current at `time`. Times before
+    /// genesis fail with `RoundBeforeGenesis`, and times whose round hasn't
+    /// happened yet with `RoundNotYetAvailable`, without a request.
+    pub fn randomness_at(&self, time: SystemTime) -> Result<VerifiedBeacon, DrandClientError> {
+        let round_number = round_for_time(&self.chain_info, time)?;
+        if round_number > round_for_time(&self.chain_info, self.clock.now())? {
+            return Err(DrandClientError::RoundNotYetAvailable);
+        }
+        self.randomness(round_number)
+    }
+
+    /// the old name of `randomness_at`. Times whose round hasn't happened yet now fail with
+    /// `RoundNotYetAvailable` rather than `InvalidRound`.
+    #[deprecated(note = "renamed to `randomness_at`")]
+    pub fn randomness_at_time(&self, time: SystemTime) -> Result<VerifiedBeacon, DrandClientError> {
+        self.randomness_at(time)
+    }
+
+    /// fetch the beacons for an arbitrary set of rounds concurrently, returning each round
+    /// with its result in the order of `rounds`. The rounds are spread over at most
+    /// `MAX_BATCH_THREADS` threads, each fetching its share in turn.
+    pub fn randomness_batch(
+        &self,
+        rounds: &[u64],
+    ) -> Vec<(u64, Result<VerifiedBeacon, DrandClientError>)>
+    where
+        T: Sync,
+    {
+        self.fetch_concurrently(
+            rounds.len() as u64,
+            |index| rounds[index as usize],
+            MAX_BATCH_THREADS,
+            false,
+        )
+    }
+
+    /// fetch and verify every beacon in `rounds` on up to `concurrency` threads, in round
+    /// order. The first failure stops all threads and is returned; use `randomness_batch`
+    /// to get a result per round instead. Ranges starting at round 0 fail with
+    /// `InvalidRound`.
+    pub fn randomness_range(
+        &self,
+        rounds: RangeInclusive<u64>,
+        concurrency: usize,
+    ) -> Result<Vec<VerifiedBeacon>, DrandClientError>
+    where
+        T: Sync,
+    {
+        if *rounds.start() == 0 {
+            return Err(InvalidRound);
+        }
+        let (start, end) = rounds.into_inner();
+        let count = if start > end { 0 } else { end - start + 1 };
+        self.fetch_concurrently(count, |index| start + index, concurrency.max(1), true)
+            .into_iter()
+            .map(|(_, result)| result)
+            .collect()
+    }
+
+    // fetch the `count` rounds `round_at(0)`, `round_at(1)`, ... on at most `threads`
+    // threads, each fetching a contiguous share in turn, so the rounds are never collected
+    // up front. With `fail_fast`, every thread stops after the first failure, so only the
+    // results up to it are complete.
+    fn fetch_concurrently(
+        &self,
+        count: u64,
+        round_at: impl Fn(u64) -> u64 + Sync,
+        threads: usize,
+        fail_fast: bool,
+    ) -> Vec<(u64, Result<VerifiedBeacon, DrandClientError>)>
+    where
+        T: Sync,
+    {
+        if count == 0 {
+            return Vec::new();
+        }
+
+        let failed = AtomicBool::new(false);
+        let chunk_size = count.div_ceil(threads as u64);
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads as u64)
+                .map(|thread| thread.saturating_mul(chunk_size))
+                .take_while(|&chunk_start| chunk_start < count)
+                .map(|chunk_start| {
+                    let chunk = chunk_start..chunk_start.saturating_add(chunk_size).min(count);
+                    let failed = &failed;
+                    let round_at = &round_at;
+                    scope.spawn(move || {
+                        let mut results = Vec::new();
+                        for index in chunk {
+                            if fail_fast && failed.load(Ordering::Relaxed) {
+                                break;
+                            }
+                            let round_number = round_at(index);
+                            let result = self.randomness(round_number);
+                            if result.is_err() {
+                                failed.store(true, Ordering::Relaxed);
+                            }
+                            results.push((round_number, result));
+                        }
+                        results
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("batch fetch thread panicked"))
+                .collect()
+        })
+    }
+
+    /// fetch a beacon for a specific round as a plain `Beacon`. Only the JSON, the round number
+    /// and whatever the client's `VerificationPolicy` asks for are checked, so under
+    /// `RandomnessHashOnly` or `None` the beacon is exactly as trustworthy as the relay. Use
+    /// `randomness` unless the beacon is verified elsewhere.
+    pub fn randomness_unverified(&self, round_number: u64) -> Result<Beacon, DrandClientError> {
+        if round_number == 0 {
+            return Err(InvalidRound);
+        }
+        let beacon = self
+            .fetch_unverified_beacon(&self.url_strategy.round_url(&self.base_url, round_number))?;
+        if beacon.round_number != round_number {
+            return Err(DrandClientError::InvalidBeacon);
+        }
+        self.check_unverified(&beacon)?;
+        Ok(beacon)
+    }
+
+    /// fetch the latest beacon WITHOUT checking that it is recent, verified as far as the
+    /// `VerificationPolicy` asks, like `randomness_unverified`. Use `latest_randomness` unless the beacon is verified
+    /// elsewhere.
+    pub fn latest_randomness_unverified(&self) -> Result<Beacon, DrandClientError> {
+        let beacon = self.fetch_unverified_beacon(&self.url_strategy.latest_url(&self.base_url))?;
+        self.check_unverified(&beacon)?;
+        Ok(beacon)
+    }
+
+    /// fetch every beacon from `target` up to and including the latest round, in round order,
+    /// the rounds before the latest one on up to `MAX_BATCH_THREADS` threads.
+    /// Returns `RoundNotYetAvailable` if the chain has not reached `target` yet.
+    /// All beacons are collected into one `Vec`, so for a `target` far behind the chain tip,
+    /// iterate lazily with `rounds` or `fetch_range` instead.
+    pub fn sync_to_round(&self, target: u64) -> Result<Vec<VerifiedBeacon>, DrandClientError>
+    where
+        T: Sync,
+    {
+        self.sync_to_round_with(target, MAX_BATCH_THREADS)
+    }
+
+    /// `sync_to_round`, fetching the rounds before the latest one through `randomness_range`
+    /// with `concurrency` threads
+    pub fn sync_to_round_with(
+        &self,
+        target: u64,
+        concurrency: usize,
+    ) -> Result<Vec<VerifiedBeacon>, DrandClientError>
+    where
+        T: Sync,
+    {
+        if target == 0 {
+            return Err(InvalidRound);
+        }
+
+        let latest = self.latest_randomness()?;
+        if latest.round_number < target {
+            return Err(DrandClientError::RoundNotYetAvailable);
+        }
+
+        let mut beacons = self.randomness_range(target..=latest.round_number - 1, concurrency)?;
+        beacons.push(latest);
+        Ok(beacons)
+    }
+
+    fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        #[cfg(feature = "tracing")]
+        let started = std::time::Instant::now();
+        let result = match &self.retry {
+            Some(config) => retry(config, || self.transport.fetch(url)),
+            None => self.transport.fetch(url),
+        };
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            url,
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            ok = result.is_ok(),
+            "fetched from the relay"
+        );
+        result
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip(self),
+            fields(scheme_id = %self.chain_info.scheme_id)
+        )
+    )]
+    fn fetch_beacon(&self, url: &str) -> Result<VerifiedBeacon, DrandClientError> {
+        let beacon = self.fetch_unverified_beacon(url)?;
+        self.verify_fetched(beacon)
+    }
+
+    fn fetch_unverified_beacon(&self, url: &str) -> Result<Beacon, DrandClientError> {
+        match self.fetch(url) {
+            Err(e) => Err(e.into()),
+            Ok(body) => {
+                serde_json::from_str::<Beacon>(&body).map_err(|_| DrandClientError::InvalidBeacon)
             }
-            Ok(beacon)
         }
     }
 
-    fn fetch_beacon_tag(&self, tag: &str) -> Result<Beacon, DrandClientError> {
-        let url = format!("{}/public/{}", self.base_url, tag);
+    pub(crate) fn verify_fetched(
+        &self,
+        beacon: Beacon,
+    ) -> Result<VerifiedBeacon, DrandClientError> {
+        let round_number = beacon.round_number;
+        verify_beacon(
+            &self.chain_info.scheme_id,
+            &self.chain_info.public_key,
+            beacon,
+        )
+        .map_err(|e| self.verification_error(round_number, e))
+    }
 
-        match self.transport.fetch(&url) {
-            Err(_) => Err(DrandClientError::NotResponding),
+    // the checks the `VerificationPolicy` asks of the `_unverified` methods
+    fn check_unverified(&self, beacon: &Beacon) -> Result<(), DrandClientError> {
+        match self.verification {
+            VerificationPolicy::Full => verify_beacon_ref(
+                &self.chain_info.scheme_id,
+                &self.chain_info.public_key,
+                beacon,
+            )
+            .map_err(|e| self.verification_error(beacon.round_number, e)),
+            VerificationPolicy::RandomnessHashOnly => verify_randomness(beacon)
+                .map_err(|e| self.verification_error(beacon.round_number, e)),
+            VerificationPolicy::None => Ok(()),
+        }
+    }
 
-            Ok(body) => match serde_json::from_str::<Beacon>(&body) {
-                Ok(beacon) => {
-                    verify_beacon(
-                        &self.chain_info.scheme_id,
-                        &self.chain_info.public_key,
-                        &beacon,
-                    )
-                    .map_err(|_| DrandClientError::FailedVerification)?;
-                    Ok(beacon)
-                }
-                Err(_) => Err(DrandClientError::InvalidBeacon),
-            },
+    fn verification_error(&self, _round_number: u64, e: VerificationError) -> DrandClientError {
+        match e {
+            VerificationError::VerificationDisabled => DrandClientError::VerificationDisabled,
+            e => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(round = _round_number, error = %e, "beacon failed verification");
+                self.verification_failure(e)
+            }
         }
     }
+
+    // a beacon that fails verification may mean the relay has swapped the chain it serves,
+    // so we check `/info` before reporting a plain verification failure
+    fn verification_failure(&self, e: VerificationError) -> DrandClientError {
+        self.fetch_current_chain_info()
+            .ok()
+            .and_then(|info| self.migration_to(&info))
+            .unwrap_or(DrandClientError::FailedVerification(e))
+    }
+
+    // the chain info the relay serves now, fetched the same way as beacons
+    fn fetch_current_chain_info(&self) -> Result<ChainInfo, DrandClientError> {
+        fetch_chain_info_from(
+            &self.transport,
+            &self.base_url,
+            self.url_strategy.as_ref(),
+            self.retry.as_ref(),
+        )
+    }
 }
 
+/// the round that was current at `time`, the inverse of `time_for_round`: round 1 from
+/// genesis until one period later, and so on. Times before genesis fail with
+/// `RoundBeforeGenesis`.
 pub fn round_for_time(chain_info: &ChainInfo, time: SystemTime) -> Result<u64, DrandClientError> {
     let epoch_seconds = time
         .duration_since(UNIX_EPOCH)
         .map_err(|_| DrandClientError::UnexpectedError)?
         .as_secs();
 
-    if epoch_seconds <= chain_info.genesis_time {
+    round_for_epoch_seconds(chain_info, epoch_seconds)
+}
+
+/// the scheduled time of `round`, the inverse of `round_for_time`: round 1 is at genesis and
+/// each later round one period after the one before. Rounds too far in the future to be
+/// represented fail with `UnexpectedError`.
+pub fn time_for_round(chain_info: &ChainInfo, round: u64) -> Result<SystemTime, DrandClientError> {
+    if round == 0 {
+        return Err(DrandClientError::InvalidRound);
+    }
+    if chain_info.period_seconds == 0 {
+        return Err(InvalidChainInfo);
+    }
+
+    (round - 1)
+        .checked_mul(chain_info.period_seconds as u64)
+        .and_then(|offset| offset.checked_add(chain_info.genesis_time))
+        .and_then(|epoch_seconds| UNIX_EPOCH.checked_add(Duration::from_secs(epoch_seconds)))
+        .ok_or(DrandClientError::UnexpectedError)
+}
+
+/// the first round scheduled strictly after `time`: round 1 before genesis, and the round
+/// after the current one from genesis on, so a time exactly on a round boundary is a full
+/// period away from the next round. A genesis time that isn't a valid `SystemTime` fails
+/// with `InvalidChainInfo`.
+pub fn next_round_after(chain_info: &ChainInfo, time: SystemTime) -> Result<u64, DrandClientError> {
+    if chain_info.period_seconds == 0 {
+        return Err(InvalidChainInfo);
+    }
+
+    match time.duration_since(chain_info.genesis_time_as_system_time()?) {
+        Ok(elapsed) => Ok(elapsed.as_secs() / chain_info.period_seconds as u64 + 2),
+        Err(_) => Ok(1),
+    }
+}
+
+// how long after `now` the next round is scheduled
+#[cfg(feature = "serde")]
+fn duration_until_next_round_at(
+    chain_info: &ChainInfo,
+    now: SystemTime,
+) -> Result<Duration, DrandClientError> {
+    time_for_round(chain_info, next_round_after(chain_info, now)?)?
+        .duration_since(now)
+        .map_err(|_| DrandClientError::UnexpectedError)
+}
+
+/// the round for a Unix timestamp in seconds, using the same arithmetic as `round_for_time`.
+/// Taking a plain `u64` makes it callable from FFI and WebAssembly hosts, which can't easily
+/// construct a `SystemTime`.
+pub fn round_for_epoch_seconds(
+    chain_info: &ChainInfo,
+    epoch_seconds: u64,
+) -> Result<u64, DrandClientError> {
+    if chain_info.period_seconds == 0 {
+        return Err(InvalidChainInfo);
+    }
+
+    // genesis itself is the start of round 1, as in `time_for_round`
+    if epoch_seconds < chain_info.genesis_time {
         return Err(DrandClientError::RoundBeforeGenesis);
     }
 
@@ -123,61 +1114,174 @@ pub fn round_for_time(chain_info: &ChainInfo, time: SystemTime) -> Result<u64, D
 }
 
 #[derive(Error, Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum DrandClientError {
     #[error("invalid round")]
     InvalidRound,
     #[error("invalid beacon")]
     InvalidBeacon,
-    #[error("beacon failed verification")]
-    FailedVerification,
+    #[error("beacon failed verification: {0}")]
+    FailedVerification(#[source] VerificationError),
+    #[error("the relay now serves a different chain: {}", hex::encode(.new_hash))]
+    ChainMigrated {
+        old_hash: Vec<u8>,
+        new_hash: Vec<u8>,
+        new_info: Box<ChainInfo>,
+    },
     #[error("invalid chain info")]
     InvalidChainInfo,
+    #[error("the relay's chain info doesn't match the pinned chain")]
+    ChainInfoMismatch,
+    #[error(
+        "expected chain {} but the relay serves {}",
+        hex::encode(.expected),
+        hex::encode(.actual)
+    )]
+    ChainHashMismatch { expected: Vec<u8>, actual: Vec<u8> },
+    #[error(
+        "the relay has no chain {beacon_id:?}, only {}{}",
+        .available.join(", "),
+        unreadable_chains(.unreadable)
+    )]
+    UnknownBeaconId {
+        beacon_id: String,
+        available: Vec<String>,
+        /// the hashes of listed chains whose info couldn't be fetched or validated
+        unreadable: Vec<String>,
+    },
+    #[error("invalid proxy URL")]
+    InvalidProxy,
+    #[error("the relay kept returning incomplete chain info")]
+    ChainInfoNotReady,
     #[error("not responding")]
     NotResponding,
+    #[error("the relay is rate limiting requests")]
+    RateLimited,
+    #[error("TLS handshake with the relay failed: {message}")]
+    Tls { message: String },
     #[error("round before genesis")]
     RoundBeforeGenesis,
+    #[error("round not yet available")]
+    RoundNotYetAvailable,
+    #[error("signature verification is disabled in this build")]
+    VerificationDisabled,
     #[error("unexpected error")]
     UnexpectedError,
 }
 
+// the `UnknownBeaconId` message's note on chains that were skipped
+fn unreadable_chains(unreadable: &[String]) -> String {
+    if unreadable.is_empty() {
+        return String::new();
+    }
+    format!(" (the info of {} couldn't be read)", unreadable.join(", "))
+}
+
+impl DrandClientError {
+    /// the error for an HTTP error response, for gateways and proxies that relay drand
+    /// responses. The mapping depends only on `status`; `body`, if the response had one, is
+    /// logged with the `tracing` feature.
+    pub fn from_http_status(status: u16, _body: Option<&str>) -> DrandClientError {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(status, body = _body, "relay error response");
+        match status {
+            404 | 503 => DrandClientError::NotResponding,
+            429 => DrandClientError::RateLimited,
+            _ => DrandClientError::UnexpectedError,
+        }
+    }
+}
+
+impl From<TransportError> for DrandClientError {
+    /// error responses are mapped with `from_http_status`, while failures without an error
+    /// status, like a dropped connection, mean the relay is not responding
+    fn from(e: TransportError) -> Self {
+        match e {
+            TransportError::NotFound => DrandClientError::from_http_status(404, None),
+            TransportError::Unexpected {
+                status_code: Some(status),
+            } if status >= 400 => DrandClientError::from_http_status(status, None),
+            TransportError::Unexpected { .. } => DrandClientError::NotResponding,
+            TransportError::Tls { message } => DrandClientError::Tls { message },
+        }
+    }
+}
+
 #[derive(Error, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum TransportError {
     #[error("not found")]
     NotFound,
-    #[error("unexpected")]
-    Unexpected,
+    /// `status_code` is the HTTP status of the response, or `None` if the request failed
+    /// without one, e.g. because the connection couldn't be established
+    #[error("unexpected{}", .status_code.map(|code| format!(" HTTP status {code}")).unwrap_or_default())]
+    Unexpected { status_code: Option<u16> },
+    /// the TLS handshake failed, e.g. because the relay's certificate isn't trusted
+    #[error("TLS handshake failed: {message}")]
+    Tls { message: String },
+}
+
+impl TransportError {
+    /// the error for an HTTP error status, as the HTTP transports report it. Converted into
+    /// a `DrandClientError`, it is the one `DrandClientError::from_http_status` gives.
+    pub fn from_http_status(status: u16) -> TransportError {
+        match status {
+            404 => TransportError::NotFound,
+            status => TransportError::Unexpected {
+                status_code: Some(status),
+            },
+        }
+    }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "http"))]
 mod test {
-    use crate::chain_info::{ChainInfo, ChainInfoMetadata};
-    use crate::verify::SchemeID::PedersenBlsChained;
+    use crate::chain_info::ChainInfo;
+    use crate::chaos::{ChaosTransport, Fault};
+    use crate::http::{HttpTransport, RetryConfig};
+    use crate::testing::FixedClock;
+    use crate::testkit::{
+        self, TestChain, TestTransport, MAINNET_INFO_JSON, MAINNET_ROUND_TWO_JSON,
+    };
+    use crate::url::{DefaultUrlStrategy, UrlStrategy};
+    use crate::verify::SchemeID::{PedersenBlsChained, PedersenBlsUnchained, UnchainedOnG1RFC9380};
+    use crate::verify::VerificationError::{
+        ChainedBeaconNeedsPreviousSignature, EmptySignature, InvalidPublicKey, InvalidRandomness,
+        SignatureFailedVerification,
+    };
+    use crate::verify::VerifiedBeacon;
     use crate::DrandClientError::InvalidRound;
-    use crate::{new_http_client, DrandClient, DrandClientError, Transport, TransportError};
-    use std::time::{SystemTime, UNIX_EPOCH};
+    use crate::{
+        duration_until_next_round_at, fetch_chain_info, fetch_chain_info_from, new_http_client,
+        next_round_after, round_for_epoch_seconds, round_for_time, time_for_round, DrandClient,
+        DrandClientError, RoundNumber, Transport, TransportError, VerificationPolicy,
+    };
+    use sha2::{Digest, Sha256};
+    use std::cell::{Cell, RefCell};
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
     #[test]
     fn request_chained_randomness_success() -> Result<(), DrandClientError> {
-        let chained_url = "https://api.drand.sh";
-        let client = new_http_client(chained_url)?;
-        let randomness = client.latest_randomness()?;
-        assert!(randomness.round_number > 0);
+        let chain = TestChain::new(PedersenBlsChained, 30, 1595431050);
+        let client = relay_client(&chain, 3)?;
+        assert_eq!(client.latest_randomness()?, chain.sign_round(3));
         Ok(())
     }
 
     #[test]
     fn request_unchained_randomness_success() -> Result<(), DrandClientError> {
-        let unchained_url = "https://pl-eu.testnet.drand.sh/7672797f548f3f4748ac4bf3352fc6c6b6468c9ad40ad456a397545c6e2df5bf";
-        let client = new_http_client(unchained_url)?;
-        let randomness = client.latest_randomness()?;
-        assert!(randomness.round_number > 0);
+        let chain = TestChain::new(PedersenBlsUnchained, 3, 1677685200);
+        let client = relay_client(&chain, 3)?;
+        assert_eq!(client.latest_randomness()?, chain.sign_round(3));
         Ok(())
     }
 
     #[test]
     fn request_genesis_returns_error() -> Result<(), DrandClientError> {
-        let chained_url = "https://api.drand.sh";
-        let client = new_http_client(chained_url)?;
+        let chain = TestChain::new(PedersenBlsChained, 30, 1595431050);
+        let client = relay_client(&chain, 3)?;
         let result = client.randomness(0);
         assert!(result.is_err());
         assert_eq!(result.unwrap_err(), InvalidRound);
@@ -186,23 +1290,23 @@ mod test {
 
     #[test]
     fn request_g1g2swapped_rfc_beacon_succeeds() -> Result<(), DrandClientError> {
-        let unchained_url =
-            "https://api.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971";
-        let client = new_http_client(unchained_url)?;
-        client.randomness(1)?;
+        let chain = TestChain::new(UnchainedOnG1RFC9380, 3, 1692803367);
+        let client = relay_client(&chain, 3)?;
+        assert_eq!(client.randomness(1)?, chain.sign_round(1));
         Ok(())
     }
 
     #[test]
     fn request_g1g2swapped_rfc_latest_succeeds() -> Result<(), DrandClientError> {
-        let unchained_url =
-            "https://api.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971";
-        let client = new_http_client(unchained_url)?;
-        client.latest_randomness()?;
+        let chain = TestChain::new(UnchainedOnG1RFC9380, 3, 1692803367);
+        let client = relay_client(&chain, 3)?;
+        assert_eq!(client.latest_randomness()?, chain.sign_round(3));
         Ok(())
     }
 
+    // the testkit can't sign bn254 beacons, so this one still needs the public relay
     #[test]
+    #[ignore = "needs the public api.drand.sh relay"]
     fn request_bn254_unchained_on_g1_latest_succeeds() -> Result<(), DrandClientError> {
         let unchained_url =
             "https://api.drand.sh/04f1e9062b8a81f848fded9c12306733282b2727ecced50032187751166ec8c3";
@@ -211,26 +1315,25 @@ mod test {
         Ok(())
     }
 
+    // a client set up like `new_http_client` would, with the chain info fetched from the
+    // relay, for a relay serving `chain` up to round `latest` at the time of round `latest`
+    fn relay_client(
+        chain: &TestChain,
+        latest: u64,
+    ) -> Result<DrandClient<TestTransport>, DrandClientError> {
+        let transport = chain.transport(latest);
+        let info = fetch_chain_info(&transport, "api.drand.sh")?;
+        Ok(DrandClient::new(transport, "api.drand.sh", info)
+            .with_clock(FixedClock::at_round(chain.chain_info(), latest)?))
+    }
+
     #[test]
     fn request_mismatching_round_fails() -> Result<(), DrandClientError> {
-        let info = ChainInfo {
-            scheme_id: PedersenBlsChained,
-            public_key: hex::decode("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31").unwrap(),
-            chain_hash: hex::decode("8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce").unwrap(),
-            group_hash: hex::decode("176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a").unwrap(),
-            genesis_time: 1595431050,
-            period_seconds: 30,
-            metadata: ChainInfoMetadata {
-                beacon_id: "default".to_string(),
-            },
-        };
-        let beacon = "{\"round\":2,\"randomness\":\"e8fee7dac6eb2b89df97d631cfccedbada7d5d05495bb546eef462e4145fdf8f\",\"signature\":\"aa18facd2d51b616511d542de6f9af8a3b920121401dad1434ed1db4a565f10e04fad8d9b2b4e3e0094364374caafe9b10478bf75650124831509c638b5a36a7a232ec70289f8751a2adb47fc32eb70b57dc81c39d48cbcac9fec46cdfc31663\",\"previous_signature\":\"8d61d9100567de44682506aea1a7a6fa6e5491cd27a0a0ed349ef6910ac5ac20ff7bc3e09d7c046566c9f7f3c6f3b10104990e7cb424998203d8f7de586fb7fa5f60045417a432684f85093b06ca91c769f0e7ca19268375e659c2a2352b4655\"";
-        let transport = MockTransport { beacon };
-        let client = DrandClient {
-            transport,
-            base_url: "api.drand.sh",
-            chain_info: info,
+        let info = mainnet_info(1595431050);
+        let transport = MockTransport {
+            beacon: MAINNET_ROUND_TWO_JSON,
         };
+        let client = DrandClient::new(transport, "api.drand.sh", info);
 
         client
             .randomness(4)
@@ -240,24 +1343,11 @@ mod test {
 
     #[test]
     fn request_latest_round_too_far_in_past_fails() -> Result<(), DrandClientError> {
-        let info = ChainInfo {
-            scheme_id: PedersenBlsChained,
-            public_key: hex::decode("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31").unwrap(),
-            chain_hash: hex::decode("8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce").unwrap(),
-            group_hash: hex::decode("176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a").unwrap(),
-            genesis_time: 1595431050,
-            period_seconds: 30,
-            metadata: ChainInfoMetadata {
-                beacon_id: "default".to_string(),
-            },
-        };
-        let beacon = "{\"round\":2,\"randomness\":\"e8fee7dac6eb2b89df97d631cfccedbada7d5d05495bb546eef462e4145fdf8f\",\"signature\":\"aa18facd2d51b616511d542de6f9af8a3b920121401dad1434ed1db4a565f10e04fad8d9b2b4e3e0094364374caafe9b10478bf75650124831509c638b5a36a7a232ec70289f8751a2adb47fc32eb70b57dc81c39d48cbcac9fec46cdfc31663\",\"previous_signature\":\"8d61d9100567de44682506aea1a7a6fa6e5491cd27a0a0ed349ef6910ac5ac20ff7bc3e09d7c046566c9f7f3c6f3b10104990e7cb424998203d8f7de586fb7fa5f60045417a432684f85093b06ca91c769f0e7ca19268375e659c2a2352b4655\"";
-        let transport = MockTransport { beacon };
-        let client = DrandClient {
-            transport,
-            base_url: "api.drand.sh",
-            chain_info: info,
+        let info = mainnet_info(1595431050);
+        let transport = MockTransport {
+            beacon: MAINNET_ROUND_TWO_JSON,
         };
+        let client = DrandClient::new(transport, "api.drand.sh", info);
 
         client
             .latest_randomness()
@@ -267,25 +1357,13 @@ mod test {
 
     #[test]
     fn request_latest_single_round_early_succeeds() -> Result<(), DrandClientError> {
-        let info = ChainInfo {
-            scheme_id: PedersenBlsChained,
-            public_key: hex::decode("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31").unwrap(),
-            chain_hash: hex::decode("8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce").unwrap(),
-            group_hash: hex::decode("176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a").unwrap(),
-            // here we set genesis so it should be round 3
-            genesis_time: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() - 60,
-            period_seconds: 30,
-            metadata: ChainInfoMetadata {
-                beacon_id: "default".to_string(),
-            },
-        };
-        let beacon = "{\"round\":2,\"randomness\":\"e8fee7dac6eb2b89df97d631cfccedbada7d5d05495bb546eef462e4145fdf8f\",\"signature\":\"aa18facd2d51b616511d542de6f9af8a3b920121401dad1434ed1db4a565f10e04fad8d9b2b4e3e0094364374caafe9b10478bf75650124831509c638b5a36a7a232ec70289f8751a2adb47fc32eb70b57dc81c39d48cbcac9fec46cdfc31663\",\"previous_signature\":\"8d61d9100567de44682506aea1a7a6fa6e5491cd27a0a0ed349ef6910ac5ac20ff7bc3e09d7c046566c9f7f3c6f3b10104990e7cb424998203d8f7de586fb7fa5f60045417a432684f85093b06ca91c769f0e7ca19268375e659c2a2352b4655\"}";
-        let transport = MockTransport { beacon };
-        let client = DrandClient {
-            transport,
-            base_url: "api.drand.sh",
-            chain_info: info,
+        // here we stop the clock at round 3
+        let info = mainnet_info(1595431050);
+        let clock = FixedClock::at_round(&info, 3)?;
+        let transport = MockTransport {
+            beacon: MAINNET_ROUND_TWO_JSON,
         };
+        let client = DrandClient::new(transport, "api.drand.sh", info).with_clock(clock);
 
         client
             .latest_randomness()
@@ -295,24 +1373,12 @@ mod test {
 
     #[test]
     fn request_latest_future_round_succeeds() -> Result<(), DrandClientError> {
-        let info = ChainInfo {
-            scheme_id: PedersenBlsChained,
-            public_key: hex::decode("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31").unwrap(),
-            chain_hash: hex::decode("8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce").unwrap(),
-            group_hash: hex::decode("176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a").unwrap(),
-            genesis_time: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() - 30,
-            period_seconds: 30,
-            metadata: ChainInfoMetadata {
-                beacon_id: "default".to_string(),
-            },
-        };
-        let beacon = "{\"round\":2,\"randomness\":\"e8fee7dac6eb2b89df97d631cfccedbada7d5d05495bb546eef462e4145fdf8f\",\"signature\":\"aa18facd2d51b616511d542de6f9af8a3b920121401dad1434ed1db4a565f10e04fad8d9b2b4e3e0094364374caafe9b10478bf75650124831509c638b5a36a7a232ec70289f8751a2adb47fc32eb70b57dc81c39d48cbcac9fec46cdfc31663\",\"previous_signature\":\"8d61d9100567de44682506aea1a7a6fa6e5491cd27a0a0ed349ef6910ac5ac20ff7bc3e09d7c046566c9f7f3c6f3b10104990e7cb424998203d8f7de586fb7fa5f60045417a432684f85093b06ca91c769f0e7ca19268375e659c2a2352b4655\"}";
-        let transport = MockTransport { beacon };
-        let client = DrandClient {
-            transport,
-            base_url: "api.drand.sh",
-            chain_info: info,
+        let info = mainnet_info(1595431050);
+        let clock = FixedClock::at_round(&info, 2)?;
+        let transport = MockTransport {
+            beacon: MAINNET_ROUND_TWO_JSON,
         };
+        let client = DrandClient::new(transport, "api.drand.sh", info).with_clock(clock);
 
         client
             .latest_randomness()
@@ -320,6 +1386,1434 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn sync_to_latest_round_returns_latest() -> Result<(), DrandClientError> {
+        // here we stop the clock at round 3
+        let info = mainnet_info(1595431050);
+        let clock = FixedClock::at_round(&info, 3)?;
+        let transport = MockTransport {
+            beacon: MAINNET_ROUND_TWO_JSON,
+        };
+        let client = DrandClient::new(transport, "api.drand.sh", info).with_clock(clock);
+
+        let beacons = client.sync_to_round(2)?;
+        assert_eq!(beacons.len(), 1);
+        assert_eq!(beacons[0].round_number, 2);
+
+        assert_eq!(
+            client.sync_to_round(3).unwrap_err(),
+            DrandClientError::RoundNotYetAvailable
+        );
+        assert_eq!(client.sync_to_round(0).unwrap_err(), InvalidRound);
+        Ok(())
+    }
+
+    #[test]
+    fn sync_to_round_returns_every_round_up_to_the_latest() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::new(
+            chain.transport(4),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        )
+        .with_clock(FixedClock::at_round(chain.chain_info(), 4)?);
+
+        let rounds = (2..=4)
+            .map(|round| chain.verified_round(round))
+            .collect::<Vec<_>>();
+        assert_eq!(client.sync_to_round(2)?, rounds);
+        assert_eq!(client.sync_to_round_with(2, 2)?, rounds);
+        assert_eq!(client.sync_to_round_with(2, 1)?, rounds);
+        Ok(())
+    }
+
+    #[test]
+    fn stale_latest_without_fallback_fails() {
+        // here we stop the clock at round 4
+        let info = mainnet_info(1595431050);
+        let clock = FixedClock::at_round(&info, 4).unwrap();
+        let transport =
+            UrlMockTransport::new(&[("api.drand.sh/public/latest", MAINNET_ROUND_TWO_JSON)]);
+        let client = DrandClient::new(transport, "api.drand.sh", info).with_clock(clock);
+
+        assert_eq!(
+            client.latest_randomness().unwrap_err(),
+            DrandClientError::InvalidBeacon
+        );
+        assert_eq!(
+            client.transport.requested.borrow().as_slice(),
+            &["api.drand.sh/public/latest".to_string()]
+        );
+    }
+
+    #[test]
+    fn stale_latest_with_fallback_requests_expected_rounds() {
+        // here we stop the clock at round 4
+        let info = mainnet_info(1595431050);
+        let clock = FixedClock::at_round(&info, 4).unwrap();
+        let transport =
+            UrlMockTransport::new(&[("api.drand.sh/public/latest", MAINNET_ROUND_TWO_JSON)]);
+        let client = DrandClient::new(transport, "api.drand.sh", info)
+            .with_latest_fallback(true)
+            .with_clock(clock);
+
+        assert_eq!(
+            client.latest_randomness().unwrap_err(),
+            DrandClientError::InvalidBeacon
+        );
+        assert_eq!(
+            client.transport.requested.borrow().as_slice(),
+            &[
+                "api.drand.sh/public/latest".to_string(),
+                "api.drand.sh/public/4".to_string(),
+                "api.drand.sh/public/3".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn stale_latest_with_fallback_returns_expected_round() -> Result<(), DrandClientError> {
+        // here we stop the clock at round 4
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let clock = FixedClock::at_round(chain.chain_info(), 4)?;
+        let transport = chain.transport(4).serve_latest(&chain.sign_round(2));
+        let client = DrandClient::new(transport, "api.drand.sh", chain.chain_info().clone())
+            .with_latest_fallback(true)
+            .with_clock(clock);
+
+        assert_eq!(client.latest_randomness()?, chain.sign_round(4));
+        Ok(())
+    }
+
+    #[test]
+    fn stale_latest_with_fallback_returns_previous_round() -> Result<(), DrandClientError> {
+        // here we stop the clock at round 4, but the relay only has round 3
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let clock = FixedClock::at_round(chain.chain_info(), 4)?;
+        let transport = chain.transport(3).serve_latest(&chain.sign_round(1));
+        let client = DrandClient::new(transport, "api.drand.sh", chain.chain_info().clone())
+            .with_latest_fallback(true)
+            .with_clock(clock);
+
+        assert_eq!(client.latest_randomness()?.round_number, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn fallback_is_not_used_when_latest_is_fresh() -> Result<(), DrandClientError> {
+        // here we stop the clock at round 3
+        let info = mainnet_info(1595431050);
+        let clock = FixedClock::at_round(&info, 3)?;
+        let transport =
+            UrlMockTransport::new(&[("api.drand.sh/public/latest", MAINNET_ROUND_TWO_JSON)]);
+        let client = DrandClient::new(transport, "api.drand.sh", info)
+            .with_latest_fallback(true)
+            .with_clock(clock);
+
+        assert_eq!(client.latest_randomness()?.round_number, 2);
+        assert_eq!(client.transport.requested.borrow().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn latest_randomness_is_cached_within_a_period() -> Result<(), DrandClientError> {
+        // here we stop the clock at round 3
+        let info = mainnet_info(1595431050);
+        let clock = FixedClock::at_round(&info, 3)?;
+        let transport =
+            UrlMockTransport::new(&[("api.drand.sh/public/latest", MAINNET_ROUND_TWO_JSON)]);
+        let client = DrandClient::new(transport, "api.drand.sh", info).with_clock(clock);
+
+        assert_eq!(client.latest_randomness()?, client.latest_randomness()?);
+        assert_eq!(client.transport.requested.borrow().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn latest_cache_expires_on_the_clients_clock() -> Result<(), DrandClientError> {
+        let info = mainnet_info(1595431050);
+        let clock = std::sync::Arc::new(FixedClock::at_round(&info, 3)?);
+        let transport =
+            UrlMockTransport::new(&[("api.drand.sh/public/latest", MAINNET_ROUND_TWO_JSON)]);
+        let client = DrandClient::new(transport, "api.drand.sh", info).with_clock(clock.clone());
+
+        client.latest_randomness()?;
+        clock.advance(Duration::from_secs(29));
+        client.latest_randomness()?;
+        assert_eq!(client.transport.requested.borrow().len(), 1);
+
+        clock.advance(Duration::from_secs(1));
+        assert!(client.latest_randomness().is_err());
+        assert_eq!(client.transport.requested.borrow().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn round_cache_serves_rounds_fetched_before() -> Result<(), DrandClientError> {
+        // here we stop the clock at round 3
+        let info = mainnet_info(1595431050);
+        let clock = FixedClock::at_round(&info, 3)?;
+        let transport = UrlMockTransport::new(&[
+            ("api.drand.sh/public/latest", MAINNET_ROUND_TWO_JSON),
+            ("api.drand.sh/public/2", MAINNET_ROUND_TWO_JSON),
+        ]);
+        let client = DrandClient::new(transport, "api.drand.sh", info)
+            .with_round_cache(8)
+            .with_clock(clock);
+
+        let latest = client.latest_randomness()?;
+        assert_eq!(client.randomness(2)?, latest);
+        assert_eq!(client.randomness(2)?, latest);
+        assert_eq!(
+            *client.transport.requested.borrow(),
+            ["api.drand.sh/public/latest"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn a_client_with_a_round_cache_can_be_shared_between_threads() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = std::sync::Arc::new(
+            DrandClient::new(
+                chain.transport(4),
+                "api.drand.sh",
+                chain.chain_info().clone(),
+            )
+            .with_round_cache(2),
+        );
+
+        let handles: Vec<_> = (1..=4)
+            .map(|round| {
+                let client = client.clone();
+                std::thread::spawn(move || client.randomness(round))
+            })
+            .collect();
+        for (round, handle) in (1..=4).zip(handles) {
+            assert_eq!(handle.join().unwrap(), Ok(chain.verified_round(round)));
+        }
+    }
+
+    #[test]
+    fn disabled_latest_cache_fetches_every_time() -> Result<(), DrandClientError> {
+        // here we stop the clock at round 3
+        let info = mainnet_info(1595431050);
+        let clock = FixedClock::at_round(&info, 3)?;
+        let transport =
+            UrlMockTransport::new(&[("api.drand.sh/public/latest", MAINNET_ROUND_TWO_JSON)]);
+        let client = DrandClient::new(transport, "api.drand.sh", info)
+            .disable_latest_cache(true)
+            .with_clock(clock);
+
+        client.latest_randomness()?;
+        client.latest_randomness()?;
+        assert_eq!(client.transport.requested.borrow().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn client_with_retry_recovers_from_failures() -> Result<(), DrandClientError> {
+        let transport = CountdownMockTransport::new(2, MAINNET_ROUND_TWO_JSON);
+        let client = DrandClient::new(transport, "api.drand.sh", mainnet_info(1595431050))
+            .with_retry(instant_retry(3));
+
+        let beacon = client.randomness(2)?;
+        assert_eq!(beacon.round_number, 2);
+        assert_eq!(
+            hex::encode(&beacon.randomness),
+            "e8fee7dac6eb2b89df97d631cfccedbada7d5d05495bb546eef462e4145fdf8f"
+        );
+        assert_eq!(client.transport.fail_for_n.load(Ordering::SeqCst), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn client_with_retry_gives_up_after_max_attempts() {
+        let transport = CountdownMockTransport::new(3, MAINNET_ROUND_TWO_JSON);
+        let client = DrandClient::new(transport, "api.drand.sh", mainnet_info(1595431050))
+            .with_retry(instant_retry(3));
+
+        assert_eq!(
+            client.randomness(2).unwrap_err(),
+            DrandClientError::NotResponding
+        );
+        assert_eq!(client.transport.fail_for_n.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn client_without_retry_surfaces_first_failure() {
+        let transport = CountdownMockTransport::new(2, MAINNET_ROUND_TWO_JSON);
+        let client = DrandClient::new(transport, "api.drand.sh", mainnet_info(1595431050));
+
+        assert_eq!(
+            client.randomness(2).unwrap_err(),
+            DrandClientError::NotResponding
+        );
+        // only a single request was made
+        assert_eq!(client.transport.fail_for_n.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn publish_delay_unknown_before_a_round_transition() -> Result<(), DrandClientError> {
+        // here we stop the clock at round 3
+        let info = mainnet_info(1595431050);
+        let clock = FixedClock::at_round(&info, 3)?;
+        let client = DrandClient::new(
+            MockTransport {
+                beacon: MAINNET_ROUND_TWO_JSON,
+            },
+            "api.drand.sh",
+            info,
+        )
+        .with_clock(clock);
+
+        assert_eq!(client.publish_delay_estimate(), None);
+        client.latest_randomness()?;
+        assert_eq!(client.publish_delay_estimate(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn chain_info_with_zero_period_is_invalid() {
+        let info = serde_json::to_string(&ChainInfo {
+            period_seconds: 0,
+            ..mainnet_info(1595431050)
+        })
+        .unwrap();
+        let transport = MockTransport { beacon: &info };
+
+        assert_eq!(
+            fetch_chain_info_from(&transport, "api.drand.sh", &DefaultUrlStrategy, None)
+                .unwrap_err(),
+            DrandClientError::InvalidChainInfo
+        );
+    }
+
+    #[test]
+    fn chain_info_with_zero_genesis_is_invalid() {
+        let info = serde_json::to_string(&ChainInfo {
+            genesis_time: 0,
+            ..mainnet_info(1595431050)
+        })
+        .unwrap();
+        let transport = MockTransport { beacon: &info };
+
+        assert_eq!(
+            fetch_chain_info_from(&transport, "api.drand.sh", &DefaultUrlStrategy, None)
+                .unwrap_err(),
+            DrandClientError::InvalidChainInfo
+        );
+    }
+
+    #[test]
+    fn valid_chain_info_is_parsed() -> Result<(), DrandClientError> {
+        let transport = MockTransport {
+            beacon: MAINNET_INFO_JSON,
+        };
+
+        assert_eq!(
+            fetch_chain_info_from(&transport, "api.drand.sh", &DefaultUrlStrategy, None)?,
+            mainnet_info(1595431050)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn chain_info_fetch_retries_truncated_responses() -> Result<(), DrandClientError> {
+        let transport = TruncatedMockTransport::new(2, MAINNET_INFO_JSON);
+
+        assert_eq!(
+            fetch_chain_info_from(
+                &transport,
+                "api.drand.sh",
+                &DefaultUrlStrategy,
+                Some(&instant_retry(3))
+            )?,
+            mainnet_info(1595431050)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn chain_info_fetch_gives_up_on_persistently_truncated_responses() {
+        let transport = TruncatedMockTransport::new(3, MAINNET_INFO_JSON);
+
+        assert_eq!(
+            fetch_chain_info_from(
+                &transport,
+                "api.drand.sh",
+                &DefaultUrlStrategy,
+                Some(&instant_retry(3))
+            )
+            .unwrap_err(),
+            DrandClientError::ChainInfoNotReady
+        );
+        assert_eq!(transport.truncated_for_n.get(), 0);
+    }
+
+    #[test]
+    fn chain_info_can_be_fetched_over_any_transport() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+
+        assert_eq!(
+            &fetch_chain_info(&chain.transport(1), "api.drand.sh")?,
+            chain.chain_info()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn truncated_chain_info_without_retry_is_invalid() {
+        let transport = TruncatedMockTransport::new(1, MAINNET_INFO_JSON);
+
+        assert_eq!(
+            fetch_chain_info_from(&transport, "api.drand.sh", &DefaultUrlStrategy, None)
+                .unwrap_err(),
+            DrandClientError::InvalidChainInfo
+        );
+    }
+
+    #[test]
+    fn round_for_time_with_zero_period_does_not_panic() {
+        let mut info = mainnet_info(1595431050);
+        info.period_seconds = 0;
+
+        assert_eq!(
+            round_for_time(&info, SystemTime::now()).unwrap_err(),
+            DrandClientError::InvalidChainInfo
+        );
+    }
+
+    #[test]
+    fn time_for_round_inverts_round_for_time() {
+        let info = mainnet_info(1595431050);
+
+        assert_eq!(
+            time_for_round(&info, 1),
+            Ok(UNIX_EPOCH + Duration::from_secs(1595431050))
+        );
+        for round in [1, 2, 1000, 5_000_000] {
+            let start = time_for_round(&info, round).unwrap();
+            assert_eq!(round_for_time(&info, start), Ok(round));
+        }
+        assert_eq!(
+            time_for_round(&info, 0),
+            Err(DrandClientError::InvalidRound)
+        );
+        assert_eq!(
+            time_for_round(&info, u64::MAX),
+            Err(DrandClientError::UnexpectedError)
+        );
+    }
+
+    #[test]
+    fn client_maps_rounds_to_times_and_back() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::new(
+            chain.transport(1),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+
+        assert_eq!(client.time_of_round(1)?, client.genesis_time()?);
+        assert_eq!(client.time_of(RoundNumber::FIRST)?, client.genesis_time()?);
+        for round in (2..200).chain([1_000_000, 10_000_000]) {
+            assert_eq!(client.round_at(client.time_of_round(round)?)?, round);
+        }
+        assert_eq!(client.time_of_round(0), Err(InvalidRound));
+        assert_eq!(
+            client.time_of_round(u64::MAX),
+            Err(DrandClientError::UnexpectedError)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn next_round_is_a_full_period_away_on_a_boundary() {
+        let info = mainnet_info(1595431050);
+        let at = |seconds| UNIX_EPOCH + Duration::from_secs(seconds);
+
+        assert_eq!(next_round_after(&info, at(1595431000)), Ok(1));
+        assert_eq!(
+            duration_until_next_round_at(&info, at(1595431000)),
+            Ok(Duration::from_secs(50))
+        );
+        assert_eq!(next_round_after(&info, at(1595431050)), Ok(2));
+        assert_eq!(
+            duration_until_next_round_at(&info, at(1595431050)),
+            Ok(Duration::from_secs(30))
+        );
+        assert_eq!(next_round_after(&info, at(1595431080)), Ok(3));
+        assert_eq!(
+            duration_until_next_round_at(&info, at(1595431080)),
+            Ok(Duration::from_secs(30))
+        );
+        assert_eq!(
+            duration_until_next_round_at(&info, at(1595431080) + Duration::from_millis(29_500)),
+            Ok(Duration::from_millis(500))
+        );
+
+        let client = DrandClient::new(UrlMockTransport::new(&[]), "api.drand.sh", info.clone());
+        let current = round_for_time(&info, SystemTime::now()).unwrap();
+        assert!((current + 1..=current + 2).contains(&client.next_round_number().unwrap()));
+        assert!(client.duration_until_next_round().unwrap() <= client.period());
+    }
+
+    #[test]
+    fn genesis_times_past_the_end_of_system_time_fail_without_panicking() {
+        let info = mainnet_info(u64::MAX);
+        let client = DrandClient::new(UrlMockTransport::new(&[]), "api.drand.sh", info.clone());
+
+        assert_eq!(
+            next_round_after(&info, SystemTime::now()),
+            Err(DrandClientError::InvalidChainInfo)
+        );
+        assert_eq!(
+            client.genesis_time(),
+            Err(DrandClientError::InvalidChainInfo)
+        );
+        assert_eq!(
+            client.duration_until_next_round(),
+            Err(DrandClientError::InvalidChainInfo)
+        );
+    }
+
+    #[test]
+    fn round_for_epoch_seconds_matches_round_for_time() {
+        let info = mainnet_info(1595431050);
+        for epoch_seconds in [1595431050, 1595431051, 1595431080, 1595431081, 1700000000] {
+            assert_eq!(
+                round_for_epoch_seconds(&info, epoch_seconds),
+                round_for_time(&info, UNIX_EPOCH + Duration::from_secs(epoch_seconds))
+            );
+        }
+        assert_eq!(round_for_epoch_seconds(&info, 1595431080), Ok(2));
+        assert_eq!(round_for_epoch_seconds(&info, 1595431050), Ok(1));
+        assert_eq!(
+            round_for_epoch_seconds(&info, 1595431049).unwrap_err(),
+            DrandClientError::RoundBeforeGenesis
+        );
+    }
+
+    #[test]
+    fn relay_swapping_chains_reports_migration() -> Result<(), DrandClientError> {
+        let old_chain = TestChain::with_seed(PedersenBlsUnchained, 30, 1595431050, b"old");
+        let new_chain = TestChain::with_seed(PedersenBlsUnchained, 30, 1595431050, b"new");
+        let transport = SwappingMockTransport {
+            before: old_chain.transport(2),
+            after: new_chain.transport(2),
+            swapped: Cell::new(false),
+        };
+        let client = DrandClient::new(transport, "api.drand.sh", old_chain.chain_info().clone());
+        client.randomness(2)?;
+
+        client.transport.swapped.set(true);
+        assert_eq!(
+            client.randomness(2).unwrap_err(),
+            DrandClientError::ChainMigrated {
+                old_hash: old_chain.chain_info().chain_hash.clone(),
+                new_hash: new_chain.chain_info().chain_hash.clone(),
+                new_info: Box::new(new_chain.chain_info().clone()),
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn chain_info_can_be_refreshed_after_a_reset() -> Result<(), DrandClientError> {
+        let old_chain = TestChain::with_seed(PedersenBlsUnchained, 30, 1595431050, b"old");
+        let new_chain = TestChain::with_seed(PedersenBlsUnchained, 30, 1595431050, b"new");
+        let transport = SwappingMockTransport {
+            before: old_chain.transport(2),
+            after: new_chain.transport(2),
+            swapped: Cell::new(false),
+        };
+        let mut client =
+            DrandClient::new(transport, "api.drand.sh", old_chain.chain_info().clone())
+                .with_round_cache(4);
+        client.randomness(2)?;
+        client.transport.swapped.set(true);
+        let migration = || DrandClientError::ChainMigrated {
+            old_hash: old_chain.chain_info().chain_hash.clone(),
+            new_hash: new_chain.chain_info().chain_hash.clone(),
+            new_info: Box::new(new_chain.chain_info().clone()),
+        };
+
+        assert_eq!(client.refresh_chain_info_strict(), Err(migration()));
+        assert_eq!(client.chain_info(), old_chain.chain_info());
+
+        assert_eq!(client.refresh_chain_info(), Err(migration()));
+        assert_eq!(client.chain_info(), new_chain.chain_info());
+        assert_eq!(client.randomness(2)?, new_chain.verified_round(2));
+
+        client.refresh_chain_info()?;
+        client.refresh_chain_info_strict()?;
+        Ok(())
+    }
+
+    #[test]
+    fn strict_refresh_keeps_the_chain_when_its_parameters_change() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let faster = TestChain::new(PedersenBlsUnchained, 3, 1595431050);
+        let transport = SwappingMockTransport {
+            before: chain.transport(2),
+            after: faster.transport(2),
+            swapped: Cell::new(true),
+        };
+        let mut client = DrandClient::new(transport, "api.drand.sh", chain.chain_info().clone());
+
+        assert!(matches!(
+            client.refresh_chain_info_strict(),
+            Err(DrandClientError::ChainMigrated { new_info, .. }) if *new_info == *faster.chain_info()
+        ));
+        assert_eq!(client.period(), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn failed_verification_on_unchanged_chain_is_not_a_migration() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let other = TestChain::with_seed(PedersenBlsUnchained, 30, 1595431050, b"other");
+        let transport = chain.transport(2).serve_round(2, &{
+            let forged = other.sign_round(2);
+            format!(
+                "{{\"round\":2,\"randomness\":\"{}\",\"signature\":\"{}\"}}",
+                hex::encode(&forged.randomness),
+                hex::encode(&forged.signature)
+            )
+        });
+        let client = DrandClient::new(transport, "api.drand.sh", chain.chain_info().clone());
+
+        assert_eq!(
+            client.randomness(2).unwrap_err(),
+            DrandClientError::FailedVerification(SignatureFailedVerification)
+        );
+    }
+
+    #[test]
+    fn verification_errors_survive_the_client() {
+        let body = |randomness: &[u8], signature: &[u8]| {
+            format!(
+                "{{\"round\":2,\"randomness\":\"{}\",\"signature\":\"{}\"}}",
+                hex::encode(randomness),
+                hex::encode(signature)
+            )
+        };
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let beacon = chain.sign_round(2);
+        let client = |body: &str, info: ChainInfo| {
+            DrandClient::new(
+                chain.transport(2).serve_round(2, body),
+                "api.drand.sh",
+                info,
+            )
+        };
+
+        let wrong_randomness = client(
+            &body(&[0; 32], &beacon.signature),
+            chain.chain_info().clone(),
+        );
+        let empty_signature = client(&body(&Sha256::digest(b""), &[]), chain.chain_info().clone());
+        let bad_key = client(
+            &body(&beacon.randomness, &beacon.signature),
+            ChainInfo {
+                public_key: vec![0; 48],
+                ..chain.chain_info().clone()
+            },
+        );
+
+        assert_eq!(
+            wrong_randomness.randomness(2),
+            Err(DrandClientError::FailedVerification(InvalidRandomness))
+        );
+        assert_eq!(
+            empty_signature.randomness(2),
+            Err(DrandClientError::FailedVerification(EmptySignature))
+        );
+        assert_eq!(
+            bad_key.randomness(2),
+            Err(DrandClientError::FailedVerification(InvalidPublicKey))
+        );
+
+        let chained = TestChain::new(PedersenBlsChained, 30, 1595431050);
+        let beacon = chained.sign_round(2);
+        let transport = chained
+            .transport(2)
+            .serve_round(2, &body(&beacon.randomness, &beacon.signature));
+        let client = DrandClient::new(transport, "api.drand.sh", chained.chain_info().clone());
+        let err = client.randomness(2).unwrap_err();
+        assert_eq!(
+            err,
+            DrandClientError::FailedVerification(ChainedBeaconNeedsPreviousSignature)
+        );
+        assert_eq!(
+            err.to_string(),
+            "beacon failed verification: chained beacons must have a `previous_signature`"
+        );
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    #[test]
+    fn unexpected_transport_error_reports_status_code() {
+        assert_eq!(
+            TransportError::Unexpected {
+                status_code: Some(503)
+            }
+            .to_string(),
+            "unexpected HTTP status 503"
+        );
+        assert_eq!(
+            TransportError::Unexpected { status_code: None }.to_string(),
+            "unexpected"
+        );
+    }
+
+    #[test]
+    fn randomness_batch_returns_results_in_input_order() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::new(
+            chain.transport(5),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+
+        let results = client.randomness_batch(&[3, 1, 7, 0, 5]);
+        let rounds: Vec<u64> = results.iter().map(|(round, _)| *round).collect();
+        assert_eq!(rounds, [3, 1, 7, 0, 5]);
+        assert_eq!(results[0].1, Ok(chain.verified_round(3)));
+        assert_eq!(results[1].1, Ok(chain.verified_round(1)));
+        assert_eq!(results[2].1, Err(DrandClientError::NotResponding));
+        assert_eq!(results[3].1, Err(InvalidRound));
+        assert_eq!(results[4].1, Ok(chain.verified_round(5)));
+    }
+
+    #[test]
+    fn randomness_batch_spreads_many_rounds_over_threads() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::new(
+            chain.transport(20),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+
+        let rounds: Vec<u64> = (1..=20).rev().collect();
+        let results = client.randomness_batch(&rounds);
+        assert_eq!(results.len(), 20);
+        for ((round, result), expected) in results.into_iter().zip(rounds) {
+            assert_eq!(round, expected);
+            assert_eq!(result.unwrap().round_number, expected);
+        }
+        assert!(client.randomness_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn randomness_range_fetches_every_round_in_order() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::new(
+            chain.transport(50),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+
+        let beacons = client.randomness_range(3..=50, 4)?;
+        assert_eq!(beacons.len(), 48);
+        for (beacon, round) in beacons.iter().zip(3..) {
+            assert_eq!(*beacon, chain.sign_round(round));
+        }
+        assert_eq!(client.randomness_range(7..=7, 0)?, [chain.sign_round(7)]);
+        assert!(client.randomness_range(8..=7, 4)?.is_empty());
+        assert_eq!(client.randomness_range(0..=5, 4), Err(InvalidRound));
+        Ok(())
+    }
+
+    #[test]
+    fn randomness_range_fails_on_the_first_bad_round() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::new(
+            chain.transport(20).serve_round(12, "not json"),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+
+        assert_eq!(
+            client.randomness_range(1..=20, 3),
+            Err(DrandClientError::InvalidBeacon)
+        );
+        assert_eq!(
+            client.randomness_range(1..=25, 1),
+            Err(DrandClientError::InvalidBeacon)
+        );
+    }
+
+    #[test]
+    fn randomness_range_doesnt_collect_the_rounds_up_front() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::new(
+            chain.transport(5),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+
+        // far more rounds than fit in memory, but every thread stops at its first failure
+        assert_eq!(
+            client.randomness_range(1..=u64::MAX, 4),
+            Err(DrandClientError::NotResponding)
+        );
+    }
+
+    #[test]
+    fn round1_check_accepts_the_pinned_randomness() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::new(
+            chain.transport(2),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+
+        client.check_round1(&chain.sign_round(1).randomness)
+    }
+
+    #[test]
+    fn round1_check_rejects_other_randomness() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::new(
+            chain.transport(2),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+
+        assert_eq!(
+            client.check_round1(&chain.sign_round(2).randomness),
+            Err(DrandClientError::InvalidChainInfo)
+        );
+    }
+
+    #[test]
+    fn chain_hash_check_reports_both_hashes() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::new(
+            chain.transport(2),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+
+        assert_eq!(
+            client.check_chain_hash(&chain.chain_info().chain_hash),
+            Ok(())
+        );
+        assert_eq!(
+            client.check_chain_hash(&[0; 32]),
+            Err(DrandClientError::ChainHashMismatch {
+                expected: vec![0; 32],
+                actual: chain.chain_info().chain_hash.clone(),
+            })
+        );
+    }
+
+    #[test]
+    fn http_statuses_map_to_client_errors() {
+        for (status, error) in [
+            (404, DrandClientError::NotResponding),
+            (429, DrandClientError::RateLimited),
+            (500, DrandClientError::UnexpectedError),
+            (503, DrandClientError::NotResponding),
+            (418, DrandClientError::UnexpectedError),
+        ] {
+            assert_eq!(DrandClientError::from_http_status(status, None), error);
+            assert_eq!(
+                DrandClientError::from_http_status(status, Some("relay error")),
+                error
+            );
+            assert_eq!(
+                DrandClientError::from(TransportError::from_http_status(status)),
+                error
+            );
+            assert_eq!(
+                DrandClientError::from(TransportError::Unexpected {
+                    status_code: Some(status)
+                }),
+                error
+            );
+        }
+        assert_eq!(
+            DrandClientError::from(TransportError::NotFound),
+            DrandClientError::NotResponding
+        );
+        assert_eq!(
+            DrandClientError::from(TransportError::Unexpected { status_code: None }),
+            DrandClientError::NotResponding
+        );
+    }
+
+    #[test]
+    fn rate_limited_fetch_is_reported() {
+        let transport = StatusMockTransport { status_code: 429 };
+        let client = DrandClient::new(transport, "api.drand.sh", mainnet_info(1595431050));
+
+        assert_eq!(
+            client.randomness(2).unwrap_err(),
+            DrandClientError::RateLimited
+        );
+    }
+
+    #[test]
+    fn latest_randomness_if_new_skips_known_rounds() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::new(
+            chain.transport(3),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        )
+        .with_clock(FixedClock::at_round(chain.chain_info(), 3)?);
+
+        assert_eq!(client.latest_randomness_if_new(3)?, None);
+        assert_eq!(client.latest_randomness_if_new(4)?, None);
+        assert_eq!(
+            client.latest_randomness_if_new(2)?,
+            Some(chain.verified_round(3))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn latest_randomness_if_new_only_verifies_new_rounds() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let other = TestChain::with_seed(PedersenBlsUnchained, 30, 1595431050, b"other");
+        let transport = chain.transport(3).serve_latest(&other.sign_round(3));
+        let client = DrandClient::new(transport, "api.drand.sh", chain.chain_info().clone())
+            .with_clock(FixedClock::at_round(chain.chain_info(), 3).unwrap());
+
+        assert_eq!(client.latest_randomness_if_new(3), Ok(None));
+        assert_eq!(
+            client.latest_randomness_if_new(2),
+            Err(DrandClientError::FailedVerification(
+                SignatureFailedVerification
+            ))
+        );
+    }
+
+    #[test]
+    fn latest_randomness_if_new_rejects_a_stale_relay() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::new(
+            chain.transport(1),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        )
+        .with_clock(FixedClock::at_round(chain.chain_info(), 3).unwrap());
+
+        assert_eq!(
+            client.latest_randomness_if_new(0),
+            Err(DrandClientError::InvalidBeacon)
+        );
+        assert_eq!(
+            client.latest_randomness_if_new(1),
+            Err(DrandClientError::InvalidBeacon)
+        );
+    }
+
+    #[test]
+    fn client_exposes_its_chain_parameters() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::new(
+            chain.transport(1),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+
+        assert_eq!(client.chain_info(), chain.chain_info());
+        assert_eq!(client.period(), Duration::from_secs(30));
+        assert_eq!(
+            client.genesis_time(),
+            Ok(UNIX_EPOCH + Duration::from_secs(1595431050))
+        );
+        assert_eq!(
+            time_for_round(client.chain_info(), 1),
+            client.genesis_time()
+        );
+    }
+
+    #[test]
+    fn rounds_start_right_after_genesis() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::new(
+            chain.transport(1),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+        let at = |seconds| UNIX_EPOCH + Duration::from_secs(seconds);
+
+        assert_eq!(
+            client.round_at(at(1595431049)),
+            Err(DrandClientError::RoundBeforeGenesis)
+        );
+        assert_eq!(client.round_at(at(1595431050))?, 1);
+        assert_eq!(client.round_at(at(1595431051))?, 1);
+        assert_eq!(client.round_at(at(1595431050 + 29))?, 1);
+        assert_eq!(client.round_at(at(1595431050 + 30))?, 2);
+        assert_eq!(
+            client.round_at(UNIX_EPOCH - Duration::from_secs(1)),
+            Err(DrandClientError::UnexpectedError)
+        );
+
+        let now = round_for_time(chain.chain_info(), SystemTime::now())?;
+        assert!((now..=now + 1).contains(&client.current_round()?));
+        Ok(())
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn randomness_at_time_is_an_alias_of_randomness_at() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::new(
+            chain.transport(2),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+        let time = UNIX_EPOCH + Duration::from_secs(1595431050 + 31);
+
+        assert_eq!(
+            client.randomness_at_time(time)?,
+            client.randomness_at(time)?
+        );
+        assert_eq!(
+            client.randomness_at_time(SystemTime::now() + Duration::from_secs(3600)),
+            Err(DrandClientError::RoundNotYetAvailable)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn randomness_at_fetches_the_round_current_at_that_time() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::new(
+            chain.transport(3),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+        let at = |seconds| UNIX_EPOCH + Duration::from_secs(seconds);
+
+        assert_eq!(
+            client.randomness_at(at(1595431050 + 31))?,
+            chain.sign_round(2)
+        );
+        // the boundary belongs to the round that starts there
+        assert_eq!(
+            client.randomness_at(at(1595431050 + 60))?,
+            chain.sign_round(3)
+        );
+        assert_eq!(client.randomness_at(at(1595431050))?, chain.sign_round(1));
+        assert_eq!(
+            client.randomness_at(at(1595431049)),
+            Err(DrandClientError::RoundBeforeGenesis)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn client_with_chain_info_never_fetches_info() -> Result<(), DrandClientError> {
+        let client = DrandClient::with_chain_info(
+            UrlMockTransport::new(&[("api.drand.sh/public/2", MAINNET_ROUND_TWO_JSON)]),
+            "api.drand.sh",
+            mainnet_info(1595431050),
+        );
+
+        assert_eq!(client.randomness(2)?.round_number, 2);
+        assert_eq!(
+            *client.transport.requested.borrow(),
+            ["api.drand.sh/public/2"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn randomness_at_a_future_time_is_not_requested() {
+        let client = DrandClient::new(
+            UrlMockTransport::new(&[]),
+            "api.drand.sh",
+            mainnet_info(1595431050),
+        );
+
+        assert_eq!(
+            client.randomness_at(SystemTime::now() + Duration::from_secs(3600)),
+            Err(DrandClientError::RoundNotYetAvailable)
+        );
+        assert!(client.transport.requested.borrow().is_empty());
+    }
+
+    #[test]
+    fn client_outlives_its_base_url() -> Result<(), DrandClientError> {
+        struct App {
+            client: DrandClient<TestTransport>,
+        }
+
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let app = {
+            let base_url = format!("https://{}", "api.drand.sh");
+            App {
+                client: DrandClient::new(chain.transport(2), base_url, chain.chain_info().clone()),
+            }
+        };
+
+        assert_eq!(app.client.randomness(2)?, chain.sign_round(2));
+        Ok(())
+    }
+
+    #[test]
+    fn http_client_can_be_shared_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<DrandClient<HttpTransport>>();
+    }
+
+    #[test]
+    fn client_can_be_moved_into_a_spawned_thread() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let expected = chain.verified_round(2);
+        let client = DrandClient::new(
+            chain.transport(2),
+            format!("https://{}", "api.drand.sh"),
+            chain.chain_info().clone(),
+        );
+
+        let beacon = std::thread::spawn(move || client.randomness(2))
+            .join()
+            .unwrap();
+        assert_eq!(beacon, Ok(expected));
+    }
+
+    #[test]
+    fn unverified_fetches_skip_the_signature_check() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let other = TestChain::with_seed(PedersenBlsUnchained, 30, 1595431050, b"other");
+        let forged = other.sign_round(2);
+        let transport = chain
+            .transport(3)
+            .serve_latest(&forged)
+            .serve_round(2, &serde_json::to_string(&forged).unwrap());
+        let mut client = DrandClient::new(transport, "api.drand.sh", chain.chain_info().clone());
+        client.verification = VerificationPolicy::None;
+
+        assert_eq!(client.randomness_unverified(2)?, forged);
+        assert_eq!(client.latest_randomness_unverified()?, forged);
+        assert_eq!(client.randomness_unverified(3)?, chain.sign_round(3));
+        Ok(())
+    }
+
+    #[test]
+    fn each_verification_policy_accepts_the_right_beacons() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let other = TestChain::with_seed(PedersenBlsUnchained, 30, 1595431050, b"other");
+        let mut corrupted = chain.sign_round(3);
+        corrupted.randomness[0] ^= 1;
+        let transport = chain
+            .transport(3)
+            .serve_round(2, &serde_json::to_string(&other.sign_round(2)).unwrap())
+            .serve_round(3, &serde_json::to_string(&corrupted).unwrap());
+        let mut client = DrandClient::new(transport, "api.drand.sh", chain.chain_info().clone());
+
+        let failed = |e| Err(DrandClientError::FailedVerification(e));
+        for (policy, forged, corrupt) in [
+            (
+                VerificationPolicy::Full,
+                failed(SignatureFailedVerification),
+                failed(InvalidRandomness),
+            ),
+            (
+                VerificationPolicy::RandomnessHashOnly,
+                Ok(2),
+                failed(InvalidRandomness),
+            ),
+            (VerificationPolicy::None, Ok(2), Ok(3)),
+        ] {
+            client.verification = policy;
+            assert_eq!(client.randomness_unverified(1), Ok(chain.sign_round(1)));
+            assert_eq!(
+                client.randomness_unverified(2).map(|b| b.round_number),
+                forged
+            );
+            assert_eq!(
+                client.randomness_unverified(3).map(|b| b.round_number),
+                corrupt
+            );
+        }
+    }
+
+    #[test]
+    fn verified_beacons_are_fully_verified_under_every_policy() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let other = TestChain::with_seed(PedersenBlsUnchained, 30, 1595431050, b"other");
+        let transport = chain
+            .transport(3)
+            .serve_round(2, &serde_json::to_string(&other.sign_round(2)).unwrap());
+        let mut client = DrandClient::new(transport, "api.drand.sh", chain.chain_info().clone())
+            .with_clock(FixedClock::at_round(chain.chain_info(), 3).unwrap());
+
+        for policy in [
+            VerificationPolicy::Full,
+            VerificationPolicy::RandomnessHashOnly,
+            VerificationPolicy::None,
+        ] {
+            client.verification = policy;
+            assert_eq!(
+                client.randomness(1).map(VerifiedBeacon::into_inner),
+                Ok(chain.sign_round(1))
+            );
+            assert_eq!(
+                client.randomness(2),
+                Err(DrandClientError::FailedVerification(
+                    SignatureFailedVerification
+                ))
+            );
+            assert_eq!(
+                client
+                    .latest_randomness()
+                    .map(|beacon| beacon.round_number()),
+                Ok(3)
+            );
+        }
+    }
+
+    #[test]
+    fn latest_round_number_skips_verification_but_not_staleness() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let other = TestChain::with_seed(PedersenBlsUnchained, 30, 1595431050, b"other");
+        let at_round_three = || FixedClock::at_round(chain.chain_info(), 3).unwrap();
+        let transport = chain.transport(3).serve_latest(&other.sign_round(3));
+        let client = DrandClient::new(transport, "api.drand.sh", chain.chain_info().clone())
+            .with_clock(at_round_three());
+        assert_eq!(client.latest_round_number()?, 3);
+
+        let stale = DrandClient::new(
+            chain.transport(1),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        )
+        .with_clock(at_round_three());
+        assert_eq!(
+            stale.latest_round_number(),
+            Err(DrandClientError::InvalidBeacon)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rounds_can_be_fetched_by_round_number() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::new(
+            chain.transport(2),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+
+        assert_eq!(
+            client.randomness_for(RoundNumber::new(2)?)?,
+            chain.sign_round(2)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn a_cloned_client_fetches_on_its_own() -> Result<(), DrandClientError> {
+        let client = DrandClient::new(
+            MockTransport {
+                beacon: MAINNET_ROUND_TWO_JSON,
+            },
+            "api.drand.sh",
+            mainnet_info(1595431050),
+        )
+        .with_round_cache(4);
+        let clone = client.clone();
+        drop(client);
+
+        assert_eq!(clone.randomness(2)?.round_number, 2);
+        assert_eq!(clone.chain_info(), &mainnet_info(1595431050));
+        Ok(())
+    }
+
+    #[test]
+    fn unverified_fetch_still_checks_the_round() {
+        let transport = MockTransport {
+            beacon: MAINNET_ROUND_TWO_JSON,
+        };
+        let client = DrandClient::new(transport, "api.drand.sh", mainnet_info(1595431050));
+
+        assert_eq!(
+            client.randomness_unverified(4).unwrap_err(),
+            DrandClientError::InvalidBeacon
+        );
+        assert_eq!(client.randomness_unverified(0).unwrap_err(), InvalidRound);
+    }
+
+    #[cfg(not(any(feature = "energon", feature = "verify-slim")))]
+    #[test]
+    fn verifying_fetches_are_disabled_in_fetch_only_builds() {
+        let transport = MockTransport {
+            beacon: MAINNET_ROUND_TWO_JSON,
+        };
+        let mut client = DrandClient::new(transport, "api.drand.sh", mainnet_info(1595431050));
+
+        assert_eq!(
+            client.randomness(2).unwrap_err(),
+            DrandClientError::VerificationDisabled
+        );
+        assert_eq!(
+            client.randomness_unverified(2).unwrap_err(),
+            DrandClientError::VerificationDisabled
+        );
+        client.verification = VerificationPolicy::None;
+        assert_eq!(client.randomness_unverified(2).unwrap().round_number, 2);
+    }
+
+    #[test]
+    fn beacon_id_is_taken_from_chain_info() {
+        let client = DrandClient::new(
+            MockTransport {
+                beacon: MAINNET_ROUND_TWO_JSON,
+            },
+            "api.drand.sh",
+            mainnet_info(1595431050),
+        );
+        assert_eq!(client.beacon_id(), "default");
+        assert!(client.is_default_chain());
+
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let client = DrandClient::new(
+            chain.transport(1),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+        assert_eq!(client.beacon_id(), "testkit");
+        assert!(!client.is_default_chain());
+    }
+
+    #[test]
+    fn custom_url_strategy_builds_every_url() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let clock = FixedClock::at_round(chain.chain_info(), 2)?;
+        let hash = hex::encode(&chain.chain_info().chain_hash);
+        let relay = chain.transport(2);
+        let gateway_url = |path: &str| format!("https://gateway/drand/v1/chains/{hash}/{path}");
+        let responses = [
+            (gateway_url("info"), relay.fetch("/info").unwrap()),
+            (
+                gateway_url("rounds/latest"),
+                relay.fetch("/public/latest").unwrap(),
+            ),
+            (gateway_url("rounds/1"), relay.fetch("/public/1").unwrap()),
+        ];
+        let responses: Vec<(&str, &str)> = responses
+            .iter()
+            .map(|(url, body)| (url.as_str(), body.as_str()))
+            .collect();
+        let transport = UrlMockTransport::new(&responses);
+        let strategy = GatewayUrlStrategy {
+            chain_hash: hash.clone(),
+        };
+
+        let info = fetch_chain_info_from(&transport, "https://gateway", &strategy, None)?;
+        let client = DrandClient::new(transport, "https://gateway", info)
+            .with_url_strategy(strategy)
+            .with_clock(clock);
+        assert_eq!(client.latest_randomness()?.round_number, 2);
+        assert_eq!(client.randomness(1)?.round_number, 1);
+        assert_eq!(
+            client.transport.requested.borrow().as_slice(),
+            &[
+                gateway_url("info"),
+                gateway_url("rounds/latest"),
+                gateway_url("rounds/1")
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn chain_migration_check_uses_url_strategy() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let hash = hex::encode(&chain.chain_info().chain_hash);
+        let round_url = format!("https://gateway/drand/v1/chains/{hash}/rounds/2");
+        let transport = UrlMockTransport::new(&[(round_url.as_str(), MAINNET_ROUND_TWO_JSON)]);
+        let client = DrandClient::new(transport, "https://gateway", chain.chain_info().clone())
+            .with_url_strategy(GatewayUrlStrategy {
+                chain_hash: hash.clone(),
+            });
+
+        // the mainnet beacon doesn't verify against the test chain
+        assert_eq!(
+            client.randomness(2).unwrap_err(),
+            DrandClientError::FailedVerification(SignatureFailedVerification)
+        );
+        assert_eq!(
+            client.transport.requested.borrow().last().unwrap(),
+            &format!("https://gateway/drand/v1/chains/{hash}/info")
+        );
+    }
+
+    #[test]
+    fn retries_survive_injected_faults() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let transport = ChaosTransport::new(chain.transport(10), 42)
+            .with_timeout(Duration::ZERO)
+            .with_schedule([
+                Some(Fault::Unexpected),
+                Some(Fault::Timeout),
+                None,
+                Some(Fault::NotFound),
+                None,
+                Some(Fault::TruncatedBody),
+                None,
+            ]);
+        let client = DrandClient::new(transport, "api.drand.sh", chain.chain_info().clone())
+            .with_retry(instant_retry(3));
+
+        // a 500 and a timeout are retried away
+        assert_eq!(client.randomness(5)?, chain.sign_round(5));
+        // a 404 means the round doesn't exist yet, and a bad body isn't a transport failure
+        assert_eq!(
+            client.randomness(6).unwrap_err(),
+            DrandClientError::NotResponding
+        );
+        assert_eq!(client.randomness(6)?, chain.sign_round(6));
+        assert_eq!(
+            client.randomness(7).unwrap_err(),
+            DrandClientError::InvalidBeacon
+        );
+        assert_eq!(client.randomness(7)?, chain.sign_round(7));
+
+        let faults: Vec<Fault> = client
+            .transport
+            .injected()
+            .into_iter()
+            .map(|injected| injected.fault)
+            .collect();
+        assert_eq!(
+            faults,
+            [
+                Fault::Unexpected,
+                Fault::Timeout,
+                Fault::NotFound,
+                Fault::TruncatedBody
+            ]
+        );
+        Ok(())
+    }
+
+    fn instant_retry(max_attempts: usize) -> RetryConfig {
+        RetryConfig {
+            max_attempts,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+        }
+    }
+
+    // mainnet, with its genesis moved so that a chosen round is the current one
+    fn mainnet_info(genesis_time: u64) -> ChainInfo {
+        ChainInfo {
+            genesis_time,
+            ..testkit::mainnet_info()
+        }
+    }
+
+    #[derive(Clone)]
     struct MockTransport<'a> {
         beacon: &'a str,
     }
@@ -329,4 +2823,145 @@ mod test {
             Ok(self.beacon.to_string())
         }
     }
+
+    /// fails every request with an HTTP error status
+    struct StatusMockTransport {
+        status_code: u16,
+    }
+
+    impl Transport for StatusMockTransport {
+        fn fetch(&self, _: &str) -> Result<String, TransportError> {
+            Err(TransportError::Unexpected {
+                status_code: Some(self.status_code),
+            })
+        }
+    }
+
+    /// serves a fixed response per URL and records every URL requested
+    struct UrlMockTransport {
+        responses: HashMap<String, String>,
+        requested: RefCell<Vec<String>>,
+    }
+
+    impl UrlMockTransport {
+        fn new(responses: &[(&str, &str)]) -> Self {
+            UrlMockTransport {
+                responses: responses
+                    .iter()
+                    .map(|(url, body)| (url.to_string(), body.to_string()))
+                    .collect(),
+                requested: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl Transport for UrlMockTransport {
+        fn fetch(&self, url: &str) -> Result<String, TransportError> {
+            self.requested.borrow_mut().push(url.to_string());
+            self.responses
+                .get(url)
+                .cloned()
+                .ok_or(TransportError::NotFound)
+        }
+    }
+
+    /// a gateway that serves each chain under `/drand/v1/chains/<hash>`
+    struct GatewayUrlStrategy {
+        chain_hash: String,
+    }
+
+    impl UrlStrategy for GatewayUrlStrategy {
+        fn info_url(&self, base_url: &str) -> String {
+            format!("{base_url}/drand/v1/chains/{}/info", self.chain_hash)
+        }
+
+        fn latest_url(&self, base_url: &str) -> String {
+            format!(
+                "{base_url}/drand/v1/chains/{}/rounds/latest",
+                self.chain_hash
+            )
+        }
+
+        fn round_url(&self, base_url: &str, round_number: u64) -> String {
+            format!(
+                "{base_url}/drand/v1/chains/{}/rounds/{round_number}",
+                self.chain_hash
+            )
+        }
+    }
+
+    /// fails the first `fail_for_n` fetches, then always succeeds
+    struct CountdownMockTransport {
+        fail_for_n: AtomicUsize,
+        success_response: String,
+    }
+
+    impl CountdownMockTransport {
+        fn new(fail_for_n: usize, success_response: &str) -> Self {
+            CountdownMockTransport {
+                fail_for_n: AtomicUsize::new(fail_for_n),
+                success_response: success_response.to_string(),
+            }
+        }
+    }
+
+    impl Transport for CountdownMockTransport {
+        fn fetch(&self, _: &str) -> Result<String, TransportError> {
+            let failed = self
+                .fail_for_n
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok();
+            if failed {
+                Err(TransportError::Unexpected {
+                    status_code: Some(503),
+                })
+            } else {
+                Ok(self.success_response.clone())
+            }
+        }
+    }
+
+    /// returns the first half of `body` for the first `truncated_for_n` fetches
+    struct TruncatedMockTransport {
+        truncated_for_n: Cell<usize>,
+        body: String,
+    }
+
+    impl TruncatedMockTransport {
+        fn new(truncated_for_n: usize, body: &str) -> Self {
+            TruncatedMockTransport {
+                truncated_for_n: Cell::new(truncated_for_n),
+                body: body.to_string(),
+            }
+        }
+    }
+
+    impl Transport for TruncatedMockTransport {
+        fn fetch(&self, _: &str) -> Result<String, TransportError> {
+            match self.truncated_for_n.get() {
+                0 => Ok(self.body.clone()),
+                n => {
+                    self.truncated_for_n.set(n - 1);
+                    Ok(self.body[..self.body.len() / 2].to_string())
+                }
+            }
+        }
+    }
+
+    /// serves one chain until `swapped` is set, then another
+    struct SwappingMockTransport {
+        before: TestTransport,
+        after: TestTransport,
+        swapped: Cell<bool>,
+    }
+
+    impl Transport for SwappingMockTransport {
+        fn fetch(&self, url: &str) -> Result<String, TransportError> {
+            if self.swapped.get() {
+                self.after.fetch(url)
+            } else {
+                self.before.fetch(url)
+            }
+        }
+    }
 }
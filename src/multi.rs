@@ -0,0 +1,191 @@
+//! # multi
+//!
+//! a transport that falls back through several relays of the same chain, in priority order
+//!
+
+use crate::http::{new_http_transport, HttpTransport};
+use crate::{fetch_chain_info, DrandClient, DrandClientError, Transport, TransportError};
+
+/// wraps a transport, sending each request to the first relay in `relays` and falling back
+/// to the next one until a relay answers. Requests for URLs under any of the relays are
+/// rewritten for each relay in turn; other URLs are passed through unchanged.
+pub struct MultiTransport<T: Transport = HttpTransport> {
+    inner: T,
+    relays: Vec<String>,
+}
+
+impl<T: Transport> MultiTransport<T> {
+    /// a transport trying `relays` over `inner`, in the given order
+    pub fn new(inner: T, relays: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        MultiTransport {
+            inner,
+            relays: relays
+                .into_iter()
+                .map(|relay| relay.into().trim_end_matches('/').to_string())
+                .collect(),
+        }
+    }
+
+    /// the relays' base URLs, in the order they are tried
+    pub fn relays(&self) -> &[String] {
+        &self.relays
+    }
+}
+
+impl<T: Transport> Transport for MultiTransport<T> {
+    /// fails with the error of the last relay if none of them answer
+    fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        let Some(path) = self
+            .relays
+            .iter()
+            .filter_map(|relay| url.strip_prefix(relay.as_str()))
+            .find(|path| path.is_empty() || path.starts_with('/'))
+        else {
+            return self.inner.fetch(url);
+        };
+
+        let mut last_error = TransportError::NotFound;
+        for relay in &self.relays {
+            match self.inner.fetch(&format!("{relay}{path}")) {
+                Ok(body) => return Ok(body),
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error)
+    }
+}
+
+/// create a new instance of the client with an HTTP transport that tries each of `urls` in
+/// turn, e.g. `["https://api.drand.sh", "https://drand.cloudflare.com"]`. Every relay that
+/// answers must serve the same chain info, or this fails with `InvalidChainInfo`.
+pub fn new_http_client_multi(
+    urls: &[&str],
+) -> Result<DrandClient<MultiTransport>, DrandClientError> {
+    connect(MultiTransport::new(
+        new_http_transport(),
+        urls.iter().copied(),
+    ))
+}
+
+/// a client over `transport`, after checking that its relays agree on the chain info.
+/// Relays that can't be reached are skipped; if none can, their last error is returned.
+fn connect<T: Transport + Send>(
+    transport: MultiTransport<T>,
+) -> Result<DrandClient<MultiTransport<T>>, DrandClientError> {
+    let mut chain_info = None;
+    let mut last_error = DrandClientError::NotResponding;
+    for relay in transport.relays() {
+        match fetch_chain_info(&transport.inner, relay) {
+            Ok(info) => match &chain_info {
+                None => chain_info = Some(info),
+                Some(first) if first.chain_hash != info.chain_hash => {
+                    return Err(DrandClientError::InvalidChainInfo);
+                }
+                Some(_) => {}
+            },
+            Err(e) => last_error = e,
+        }
+    }
+
+    let chain_info = chain_info.ok_or(last_error)?;
+    let base_url = transport.relays()[0].clone();
+    Ok(DrandClient::with_chain_info(
+        transport, base_url, chain_info,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::multi::{connect, MultiTransport};
+    use crate::testkit::{TestChain, TestTransport};
+    use crate::verify::SchemeID::PedersenBlsUnchained;
+    use crate::{DrandClientError, Transport, TransportError};
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    // serves a `TestTransport` per relay, recording every URL requested
+    #[derive(Default)]
+    struct Relays {
+        relays: HashMap<&'static str, TestTransport>,
+        requested: RefCell<Vec<String>>,
+    }
+
+    impl Relays {
+        fn with(mut self, relay: &'static str, transport: TestTransport) -> Self {
+            self.relays.insert(relay, transport);
+            self
+        }
+    }
+
+    impl Transport for Relays {
+        fn fetch(&self, url: &str) -> Result<String, TransportError> {
+            self.requested.borrow_mut().push(url.to_string());
+            let (relay, path) = url.split_at(url.find('/').unwrap_or(url.len()));
+            match self.relays.get(relay) {
+                Some(transport) => transport.fetch(path),
+                None => Err(TransportError::Unexpected { status_code: None }),
+            }
+        }
+    }
+
+    #[test]
+    fn requests_fall_back_to_the_next_relay() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let relays = Relays::default()
+            .with("second", chain.transport(1))
+            .with("third", chain.transport(2));
+        let client = connect(MultiTransport::new(relays, ["first", "second", "third"]))?;
+
+        assert_eq!(client.chain_info(), chain.chain_info());
+        assert_eq!(client.randomness(2)?, chain.sign_round(2));
+        assert_eq!(
+            client.transport.inner.requested.borrow()[3..],
+            ["first/public/2", "second/public/2", "third/public/2"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn urls_only_sharing_a_prefix_with_a_relay_are_passed_through() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let relays = Relays::default()
+            .with("api.drand.sh", chain.transport(1))
+            .with("api.drand.shop", chain.transport(2));
+        let transport = MultiTransport::new(relays, ["api.drand.sh", "other"]);
+
+        assert_eq!(
+            transport.fetch("api.drand.shop/public/2").unwrap(),
+            chain.transport(2).fetch("/public/2").unwrap()
+        );
+        assert_eq!(
+            transport.inner.requested.borrow().as_slice(),
+            ["api.drand.shop/public/2"]
+        );
+    }
+
+    #[test]
+    fn relays_must_agree_on_the_chain() {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let other = TestChain::with_seed(PedersenBlsUnchained, 30, 1595431050, b"other");
+        let relays = Relays::default()
+            .with("first", chain.transport(1))
+            .with("second", other.transport(1));
+
+        assert!(matches!(
+            connect(MultiTransport::new(relays, ["first", "second"])),
+            Err(DrandClientError::InvalidChainInfo)
+        ));
+    }
+
+    #[test]
+    fn no_reachable_relay_is_an_error() {
+        assert!(matches!(
+            connect(MultiTransport::new(Relays::default(), ["first", "second"])),
+            Err(DrandClientError::NotResponding)
+        ));
+        assert!(matches!(
+            connect(MultiTransport::new(Relays::default(), Vec::<String>::new())),
+            Err(DrandClientError::NotResponding)
+        ));
+    }
+}
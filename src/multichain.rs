@@ -0,0 +1,407 @@
+//! # multichain
+//!
+//! discovery of the chains a relay hosts from its `/chains` endpoint, and a client for every
+//! one of them
+//!
+
+use crate::chain_info::{ChainHash, ChainInfo};
+#[cfg(feature = "http")]
+use crate::http::{new_http_transport, HttpTransport};
+use crate::{fetch_chain_info, DrandClient, DrandClientError, Transport};
+use std::sync::Arc;
+
+/// the chains hosted by one relay, each with its own `DrandClient` over a shared transport.
+/// Every chain's info is fetched once, when the client is created.
+pub struct MultiChainClient<T: Transport + Send + Sync> {
+    chains: Vec<DrandClient<Arc<T>>>,
+    unreadable: Vec<ChainHash>,
+}
+
+impl<T: Transport + Send + Sync> MultiChainClient<T> {
+    /// discover the chains at `base_url`, then fetch and validate each one's info from
+    /// `{base_url}/{chain_hash}/info`. Chains whose info can't be fetched or isn't served
+    /// under their own hash are skipped and listed by `unreadable_chains`, like in
+    /// `resolve_beacon_id`. Fails with `InvalidChainInfo` if `/chains` can't be parsed, and
+    /// with the last chain's error if none of the listed chains could be read.
+    pub fn new(transport: T, base_url: &str) -> Result<Self, DrandClientError> {
+        let base_url = base_url.trim_end_matches('/');
+        let transport = Arc::new(transport);
+        let mut chains = Vec::new();
+        let mut unreadable = Vec::new();
+        let mut last_error = None;
+        for chain_hash in fetch_chains(&transport, base_url)? {
+            match fetch_listed_chain_info(&transport, base_url, &chain_hash) {
+                Ok(chain_info) => chains.push(DrandClient::with_chain_info(
+                    transport.clone(),
+                    format!("{base_url}/{chain_hash}"),
+                    chain_info,
+                )),
+                Err(e) => {
+                    unreadable.push(chain_hash);
+                    last_error = Some(e);
+                }
+            }
+        }
+        match last_error {
+            Some(e) if chains.is_empty() => Err(e),
+            _ => Ok(MultiChainClient { chains, unreadable }),
+        }
+    }
+
+    /// the hashes of listed chains whose info couldn't be fetched or validated, and which
+    /// have no client
+    pub fn unreadable_chains(&self) -> &[ChainHash] {
+        &self.unreadable
+    }
+
+    /// the client for the chain with the beacon id or hex-encoded chain hash `chain`, e.g.
+    /// `"quicknet"`
+    pub fn chain(&self, chain: &str) -> Option<&DrandClient<Arc<T>>> {
+        let chain_hash = chain.parse::<ChainHash>().ok();
+        self.chains.iter().find(|client| {
+            client.beacon_id() == chain
+                || chain_hash.is_some_and(|chain_hash| chain_hash.matches(client.chain_info()))
+        })
+    }
+
+    /// the info of every chain on the relay, in the order the relay listed them
+    pub fn chain_infos(&self) -> impl Iterator<Item = &ChainInfo> {
+        self.chains.iter().map(DrandClient::chain_info)
+    }
+}
+
+/// the hashes of the chains the relay at `base_url` hosts, from its `/chains` endpoint.
+/// Fails with `InvalidChainInfo` if the response isn't a list of chain hashes.
+pub fn fetch_chains<T: Transport>(
+    transport: &T,
+    base_url: &str,
+) -> Result<Vec<ChainHash>, DrandClientError> {
+    let body = transport.fetch(&format!("{}/chains", base_url.trim_end_matches('/')))?;
+    serde_json::from_str::<Vec<String>>(&body)
+        .map_err(|_| DrandClientError::InvalidChainInfo)?
+        .iter()
+        .map(|chain_hash| {
+            chain_hash
+                .parse()
+                .map_err(|_| DrandClientError::InvalidChainInfo)
+        })
+        .collect()
+}
+
+/// the hash of the chain with the beacon id `beacon_id` (e.g. `"quicknet"`) on the relay at
+/// `base_url`, or `None` if it hosts no such chain. Every chain's info is fetched until one
+/// matches. Chains whose info can't be fetched or isn't served under their own hash are
+/// skipped; if one was skipped and none matched, the result is `UnknownBeaconId` listing
+/// them instead of `None`, since the chain might be one of them.
+pub fn resolve_beacon_id<T: Transport>(
+    transport: &T,
+    base_url: &str,
+    beacon_id: &str,
+) -> Result<Option<ChainHash>, DrandClientError> {
+    match find_beacon(transport, base_url.trim_end_matches('/'), beacon_id) {
+        Ok((chain_hash, _)) => Ok(Some(chain_hash)),
+        Err(DrandClientError::UnknownBeaconId { unreadable, .. }) if unreadable.is_empty() => {
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// a client for the chain with the beacon id `beacon_id` on the relay at `base_url`, found
+/// like `resolve_beacon_id` and addressed by its hash from then on. Fails with
+/// `UnknownBeaconId`, listing the beacon ids the relay does host and the chains that were
+/// skipped, if none matches.
+pub fn new_client_for_beacon<T: Transport + Send>(
+    transport: T,
+    base_url: &str,
+    beacon_id: &str,
+) -> Result<DrandClient<T>, DrandClientError> {
+    let base_url = base_url.trim_end_matches('/');
+    let (chain_hash, chain_info) = find_beacon(&transport, base_url, beacon_id)?;
+    Ok(DrandClient::with_chain_info(
+        transport,
+        format!("{base_url}/{chain_hash}"),
+        chain_info,
+    ))
+}
+
+// the first listed chain with the beacon id `beacon_id`, skipping chains whose info can't be
+// read, or `UnknownBeaconId`
+fn find_beacon<T: Transport>(
+    transport: &T,
+    base_url: &str,
+    beacon_id: &str,
+) -> Result<(ChainHash, ChainInfo), DrandClientError> {
+    let mut available = Vec::new();
+    let mut unreadable = Vec::new();
+    for chain_hash in fetch_chains(transport, base_url)? {
+        match fetch_listed_chain_info(transport, base_url, &chain_hash) {
+            Ok(chain_info) if chain_info.metadata.beacon_id == beacon_id => {
+                return Ok((chain_hash, chain_info))
+            }
+            Ok(chain_info) => available.push(chain_info.metadata.beacon_id),
+            Err(_) => unreadable.push(chain_hash.to_string()),
+        }
+    }
+    Err(DrandClientError::UnknownBeaconId {
+        beacon_id: beacon_id.to_string(),
+        available,
+        unreadable,
+    })
+}
+
+// a chain's info from under its hash, which it must match
+fn fetch_listed_chain_info<T: Transport>(
+    transport: &T,
+    base_url: &str,
+    chain_hash: &ChainHash,
+) -> Result<ChainInfo, DrandClientError> {
+    let chain_info = fetch_chain_info(transport, &format!("{base_url}/{chain_hash}"))?;
+    if !chain_hash.matches(&chain_info) {
+        return Err(DrandClientError::InvalidChainInfo);
+    }
+    Ok(chain_info)
+}
+
+/// create a client for every chain hosted by the relay at `base_url`, over HTTP
+#[cfg(feature = "http")]
+pub fn new_http_multi_chain_client(
+    base_url: &str,
+) -> Result<MultiChainClient<HttpTransport>, DrandClientError> {
+    MultiChainClient::new(new_http_transport(), base_url)
+}
+
+/// create a client for the chain with the beacon id `beacon_id` on the relay at `base_url`,
+/// e.g. `new_http_client_for_beacon("https://api.drand.sh", "quicknet")`, over HTTP
+#[cfg(feature = "http")]
+pub fn new_http_client_for_beacon(
+    base_url: &str,
+    beacon_id: &str,
+) -> Result<DrandClient<HttpTransport>, DrandClientError> {
+    new_client_for_beacon(new_http_transport(), base_url, beacon_id)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chain_info::{ChainHash, ChainInfo};
+    use crate::multichain::{
+        fetch_chains, new_client_for_beacon, resolve_beacon_id, MultiChainClient,
+    };
+    use crate::testkit::{TestChain, TestTransport};
+    use crate::verify::SchemeID::{PedersenBlsUnchained, UnchainedOnG1RFC9380};
+    use crate::{DrandClientError, Transport, TransportError};
+    use std::collections::HashMap;
+
+    // a relay listing and serving each chain under `relay/<hash>`
+    #[derive(Default)]
+    struct Relay {
+        listed: Vec<String>,
+        chains: HashMap<String, (String, TestTransport)>,
+    }
+
+    impl Relay {
+        fn with(mut self, listed_as: &str, info: &ChainInfo, transport: TestTransport) -> Self {
+            let info = serde_json::to_string(info).unwrap();
+            self.listed.push(listed_as.to_string());
+            self.chains.insert(listed_as.to_string(), (info, transport));
+            self
+        }
+
+        // list a chain without serving it
+        fn listing(mut self, listed_as: &str) -> Self {
+            self.listed.push(listed_as.to_string());
+            self
+        }
+    }
+
+    impl Transport for Relay {
+        fn fetch(&self, url: &str) -> Result<String, TransportError> {
+            let path = url.strip_prefix("relay/").ok_or(TransportError::NotFound)?;
+            if path == "chains" {
+                return Ok(serde_json::to_string(&self.listed).unwrap());
+            }
+            let (hash, rest) = path.split_once('/').ok_or(TransportError::NotFound)?;
+            let (info, transport) = self.chains.get(hash).ok_or(TransportError::NotFound)?;
+            match rest {
+                "info" => Ok(info.clone()),
+                _ => transport.fetch(url),
+            }
+        }
+    }
+
+    fn quicknet() -> (TestChain, ChainInfo) {
+        let chain = TestChain::with_seed(UnchainedOnG1RFC9380, 3, 1692803367, b"quicknet");
+        let mut info = chain.chain_info().clone();
+        info.metadata.beacon_id = "quicknet".to_string();
+        info.chain_hash = info.compute_chain_hash();
+        (chain, info)
+    }
+
+    #[test]
+    fn every_listed_chain_gets_a_client() -> Result<(), DrandClientError> {
+        let default = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let (quicknet, quicknet_info) = quicknet();
+        let default_hash = hex::encode(&default.chain_info().chain_hash);
+        let quicknet_hash = hex::encode(&quicknet_info.chain_hash);
+        let relay = Relay::default()
+            .with(&default_hash, default.chain_info(), default.transport(2))
+            .with(&quicknet_hash, &quicknet_info, quicknet.transport(5));
+        let client = MultiChainClient::new(relay, "relay/")?;
+
+        let infos: Vec<_> = client.chain_infos().cloned().collect();
+        assert_eq!(infos, [default.chain_info().clone(), quicknet_info]);
+        assert_eq!(
+            client.chain("quicknet").unwrap().randomness(5)?,
+            quicknet.sign_round(5)
+        );
+        assert_eq!(
+            client.chain(&default_hash).unwrap().randomness(2)?,
+            default.sign_round(2)
+        );
+        assert!(client.chain("testnet").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn beacon_ids_resolve_to_chain_hashes() -> Result<(), DrandClientError> {
+        let default = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let (quicknet, quicknet_info) = quicknet();
+        let default_hash = hex::encode(&default.chain_info().chain_hash);
+        let quicknet_hash = hex::encode(&quicknet_info.chain_hash);
+        let relay = Relay::default()
+            .with(&default_hash, default.chain_info(), default.transport(2))
+            .with(&quicknet_hash, &quicknet_info, quicknet.transport(5));
+
+        let chains: Vec<_> = fetch_chains(&relay, "relay")?
+            .iter()
+            .map(ChainHash::to_string)
+            .collect();
+        assert_eq!(chains, [default_hash, quicknet_hash.clone()]);
+        assert_eq!(
+            resolve_beacon_id(&relay, "relay", "quicknet")?,
+            Some(quicknet_hash.parse().unwrap())
+        );
+        assert_eq!(resolve_beacon_id(&relay, "relay", "testnet")?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn a_client_can_be_created_by_beacon_id() -> Result<(), DrandClientError> {
+        let default = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let (quicknet, quicknet_info) = quicknet();
+        let default_hash = hex::encode(&default.chain_info().chain_hash);
+        let quicknet_hash = hex::encode(&quicknet_info.chain_hash);
+        let relay = || {
+            Relay::default()
+                .with(&default_hash, default.chain_info(), default.transport(2))
+                .with(&quicknet_hash, &quicknet_info, quicknet.transport(5))
+        };
+
+        let client = new_client_for_beacon(relay(), "relay/", "quicknet")?;
+        assert_eq!(client.chain_info(), &quicknet_info);
+        assert_eq!(client.base_url, format!("relay/{quicknet_hash}"));
+        assert_eq!(client.randomness(5)?, quicknet.sign_round(5));
+
+        let error = new_client_for_beacon(relay(), "relay", "testnet")
+            .err()
+            .unwrap();
+        assert_eq!(
+            error,
+            DrandClientError::UnknownBeaconId {
+                beacon_id: "testnet".to_string(),
+                available: vec!["testkit".to_string(), "quicknet".to_string()],
+                unreadable: Vec::new(),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            r#"the relay has no chain "testnet", only testkit, quicknet"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unreadable_chains_are_skipped() -> Result<(), DrandClientError> {
+        let default = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let (quicknet, quicknet_info) = quicknet();
+        let quicknet_hash = hex::encode(&quicknet_info.chain_hash);
+        let missing_hash = hex::encode([1; 32]);
+        let mismatched_hash = hex::encode([2; 32]);
+        let relay = || {
+            Relay::default()
+                .listing(&missing_hash)
+                .with(&mismatched_hash, default.chain_info(), default.transport(2))
+                .with(&quicknet_hash, &quicknet_info, quicknet.transport(5))
+        };
+
+        assert_eq!(
+            resolve_beacon_id(&relay(), "relay", "quicknet")?,
+            Some(quicknet_hash.parse().unwrap())
+        );
+        let client = new_client_for_beacon(relay(), "relay", "quicknet")?;
+        assert_eq!(client.randomness(5)?, quicknet.sign_round(5));
+
+        let unknown = || DrandClientError::UnknownBeaconId {
+            beacon_id: "testnet".to_string(),
+            available: vec!["quicknet".to_string()],
+            unreadable: vec![missing_hash.clone(), mismatched_hash.clone()],
+        };
+        assert_eq!(
+            resolve_beacon_id(&relay(), "relay", "testnet"),
+            Err(unknown())
+        );
+        let error = new_client_for_beacon(relay(), "relay", "testnet")
+            .err()
+            .unwrap();
+        assert_eq!(error, unknown());
+        assert_eq!(
+            error.to_string(),
+            format!(
+                r#"the relay has no chain "testnet", only quicknet (the info of {missing_hash}, {mismatched_hash} couldn't be read)"#
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn chains_must_be_served_under_their_own_hash() {
+        let default = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let (_, quicknet_info) = quicknet();
+        let quicknet_hash = hex::encode(&quicknet_info.chain_hash);
+        let relay =
+            Relay::default().with(&quicknet_hash, default.chain_info(), default.transport(2));
+
+        assert!(matches!(
+            MultiChainClient::new(relay, "relay"),
+            Err(DrandClientError::InvalidChainInfo)
+        ));
+    }
+
+    #[test]
+    fn a_broken_chain_doesnt_hide_the_others() -> Result<(), DrandClientError> {
+        let default = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let (quicknet, quicknet_info) = quicknet();
+        let quicknet_hash = hex::encode(&quicknet_info.chain_hash);
+        let missing_hash = hex::encode([1; 32]);
+        let mismatched_hash = hex::encode([2; 32]);
+        let relay = Relay::default()
+            .listing(&missing_hash)
+            .with(&mismatched_hash, default.chain_info(), default.transport(2))
+            .with(&quicknet_hash, &quicknet_info, quicknet.transport(5));
+        let client = MultiChainClient::new(relay, "relay")?;
+
+        let infos: Vec<_> = client.chain_infos().cloned().collect();
+        assert_eq!(infos, [quicknet_info]);
+        assert_eq!(
+            client.chain("quicknet").unwrap().randomness(5)?,
+            quicknet.sign_round(5)
+        );
+        let unreadable: Vec<_> = client
+            .unreadable_chains()
+            .iter()
+            .map(ChainHash::to_string)
+            .collect();
+        assert_eq!(unreadable, [missing_hash, mismatched_hash]);
+        Ok(())
+    }
+}
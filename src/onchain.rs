@@ -0,0 +1,248 @@
+//! # onchain
+//!
+//! borsh encodings of beacons and of the chain parameters a program needs to check them, for
+//! passing into NEAR and Solana programs. The layouts are stable:
+//!
+//! - `Beacon`: `round_number: u64`, `randomness: Vec<u8>`, `signature: Vec<u8>`,
+//!   `previous_signature: Vec<u8>`
+//! - `CompactChainInfo`: `scheme_id: u8`, `public_key: Vec<u8>`, `chain_hash: Vec<u8>`,
+//!   `genesis_time: u64`, `period_seconds: u32`
+//!
+//! with integers little-endian and byte vectors prefixed by their length as a `u32`, as borsh
+//! does. `scheme_id` is 0 for `pedersen-bls-chained`, 1 for `pedersen-bls-unchained`, 2 for
+//! `bls-unchained-g1-rfc9380` and 3 for `bls-bn254-unchained-on-g1`.
+//!
+//! Deserializing checks the lengths of the byte vectors, and `borsh::from_slice` rejects
+//! trailing bytes.
+//!
+
+use crate::chain_info::ChainInfo;
+use crate::verify::{Beacon, SchemeID};
+use borsh::{BorshDeserialize, BorshSerialize};
+use std::io::{Error, ErrorKind, Read, Result, Write};
+
+const RANDOMNESS_LENGTH: usize = 32;
+const CHAIN_HASH_LENGTH: usize = 32;
+const SIGNATURE_LENGTHS: [usize; 3] = [48, 64, 96];
+const PUBLIC_KEY_LENGTHS: [usize; 3] = [48, 96, 128];
+
+/// the chain parameters needed to verify beacons and map rounds to times on-chain
+#[derive(Debug, PartialEq, Clone)]
+pub struct CompactChainInfo {
+    pub scheme_id: SchemeID,
+    pub public_key: Vec<u8>,
+    pub chain_hash: Vec<u8>,
+    pub genesis_time: u64,
+    pub period_seconds: u32,
+}
+
+impl From<&ChainInfo> for CompactChainInfo {
+    fn from(info: &ChainInfo) -> Self {
+        CompactChainInfo {
+            scheme_id: info.scheme_id.clone(),
+            public_key: info.public_key.clone(),
+            chain_hash: info.chain_hash.clone(),
+            genesis_time: info.genesis_time,
+            // the chain hash commits to the period as a u32 too
+            period_seconds: info.period_seconds as u32,
+        }
+    }
+}
+
+impl BorshSerialize for SchemeID {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let tag: u8 = match self {
+            SchemeID::PedersenBlsChained => 0,
+            SchemeID::PedersenBlsUnchained => 1,
+            SchemeID::UnchainedOnG1RFC9380 => 2,
+            SchemeID::Bn254UnchainedOnG1 => 3,
+        };
+        tag.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for SchemeID {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        match u8::deserialize_reader(reader)? {
+            0 => Ok(SchemeID::PedersenBlsChained),
+            1 => Ok(SchemeID::PedersenBlsUnchained),
+            2 => Ok(SchemeID::UnchainedOnG1RFC9380),
+            3 => Ok(SchemeID::Bn254UnchainedOnG1),
+            tag => Err(invalid_data(format!("unknown scheme ID {tag}"))),
+        }
+    }
+}
+
+impl BorshSerialize for Beacon {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.round_number.serialize(writer)?;
+        self.randomness.serialize(writer)?;
+        self.signature.serialize(writer)?;
+        self.previous_signature.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for Beacon {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        let round_number = u64::deserialize_reader(reader)?;
+        let randomness = bytes(reader, "randomness", &[RANDOMNESS_LENGTH])?;
+        let signature = bytes(reader, "signature", &SIGNATURE_LENGTHS)?;
+        // unchained beacons don't carry a previous signature
+        let previous_signature = Vec::<u8>::deserialize_reader(reader)?;
+        if !previous_signature.is_empty() && !SIGNATURE_LENGTHS.contains(&previous_signature.len())
+        {
+            return Err(invalid_length(
+                "previous_signature",
+                previous_signature.len(),
+            ));
+        }
+
+        Ok(Beacon {
+            round_number,
+            randomness,
+            signature,
+            previous_signature,
+        })
+    }
+}
+
+impl BorshSerialize for CompactChainInfo {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.scheme_id.serialize(writer)?;
+        self.public_key.serialize(writer)?;
+        self.chain_hash.serialize(writer)?;
+        self.genesis_time.serialize(writer)?;
+        self.period_seconds.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for CompactChainInfo {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        let scheme_id = SchemeID::deserialize_reader(reader)?;
+        let public_key = bytes(reader, "public_key", &PUBLIC_KEY_LENGTHS)?;
+        let chain_hash = bytes(reader, "chain_hash", &[CHAIN_HASH_LENGTH])?;
+        let genesis_time = u64::deserialize_reader(reader)?;
+        let period_seconds = u32::deserialize_reader(reader)?;
+        if period_seconds == 0 {
+            return Err(invalid_data("period_seconds can't be zero".to_string()));
+        }
+
+        Ok(CompactChainInfo {
+            scheme_id,
+            public_key,
+            chain_hash,
+            genesis_time,
+            period_seconds,
+        })
+    }
+}
+
+fn bytes<R: Read>(reader: &mut R, field: &str, lengths: &[usize]) -> Result<Vec<u8>> {
+    let bytes = Vec::<u8>::deserialize_reader(reader)?;
+    if !lengths.contains(&bytes.len()) {
+        return Err(invalid_length(field, bytes.len()));
+    }
+    Ok(bytes)
+}
+
+fn invalid_length(field: &str, len: usize) -> Error {
+    invalid_data(format!("invalid {field} length {len}"))
+}
+
+fn invalid_data(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::onchain::CompactChainInfo;
+    use crate::testkit::{mainnet_info, mainnet_round_two};
+    use crate::verify::Beacon;
+
+    #[test]
+    fn beacon_bytes_are_pinned() {
+        let beacon = mainnet_round_two();
+        let expected = [
+            "0200000000000000",
+            "20000000",
+            hex::encode(&beacon.randomness).as_str(),
+            "60000000",
+            hex::encode(&beacon.signature).as_str(),
+            "60000000",
+            hex::encode(&beacon.previous_signature).as_str(),
+        ]
+        .concat();
+        let bytes = borsh::to_vec(&beacon).unwrap();
+
+        assert_eq!(hex::encode(&bytes), expected);
+        assert_eq!(borsh::from_slice::<Beacon>(&bytes).unwrap(), beacon);
+    }
+
+    #[test]
+    fn compact_chain_info_bytes_are_pinned() {
+        let mainnet = mainnet_info();
+        let expected = [
+            "00",
+            "30000000",
+            hex::encode(&mainnet.public_key).as_str(),
+            "20000000",
+            hex::encode(&mainnet.chain_hash).as_str(),
+            "8a58185f00000000",
+            "1e000000",
+        ]
+        .concat();
+        let info = CompactChainInfo::from(&mainnet);
+        let bytes = borsh::to_vec(&info).unwrap();
+
+        assert_eq!(hex::encode(&bytes), expected);
+        assert_eq!(borsh::from_slice::<CompactChainInfo>(&bytes).unwrap(), info);
+    }
+
+    #[test]
+    fn trailing_bytes_are_rejected() {
+        let mut bytes = borsh::to_vec(&mainnet_round_two()).unwrap();
+        bytes.push(0);
+        assert!(borsh::from_slice::<Beacon>(&bytes).is_err());
+
+        let mut bytes = borsh::to_vec(&CompactChainInfo::from(&mainnet_info())).unwrap();
+        bytes.push(0);
+        assert!(borsh::from_slice::<CompactChainInfo>(&bytes).is_err());
+    }
+
+    #[test]
+    fn invalid_lengths_are_rejected() {
+        let mut short_randomness = mainnet_round_two();
+        short_randomness.randomness.pop();
+        let mut short_signature = mainnet_round_two();
+        short_signature.signature.pop();
+        let mut short_previous_signature = mainnet_round_two();
+        short_previous_signature.previous_signature.pop();
+
+        for invalid in [short_randomness, short_signature, short_previous_signature] {
+            let bytes = borsh::to_vec(&invalid).unwrap();
+            assert!(borsh::from_slice::<Beacon>(&bytes).is_err());
+        }
+
+        let mut unchained = mainnet_round_two();
+        unchained.previous_signature.clear();
+        let bytes = borsh::to_vec(&unchained).unwrap();
+        assert_eq!(borsh::from_slice::<Beacon>(&bytes).unwrap(), unchained);
+    }
+
+    #[test]
+    fn invalid_chain_info_is_rejected() {
+        let mut zero_period = CompactChainInfo::from(&mainnet_info());
+        zero_period.period_seconds = 0;
+        let mut short_hash = CompactChainInfo::from(&mainnet_info());
+        short_hash.chain_hash.pop();
+
+        for invalid in [zero_period, short_hash] {
+            let bytes = borsh::to_vec(&invalid).unwrap();
+            assert!(borsh::from_slice::<CompactChainInfo>(&bytes).is_err());
+        }
+
+        let mut unknown_scheme = borsh::to_vec(&CompactChainInfo::from(&mainnet_info())).unwrap();
+        unknown_scheme[0] = 4;
+        assert!(borsh::from_slice::<CompactChainInfo>(&unknown_scheme).is_err());
+    }
+}
@@ -0,0 +1,661 @@
+//! # pinned
+//!
+//! chain info that is parsed and checked at compile time, for deployments that bake the
+//! trusted chain into the binary instead of loading it at runtime. `pin_chain!` takes a
+//! relay's `/info` JSON, either as a literal or as `file = "path"` (resolved like
+//! `include_str!`), and expands to a `PinnedChainInfo` constant:
+//!
+//! ```
+//! use drand_client_rs::pin_chain;
+//! use drand_client_rs::pinned::PinnedChainInfo;
+//!
+//! const QUICKNET: PinnedChainInfo = pin_chain!(
+//!     r#"{
+//!         "public_key": "83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a",
+//!         "period": 3,
+//!         "genesis_time": 1692803367,
+//!         "hash": "52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971",
+//!         "groupHash": "f477d5c89f21a17c863a7f937c6a6d15859414d2be09cd448d4279af331c5d3e",
+//!         "schemeID": "bls-unchained-g1-rfc9380",
+//!         "metadata": { "beaconID": "quicknet" }
+//!     }"#
+//! );
+//!
+//! let info = QUICKNET.to_chain_info();
+//! assert_eq!(info.compute_chain_hash(), info.chain_hash);
+//! ```
+//!
+//! The JSON accepts the same field names and aliases as `ChainInfo`'s `Deserialize`, and
+//! unknown fields are skipped. Invalid JSON, a missing or duplicated field, invalid hex, an
+//! unknown scheme, a zero period or genesis time, or a chain hash that doesn't match the
+//! chain parameters fail the build, with the reason as the panic message of the constant
+//! evaluation. String escapes and non-integer numbers aren't supported, as relays don't
+//! serve them in chain info.
+//!
+
+use crate::chain_info::{ChainInfo, ChainInfoMetadata};
+use crate::verify::SchemeID;
+
+/// the largest public key of the known schemes, bn254's G2 point
+const MAX_PUBLIC_KEY_LENGTH: usize = 128;
+const HASH_LENGTH: usize = 32;
+
+/// expand to a `PinnedChainInfo` for the `/info` JSON literal, or the file at
+/// `file = "path"`, failing the build if it isn't valid chain info
+#[macro_export]
+macro_rules! pin_chain {
+    (file = $path:literal) => {
+        $crate::pin_chain!(include_str!($path))
+    };
+    ($json:expr) => {{
+        // a constant, so the JSON is checked at compile time wherever the macro is used
+        const PINNED: $crate::pinned::PinnedChainInfo =
+            $crate::pinned::PinnedChainInfo::parse($json);
+        PINNED
+    }};
+}
+
+/// chain info that passed the compile-time checks of `pin_chain!`
+#[derive(Debug, PartialEq, Clone)]
+pub struct PinnedChainInfo {
+    scheme_id: SchemeID,
+    public_key: [u8; MAX_PUBLIC_KEY_LENGTH],
+    public_key_length: usize,
+    chain_hash: [u8; HASH_LENGTH],
+    group_hash: [u8; HASH_LENGTH],
+    genesis_time: u64,
+    period_seconds: usize,
+    beacon_id: &'static str,
+}
+
+impl PinnedChainInfo {
+    /// parse and check `/info` JSON. Prefer `pin_chain!`, which guarantees this runs at
+    /// compile time; called at runtime, invalid JSON panics. Like every function of the
+    /// parser that can panic, it is `#[track_caller]`, so a failure is reported where
+    /// `pin_chain!` is used rather than somewhere inside the parser.
+    #[track_caller]
+    pub const fn parse(json: &'static str) -> PinnedChainInfo {
+        let b = json.as_bytes();
+        let mut scheme_id: Option<&'static [u8]> = None;
+        let mut public_key: Option<&'static [u8]> = None;
+        let mut chain_hash: Option<&'static [u8]> = None;
+        let mut group_hash: Option<&'static [u8]> = None;
+        let mut genesis_time: Option<u64> = None;
+        let mut period_seconds: Option<u64> = None;
+        let mut beacon_id: Option<&'static [u8]> = None;
+
+        let mut i = expect(b, skip_whitespace(b, 0), b'{');
+        i = skip_whitespace(b, i);
+        if byte(b, i) == b'}' {
+            i += 1;
+        } else {
+            loop {
+                let (key, next) = string(b, i);
+                i = skip_whitespace(b, expect(b, skip_whitespace(b, next), b':'));
+
+                if eq(key, b"scheme_id") || eq(key, b"schemeID") {
+                    if scheme_id.is_some() {
+                        panic!("pin_chain!: duplicate field `scheme_id`");
+                    }
+                    let (value, next) = string(b, i);
+                    scheme_id = Some(value);
+                    i = next;
+                } else if eq(key, b"public_key") {
+                    if public_key.is_some() {
+                        panic!("pin_chain!: duplicate field `public_key`");
+                    }
+                    let (value, next) = string(b, i);
+                    public_key = Some(value);
+                    i = next;
+                } else if eq(key, b"chain_hash") || eq(key, b"hash") {
+                    if chain_hash.is_some() {
+                        panic!("pin_chain!: duplicate field `chain_hash`");
+                    }
+                    let (value, next) = string(b, i);
+                    chain_hash = Some(value);
+                    i = next;
+                } else if eq(key, b"group_hash") || eq(key, b"groupHash") {
+                    if group_hash.is_some() {
+                        panic!("pin_chain!: duplicate field `group_hash`");
+                    }
+                    let (value, next) = string(b, i);
+                    group_hash = Some(value);
+                    i = next;
+                } else if eq(key, b"genesis_time") {
+                    if genesis_time.is_some() {
+                        panic!("pin_chain!: duplicate field `genesis_time`");
+                    }
+                    let (value, next) = integer(b, i);
+                    genesis_time = Some(value);
+                    i = next;
+                } else if eq(key, b"period_seconds") || eq(key, b"period") {
+                    if period_seconds.is_some() {
+                        panic!("pin_chain!: duplicate field `period_seconds`");
+                    }
+                    let (value, next) = integer(b, i);
+                    period_seconds = Some(value);
+                    i = next;
+                } else if eq(key, b"metadata") {
+                    if beacon_id.is_some() {
+                        panic!("pin_chain!: duplicate field `metadata`");
+                    }
+                    let (value, next) = metadata(b, i);
+                    beacon_id = Some(value);
+                    i = next;
+                } else {
+                    i = skip_value(b, i);
+                }
+
+                i = skip_whitespace(b, i);
+                if byte(b, i) == b',' {
+                    i = skip_whitespace(b, i + 1);
+                } else {
+                    i = expect(b, i, b'}');
+                    break;
+                }
+            }
+        }
+        if skip_whitespace(b, i) != b.len() {
+            panic!("pin_chain!: trailing characters after the chain info");
+        }
+
+        let scheme_id = match scheme_id {
+            Some(name) if eq(name, b"pedersen-bls-chained") => SchemeID::PedersenBlsChained,
+            Some(name) if eq(name, b"pedersen-bls-unchained") => SchemeID::PedersenBlsUnchained,
+            Some(name) if eq(name, b"bls-unchained-g1-rfc9380") => SchemeID::UnchainedOnG1RFC9380,
+            Some(name) if eq(name, b"bls-bn254-unchained-on-g1") => SchemeID::Bn254UnchainedOnG1,
+            Some(_) => panic!("pin_chain!: unknown scheme ID"),
+            None => panic!("pin_chain!: missing field `scheme_id`"),
+        };
+        let (public_key, public_key_length) = match public_key {
+            Some(hex) => match decode_hex::<MAX_PUBLIC_KEY_LENGTH>(hex) {
+                Some(decoded) => decoded,
+                None => panic!("pin_chain!: `public_key` is not valid hex"),
+            },
+            None => panic!("pin_chain!: missing field `public_key`"),
+        };
+        let chain_hash = match chain_hash {
+            Some(hex) => match decode_hex::<HASH_LENGTH>(hex) {
+                Some((decoded, HASH_LENGTH)) => decoded,
+                _ => panic!("pin_chain!: `chain_hash` is not 32 bytes of hex"),
+            },
+            None => panic!("pin_chain!: missing field `chain_hash`"),
+        };
+        let group_hash = match group_hash {
+            Some(hex) => match decode_hex::<HASH_LENGTH>(hex) {
+                Some((decoded, HASH_LENGTH)) => decoded,
+                _ => panic!("pin_chain!: `group_hash` is not 32 bytes of hex"),
+            },
+            None => panic!("pin_chain!: missing field `group_hash`"),
+        };
+        let genesis_time = match genesis_time {
+            Some(0) => panic!("pin_chain!: `genesis_time` can't be zero"),
+            Some(genesis_time) => genesis_time,
+            None => panic!("pin_chain!: missing field `genesis_time`"),
+        };
+        let period_seconds = match period_seconds {
+            Some(0) => panic!("pin_chain!: `period_seconds` can't be zero"),
+            Some(period) if period > u32::MAX as u64 => {
+                panic!("pin_chain!: `period_seconds` doesn't fit in a u32")
+            }
+            Some(period) => period as usize,
+            None => panic!("pin_chain!: missing field `period_seconds`"),
+        };
+        let beacon_id = match beacon_id {
+            Some(id) => match core::str::from_utf8(id) {
+                Ok(id) => id,
+                Err(_) => panic!("pin_chain!: `beacon_id` is not UTF-8"),
+            },
+            None => panic!("pin_chain!: missing field `metadata`"),
+        };
+
+        // the same derivation as `ChainInfo::compute_chain_hash`
+        let (public_key_bytes, _) = public_key.split_at(public_key_length);
+        let mut hasher = Sha256::new()
+            .update(&(period_seconds as u32).to_be_bytes())
+            .update(&(genesis_time as i64).to_be_bytes())
+            .update(public_key_bytes)
+            .update(&group_hash);
+        if !beacon_id.is_empty() && !eq(beacon_id.as_bytes(), b"default") {
+            hasher = hasher.update(beacon_id.as_bytes());
+        }
+        if !eq(&hasher.finalize(), &chain_hash) {
+            panic!("pin_chain!: the chain hash doesn't match the chain parameters");
+        }
+
+        PinnedChainInfo {
+            scheme_id,
+            public_key,
+            public_key_length,
+            chain_hash,
+            group_hash,
+            genesis_time,
+            period_seconds,
+            beacon_id,
+        }
+    }
+
+    pub fn chain_hash(&self) -> &[u8] {
+        &self.chain_hash
+    }
+
+    /// the chain info, e.g. for creating a client without fetching it
+    pub fn to_chain_info(&self) -> ChainInfo {
+        ChainInfo {
+            scheme_id: self.scheme_id.clone(),
+            public_key: self.public_key[..self.public_key_length].to_vec(),
+            chain_hash: self.chain_hash.to_vec(),
+            group_hash: self.group_hash.to_vec(),
+            genesis_time: self.genesis_time,
+            period_seconds: self.period_seconds,
+            metadata: ChainInfoMetadata {
+                beacon_id: self.beacon_id.to_string(),
+            },
+        }
+    }
+}
+
+impl From<&PinnedChainInfo> for ChainInfo {
+    fn from(pinned: &PinnedChainInfo) -> Self {
+        pinned.to_chain_info()
+    }
+}
+
+/// the value of `metadata`'s `beacon_id`, skipping any other metadata
+#[track_caller]
+const fn metadata(b: &'static [u8], i: usize) -> (&'static [u8], usize) {
+    let mut beacon_id: Option<&'static [u8]> = None;
+    let mut i = skip_whitespace(b, expect(b, i, b'{'));
+    if byte(b, i) == b'}' {
+        i += 1;
+    } else {
+        loop {
+            let (key, next) = string(b, i);
+            i = skip_whitespace(b, expect(b, skip_whitespace(b, next), b':'));
+            if eq(key, b"beacon_id") || eq(key, b"beaconID") {
+                if beacon_id.is_some() {
+                    panic!("pin_chain!: duplicate field `beacon_id`");
+                }
+                let (value, next) = string(b, i);
+                beacon_id = Some(value);
+                i = next;
+            } else {
+                i = skip_value(b, i);
+            }
+
+            i = skip_whitespace(b, i);
+            if byte(b, i) == b',' {
+                i = skip_whitespace(b, i + 1);
+            } else {
+                i = expect(b, i, b'}');
+                break;
+            }
+        }
+    }
+    match beacon_id {
+        Some(beacon_id) => (beacon_id, i),
+        None => panic!("pin_chain!: missing field `beacon_id` in `metadata`"),
+    }
+}
+
+/// the index just past the JSON value starting at `i`
+#[track_caller]
+const fn skip_value(b: &'static [u8], i: usize) -> usize {
+    match byte(b, i) {
+        b'"' => string(b, i).1,
+        b'{' | b'[' => {
+            let close = if b[i] == b'{' { b'}' } else { b']' };
+            let mut i = skip_whitespace(b, i + 1);
+            if byte(b, i) == close {
+                return i + 1;
+            }
+            loop {
+                if close == b'}' {
+                    let (_, next) = string(b, i);
+                    i = skip_whitespace(b, expect(b, skip_whitespace(b, next), b':'));
+                }
+                i = skip_whitespace(b, skip_value(b, i));
+                if byte(b, i) == b',' {
+                    i = skip_whitespace(b, i + 1);
+                } else {
+                    return expect(b, i, close);
+                }
+            }
+        }
+        b't' => literal(b, i, b"true"),
+        b'f' => literal(b, i, b"false"),
+        b'n' => literal(b, i, b"null"),
+        b'-' | b'0'..=b'9' => {
+            let mut i = i + 1;
+            while i < b.len() && matches!(b[i], b'0'..=b'9' | b'.' | b'e' | b'E' | b'+' | b'-') {
+                i += 1;
+            }
+            i
+        }
+        _ => panic!("pin_chain!: invalid JSON"),
+    }
+}
+
+/// the contents of the string starting at `i`, and the index just past it
+#[track_caller]
+const fn string(b: &'static [u8], i: usize) -> (&'static [u8], usize) {
+    let start = expect(b, i, b'"');
+    let mut end = start;
+    while byte(b, end) != b'"' {
+        if b[end] == b'\\' {
+            panic!("pin_chain!: string escapes are not supported");
+        }
+        end += 1;
+    }
+    let (_, rest) = b.split_at(start);
+    let (contents, _) = rest.split_at(end - start);
+    (contents, end + 1)
+}
+
+#[track_caller]
+
+const fn integer(b: &'static [u8], i: usize) -> (u64, usize) {
+    if !byte(b, i).is_ascii_digit() {
+        panic!("pin_chain!: expected a non-negative integer");
+    }
+    let mut value: u64 = 0;
+    let mut i = i;
+    while i < b.len() && b[i].is_ascii_digit() {
+        value = match value.checked_mul(10) {
+            Some(value) => match value.checked_add((b[i] - b'0') as u64) {
+                Some(value) => value,
+                None => panic!("pin_chain!: integer out of range"),
+            },
+            None => panic!("pin_chain!: integer out of range"),
+        };
+        i += 1;
+    }
+    if i < b.len() && matches!(b[i], b'.' | b'e' | b'E') {
+        panic!("pin_chain!: expected a non-negative integer");
+    }
+    (value, i)
+}
+
+#[track_caller]
+
+const fn literal(b: &'static [u8], i: usize, expected: &[u8]) -> usize {
+    let mut j = 0;
+    while j < expected.len() {
+        if byte(b, i + j) != expected[j] {
+            panic!("pin_chain!: invalid JSON");
+        }
+        j += 1;
+    }
+    i + expected.len()
+}
+
+/// `i` plus one if the byte at `i` is `c`
+#[track_caller]
+const fn expect(b: &[u8], i: usize, c: u8) -> usize {
+    if byte(b, i) != c {
+        panic!("pin_chain!: invalid JSON");
+    }
+    i + 1
+}
+
+#[track_caller]
+
+const fn byte(b: &[u8], i: usize) -> u8 {
+    if i >= b.len() {
+        panic!("pin_chain!: unexpected end of JSON");
+    }
+    b[i]
+}
+
+const fn skip_whitespace(b: &[u8], mut i: usize) -> usize {
+    while i < b.len() && matches!(b[i], b' ' | b'\t' | b'\n' | b'\r') {
+        i += 1;
+    }
+    i
+}
+
+const fn eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// hex of either case decoded into the front of an `N` byte array, with the decoded length
+const fn decode_hex<const N: usize>(hex: &[u8]) -> Option<([u8; N], usize)> {
+    if hex.len() % 2 != 0 || hex.len() / 2 > N {
+        return None;
+    }
+    let mut bytes = [0u8; N];
+    let mut i = 0;
+    while i < hex.len() / 2 {
+        match (nibble(hex[2 * i]), nibble(hex[2 * i + 1])) {
+            (Some(high), Some(low)) => bytes[i] = (high << 4) | low,
+            _ => return None,
+        }
+        i += 1;
+    }
+    Some((bytes, hex.len() / 2))
+}
+
+const fn nibble(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// SHA-256 usable in constant evaluation, which the `sha2` crate isn't
+struct Sha256 {
+    state: [u32; 8],
+    block: [u8; 64],
+    block_length: usize,
+    length: u64,
+}
+
+const ROUND_CONSTANTS: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+impl Sha256 {
+    const fn new() -> Self {
+        Sha256 {
+            state: [
+                0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+                0x5be0cd19,
+            ],
+            block: [0; 64],
+            block_length: 0,
+            length: 0,
+        }
+    }
+
+    const fn update(mut self, data: &[u8]) -> Self {
+        let mut i = 0;
+        while i < data.len() {
+            self.block[self.block_length] = data[i];
+            self.block_length += 1;
+            if self.block_length == 64 {
+                self.state = compress(self.state, &self.block);
+                self.block_length = 0;
+            }
+            i += 1;
+        }
+        self.length += data.len() as u64;
+        self
+    }
+
+    const fn finalize(mut self) -> [u8; 32] {
+        self.block[self.block_length] = 0x80;
+        self.block_length += 1;
+        if self.block_length > 56 {
+            while self.block_length < 64 {
+                self.block[self.block_length] = 0;
+                self.block_length += 1;
+            }
+            self.state = compress(self.state, &self.block);
+            self.block_length = 0;
+        }
+        while self.block_length < 56 {
+            self.block[self.block_length] = 0;
+            self.block_length += 1;
+        }
+        let bit_length = (self.length * 8).to_be_bytes();
+        let mut i = 0;
+        while i < 8 {
+            self.block[56 + i] = bit_length[i];
+            i += 1;
+        }
+        self.state = compress(self.state, &self.block);
+
+        let mut digest = [0u8; 32];
+        let mut i = 0;
+        while i < 8 {
+            let word = self.state[i].to_be_bytes();
+            digest[4 * i] = word[0];
+            digest[4 * i + 1] = word[1];
+            digest[4 * i + 2] = word[2];
+            digest[4 * i + 3] = word[3];
+            i += 1;
+        }
+        digest
+    }
+}
+
+const fn compress(state: [u32; 8], block: &[u8; 64]) -> [u32; 8] {
+    let mut w = [0u32; 64];
+    let mut i = 0;
+    while i < 16 {
+        w[i] = u32::from_be_bytes([
+            block[4 * i],
+            block[4 * i + 1],
+            block[4 * i + 2],
+            block[4 * i + 3],
+        ]);
+        i += 1;
+    }
+    while i < 64 {
+        let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+        i += 1;
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+    let mut i = 0;
+    while i < 64 {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(ROUND_CONSTANTS[i])
+            .wrapping_add(w[i]);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+        i += 1;
+    }
+
+    [
+        state[0].wrapping_add(a),
+        state[1].wrapping_add(b),
+        state[2].wrapping_add(c),
+        state[3].wrapping_add(d),
+        state[4].wrapping_add(e),
+        state[5].wrapping_add(f),
+        state[6].wrapping_add(g),
+        state[7].wrapping_add(h),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pinned::{PinnedChainInfo, Sha256};
+    use crate::testkit::MAINNET_INFO_JSON;
+    use crate::verify::SchemeID;
+    #[cfg(feature = "serde")]
+    use crate::{chain_info::ChainInfo, chains::MAINNET};
+    use sha2::Digest;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn pinned_chain_info_matches_runtime_parsing() {
+        let parsed: ChainInfo = serde_json::from_str(MAINNET_INFO_JSON).unwrap();
+
+        assert_eq!(MAINNET.to_chain_info(), parsed);
+        assert_eq!(
+            ChainInfo::from(&MAINNET).scheme_id,
+            SchemeID::PedersenBlsChained
+        );
+    }
+
+    #[test]
+    fn unknown_fields_and_serde_names_are_accepted() {
+        let pinned = PinnedChainInfo::parse(
+            r#" {
+                "scheme_id": "bls-unchained-g1-rfc9380",
+                "public_key": "83CF0F2896ADEE7EB8B5F01FCAD3912212C437E0073E911FB90022D3E760183C8C4B450B6A0A6C3AC6A5776A2D1064510D1FEC758C921CC22B0E17E63AAF4BCB5ED66304DE9CF809BD274CA73BAB4AF5A6E9C76A4BC09E76EAE8991EF5ECE45A",
+                "chain_hash": "52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971",
+                "group_hash": "f477d5c89f21a17c863a7f937c6a6d15859414d2be09cd448d4279af331c5d3e",
+                "genesis_time": 1692803367,
+                "period_seconds": 3,
+                "extra": [1, -2.5e3, true, null, {"nested": []}],
+                "metadata": { "beacon_id": "quicknet", "note": "x" }
+            } "#,
+        );
+        let info = pinned.to_chain_info();
+
+        assert_eq!(info.scheme_id, SchemeID::UnchainedOnG1RFC9380);
+        assert_eq!(info.public_key.len(), 96);
+        assert_eq!(info.metadata.beacon_id, "quicknet");
+        assert_eq!(info.compute_chain_hash(), pinned.chain_hash());
+    }
+
+    #[test]
+    #[should_panic(expected = "the chain hash doesn't match the chain parameters")]
+    fn mismatched_chain_hash_panics_at_runtime() {
+        // mainnet with a different period, so its hash no longer matches
+        let json = MAINNET_INFO_JSON.replace(r#""period":30"#, r#""period":3"#);
+        PinnedChainInfo::parse(json.leak());
+    }
+
+    #[test]
+    fn const_sha256_matches_sha2() {
+        for len in [0, 1, 55, 56, 63, 64, 65, 200] {
+            let data: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            assert_eq!(
+                Sha256::new().update(&data).finalize().to_vec(),
+                sha2::Sha256::digest(&data).to_vec(),
+                "length {len}"
+            );
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! # python
+//!
+//! a pyo3 extension module exposing beacon verification and the blocking HTTP client to
+//! Python. Wheels are built with maturin from `pyproject.toml`. Beacons and chain info are
+//! returned as dicts with the serde field names and hex-encoded bytes, and every
+//! `DrandClientError` variant raises an exception of the same name deriving from
+//! `DrandError`.
+//!
+
+use crate::chain_info::ChainInfo;
+use crate::http::HttpTransport;
+use crate::verify::{Beacon, SchemeID, UnknownSchemeError};
+use crate::{new_http_client, verify, DrandClientError};
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+create_exception!(drand_client_rs, DrandError, PyException);
+create_exception!(drand_client_rs, InvalidRound, DrandError);
+create_exception!(drand_client_rs, InvalidBeacon, DrandError);
+create_exception!(drand_client_rs, FailedVerification, DrandError);
+create_exception!(drand_client_rs, ChainMigrated, DrandError);
+create_exception!(drand_client_rs, InvalidChainInfo, DrandError);
+create_exception!(drand_client_rs, ChainInfoMismatch, DrandError);
+create_exception!(drand_client_rs, ChainHashMismatch, DrandError);
+create_exception!(drand_client_rs, UnknownBeaconId, DrandError);
+create_exception!(drand_client_rs, InvalidProxy, DrandError);
+create_exception!(drand_client_rs, ChainInfoNotReady, DrandError);
+create_exception!(drand_client_rs, NotResponding, DrandError);
+create_exception!(drand_client_rs, RateLimited, DrandError);
+create_exception!(drand_client_rs, Tls, DrandError);
+create_exception!(drand_client_rs, RoundBeforeGenesis, DrandError);
+create_exception!(drand_client_rs, RoundNotYetAvailable, DrandError);
+create_exception!(drand_client_rs, VerificationDisabled, DrandError);
+create_exception!(drand_client_rs, UnexpectedError, DrandError);
+
+/// verify a beacon, raising `FailedVerification` (or `VerificationDisabled`) if it doesn't
+/// verify and `ValueError` for an unknown scheme or invalid hex
+#[pyfunction]
+fn verify_beacon(
+    scheme_id: &str,
+    public_key_hex: &str,
+    round: u64,
+    signature_hex: &str,
+    previous_signature_hex: &str,
+    randomness_hex: &str,
+) -> PyResult<()> {
+    let scheme_id: SchemeID = scheme_id
+        .parse()
+        .map_err(|e: UnknownSchemeError| PyValueError::new_err(e.to_string()))?;
+    let public_key = decode("public_key_hex", public_key_hex)?;
+    let beacon = Beacon {
+        round_number: round,
+        randomness: decode("randomness_hex", randomness_hex)?,
+        signature: decode("signature_hex", signature_hex)?,
+        previous_signature: decode("previous_signature_hex", previous_signature_hex)?,
+    };
+
+    verify::verify_beacon(&scheme_id, &public_key, beacon)
+        .map(|_| ())
+        .map_err(|e| match e {
+            verify::VerificationError::VerificationDisabled => {
+                VerificationDisabled::new_err(e.to_string())
+            }
+            _ => FailedVerification::new_err(e.to_string()),
+        })
+}
+
+/// the blocking HTTP client. The GIL is released while requests are in flight.
+#[pyclass(name = "DrandClient", frozen)]
+struct PyDrandClient {
+    client: crate::DrandClient<HttpTransport>,
+}
+
+#[pymethods]
+impl PyDrandClient {
+    #[new]
+    fn new(py: Python<'_>, base_url: String) -> PyResult<Self> {
+        let client = py
+            .allow_threads(|| new_http_client(base_url))
+            .map_err(to_py_err)?;
+        Ok(PyDrandClient { client })
+    }
+
+    fn latest<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let beacon = py
+            .allow_threads(|| self.client.latest_randomness())
+            .map_err(to_py_err)?;
+        beacon_dict(py, &beacon)
+    }
+
+    fn round<'py>(&self, py: Python<'py>, n: u64) -> PyResult<Bound<'py, PyDict>> {
+        let beacon = py
+            .allow_threads(|| self.client.randomness(n))
+            .map_err(to_py_err)?;
+        beacon_dict(py, &beacon)
+    }
+
+    fn chain_info<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        chain_info_dict(py, self.client.chain_info())
+    }
+}
+
+#[pymodule]
+fn drand_client_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add_function(wrap_pyfunction!(verify_beacon, m)?)?;
+    m.add_class::<PyDrandClient>()?;
+    macro_rules! add_exceptions {
+        ($($name:ident),*) => {
+            $(m.add(stringify!($name), py.get_type_bound::<$name>())?;)*
+        };
+    }
+    add_exceptions!(
+        DrandError,
+        InvalidRound,
+        InvalidBeacon,
+        FailedVerification,
+        ChainMigrated,
+        InvalidChainInfo,
+        ChainInfoMismatch,
+        ChainHashMismatch,
+        UnknownBeaconId,
+        InvalidProxy,
+        ChainInfoNotReady,
+        NotResponding,
+        RateLimited,
+        Tls,
+        RoundBeforeGenesis,
+        RoundNotYetAvailable,
+        VerificationDisabled,
+        UnexpectedError
+    );
+    Ok(())
+}
+
+fn to_py_err(e: DrandClientError) -> PyErr {
+    let message = e.to_string();
+    match e {
+        DrandClientError::InvalidRound => InvalidRound::new_err(message),
+        DrandClientError::InvalidBeacon => InvalidBeacon::new_err(message),
+        DrandClientError::FailedVerification(_) => FailedVerification::new_err(message),
+        DrandClientError::ChainMigrated { .. } => ChainMigrated::new_err(message),
+        DrandClientError::InvalidChainInfo => InvalidChainInfo::new_err(message),
+        DrandClientError::ChainInfoMismatch => ChainInfoMismatch::new_err(message),
+        DrandClientError::ChainHashMismatch { .. } => ChainHashMismatch::new_err(message),
+        DrandClientError::UnknownBeaconId { .. } => UnknownBeaconId::new_err(message),
+        DrandClientError::InvalidProxy => InvalidProxy::new_err(message),
+        DrandClientError::ChainInfoNotReady => ChainInfoNotReady::new_err(message),
+        DrandClientError::NotResponding => NotResponding::new_err(message),
+        DrandClientError::RateLimited => RateLimited::new_err(message),
+        DrandClientError::Tls { .. } => Tls::new_err(message),
+        DrandClientError::RoundBeforeGenesis => RoundBeforeGenesis::new_err(message),
+        DrandClientError::RoundNotYetAvailable => RoundNotYetAvailable::new_err(message),
+        DrandClientError::VerificationDisabled => VerificationDisabled::new_err(message),
+        DrandClientError::UnexpectedError => UnexpectedError::new_err(message),
+    }
+}
+
+fn beacon_dict<'py>(py: Python<'py>, beacon: &Beacon) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("round_number", beacon.round_number)?;
+    dict.set_item("randomness", hex::encode(&beacon.randomness))?;
+    dict.set_item("signature", hex::encode(&beacon.signature))?;
+    dict.set_item(
+        "previous_signature",
+        hex::encode(&beacon.previous_signature),
+    )?;
+    Ok(dict)
+}
+
+fn chain_info_dict<'py>(py: Python<'py>, info: &ChainInfo) -> PyResult<Bound<'py, PyDict>> {
+    let metadata = PyDict::new_bound(py);
+    metadata.set_item("beacon_id", &info.metadata.beacon_id)?;
+
+    let dict = PyDict::new_bound(py);
+    dict.set_item("scheme_id", info.scheme_id.name())?;
+    dict.set_item("public_key", hex::encode(&info.public_key))?;
+    dict.set_item("chain_hash", hex::encode(&info.chain_hash))?;
+    dict.set_item("group_hash", hex::encode(&info.group_hash))?;
+    dict.set_item("genesis_time", info.genesis_time)?;
+    dict.set_item("period_seconds", info.period_seconds)?;
+    dict.set_item("metadata", metadata)?;
+    Ok(dict)
+}
+
+fn decode(field: &str, hex: &str) -> PyResult<Vec<u8>> {
+    hex::decode(hex).map_err(|_| PyValueError::new_err(format!("{field} is not valid hex")))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chain_info::{ChainInfo, ChainInfoMetadata};
+    use crate::python::{
+        beacon_dict, chain_info_dict, to_py_err, verify_beacon, DrandError, FailedVerification,
+        RateLimited,
+    };
+    use crate::verify::{Beacon, SchemeID};
+    use crate::DrandClientError;
+    use pyo3::exceptions::PyValueError;
+    use pyo3::prelude::*;
+
+    const PUBLIC_KEY: &str = "83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a";
+    const SIGNATURE: &str = "b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39";
+    const RANDOMNESS: &str = "fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd";
+
+    #[cfg(any(feature = "energon", feature = "verify-slim"))]
+    #[test]
+    fn verify_beacon_accepts_and_rejects() {
+        let scheme = "bls-unchained-g1-rfc9380";
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            assert!(verify_beacon(scheme, PUBLIC_KEY, 1000, SIGNATURE, "", RANDOMNESS).is_ok());
+            assert!(
+                verify_beacon(scheme, PUBLIC_KEY, 1001, SIGNATURE, "", RANDOMNESS)
+                    .unwrap_err()
+                    .is_instance_of::<FailedVerification>(py)
+            );
+            assert!(
+                verify_beacon("bls-unknown", PUBLIC_KEY, 1000, SIGNATURE, "", RANDOMNESS)
+                    .unwrap_err()
+                    .is_instance_of::<PyValueError>(py)
+            );
+            assert!(verify_beacon(scheme, "zz", 1000, SIGNATURE, "", RANDOMNESS)
+                .unwrap_err()
+                .is_instance_of::<PyValueError>(py));
+        });
+    }
+
+    #[test]
+    fn client_errors_raise_matching_exceptions() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let err = to_py_err(DrandClientError::RateLimited);
+            assert!(err.is_instance_of::<RateLimited>(py));
+            assert!(err.is_instance_of::<DrandError>(py));
+            assert!(!err.is_instance_of::<FailedVerification>(py));
+        });
+    }
+
+    #[test]
+    fn beacons_and_chain_info_convert_to_dicts() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let beacon = Beacon {
+                round_number: 1000,
+                randomness: hex::decode(RANDOMNESS).unwrap(),
+                signature: hex::decode(SIGNATURE).unwrap(),
+                previous_signature: Vec::new(),
+            };
+            let dict = beacon_dict(py, &beacon).unwrap();
+            let round: u64 = dict
+                .get_item("round_number")
+                .unwrap()
+                .unwrap()
+                .extract()
+                .unwrap();
+            let signature: String = dict
+                .get_item("signature")
+                .unwrap()
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(round, 1000);
+            assert_eq!(signature, SIGNATURE);
+
+            let info = ChainInfo {
+                scheme_id: SchemeID::UnchainedOnG1RFC9380,
+                public_key: hex::decode(PUBLIC_KEY).unwrap(),
+                chain_hash: Vec::new(),
+                group_hash: Vec::new(),
+                genesis_time: 1692803367,
+                period_seconds: 3,
+                metadata: ChainInfoMetadata {
+                    beacon_id: "quicknet".to_string(),
+                },
+            };
+            let dict = chain_info_dict(py, &info).unwrap();
+            let scheme_id: String = dict
+                .get_item("scheme_id")
+                .unwrap()
+                .unwrap()
+                .extract()
+                .unwrap();
+            let period: usize = dict
+                .get_item("period_seconds")
+                .unwrap()
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(scheme_id, "bls-unchained-g1-rfc9380");
+            assert_eq!(period, 3);
+        });
+    }
+}
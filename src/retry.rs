@@ -0,0 +1,98 @@
+//! # retry
+//!
+//! retrying of failed fetches with jittered exponential backoff, for any transport
+//!
+
+use crate::TransportError;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::thread;
+use std::time::Duration;
+
+/// how many times, and how quickly, failed fetches are retried. Only
+/// `TransportError::Unexpected` is retried: a `NotFound` means the relay answered, so
+/// asking again won't help.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryConfig {
+    /// the total number of attempts, including the first one
+    pub max_attempts: usize,
+    /// the delay before the first retry, doubled for each subsequent retry. Each actual
+    /// delay is drawn at random from between half and all of it.
+    pub base_delay: Duration,
+    /// the upper bound for the delay between two attempts
+    pub max_delay: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+/// call `fetch` until it succeeds, fails with anything other than
+/// `TransportError::Unexpected`, or `config.max_attempts` is reached
+pub fn retry<F>(config: &RetryConfig, fetch: F) -> Result<String, TransportError>
+where
+    F: FnMut() -> Result<String, TransportError>,
+{
+    retry_if(
+        config,
+        |e| matches!(e, TransportError::Unexpected { .. }),
+        fetch,
+    )
+}
+
+// call `attempt` until it succeeds, fails with an error `should_retry` rejects, or
+// `config.max_attempts` is reached
+pub(crate) fn retry_if<T, E, F>(
+    config: &RetryConfig,
+    should_retry: impl Fn(&E) -> bool,
+    mut attempt: F,
+) -> Result<T, E>
+where
+    F: FnMut() -> Result<T, E>,
+{
+    let mut delay = config.base_delay;
+    let mut attempts = 1;
+    loop {
+        match attempt() {
+            Err(e) if should_retry(&e) && attempts < config.max_attempts => {
+                thread::sleep(jittered(delay.min(config.max_delay)));
+                delay = delay.saturating_mul(2);
+                attempts += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+// somewhere between half and all of `delay`, so that clients which failed together don't
+// all retry at the same instant. A fresh `RandomState` is randomly seeded, which is plenty
+// for spreading retries.
+pub(crate) fn jittered(delay: Duration) -> Duration {
+    let random = RandomState::new().build_hasher().finish();
+    let fraction = (random >> 11) as f64 / (1u64 << 53) as f64;
+    delay / 2 + delay.mul_f64(fraction) / 2
+}
+
+#[cfg(test)]
+mod test {
+    use crate::retry::jittered;
+    use std::time::Duration;
+
+    #[test]
+    fn jitter_stays_within_half_of_the_delay() {
+        let delay = Duration::from_millis(200);
+        let delays: Vec<_> = (0..100).map(|_| jittered(delay)).collect();
+
+        assert!(delays
+            .iter()
+            .all(|jittered| (delay / 2..=delay).contains(jittered)));
+        assert!(delays.iter().any(|jittered| *jittered != delays[0]));
+        assert_eq!(jittered(Duration::ZERO), Duration::ZERO);
+    }
+}
@@ -0,0 +1,98 @@
+//! # rng
+//!
+//! a `rand_core` generator seeded from a beacon, for shuffles, lotteries and simulations
+//! that should be reproducible by anyone holding the same beacon.
+//!
+//! The output is exactly as public as the beacon it is seeded from, so `DrandRng` is
+//! deliberately not a `CryptoRng`: never use it for keys or other secrets.
+//!
+
+use crate::verify::Beacon;
+use crate::DrandClientError;
+use rand_chacha::ChaCha20Rng;
+use rand_core::{RngCore, SeedableRng};
+
+/// ChaCha20 seeded with a beacon's 32 bytes of randomness
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrandRng(ChaCha20Rng);
+
+impl DrandRng {
+    /// a generator seeded with `beacon.randomness`, failing with `InvalidBeacon` if it isn't
+    /// 32 bytes long. The beacon should already be verified.
+    pub fn from_beacon(beacon: &Beacon) -> Result<Self, DrandClientError> {
+        let seed = beacon
+            .randomness_array()
+            .map_err(|_| DrandClientError::InvalidBeacon)?;
+        Ok(DrandRng::from_seed(seed))
+    }
+}
+
+impl SeedableRng for DrandRng {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        DrandRng(ChaCha20Rng::from_seed(seed))
+    }
+}
+
+impl RngCore for DrandRng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.0.try_fill_bytes(dest)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::rng::DrandRng;
+    use crate::verify::Beacon;
+    use crate::DrandClientError;
+    use rand_core::{RngCore, SeedableRng};
+
+    #[test]
+    fn the_same_beacon_yields_the_same_stream() -> Result<(), DrandClientError> {
+        let beacon = beacon();
+        let mut rng = DrandRng::from_beacon(&beacon)?;
+        let seed = beacon.randomness_array().unwrap();
+
+        let first = rng.next_u64();
+        assert_eq!(DrandRng::from_beacon(&beacon)?.next_u64(), first);
+        assert_eq!(DrandRng::from_seed(seed).next_u64(), first);
+        assert_ne!(rng.next_u64(), first);
+        Ok(())
+    }
+
+    #[test]
+    fn randomness_must_be_32_bytes() {
+        let mut beacon = beacon();
+        beacon.randomness.pop();
+
+        assert_eq!(
+            DrandRng::from_beacon(&beacon),
+            Err(DrandClientError::InvalidBeacon)
+        );
+    }
+
+    fn beacon() -> Beacon {
+        Beacon {
+            round_number: 2,
+            randomness: hex::decode(
+                "e8fee7dac6eb2b89df97d631cfccedbada7d5d05495bb546eef462e4145fdf8f",
+            )
+            .unwrap(),
+            signature: Vec::new(),
+            previous_signature: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! # round_number
+//!
+//! a round number that can't be 0, so a round can't be mixed up with a timestamp or a count
+//! by accident
+//!
+
+use crate::DrandClientError;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::num::NonZeroU64;
+
+/// a drand round, starting at 1 at genesis. In JSON it is a plain number, and 0 is rejected.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "u64", into = "u64")
+)]
+pub struct RoundNumber(NonZeroU64);
+
+impl RoundNumber {
+    /// the first round of every chain
+    pub const FIRST: RoundNumber = RoundNumber(NonZeroU64::MIN);
+
+    /// `round`, or `InvalidRound` if it is 0
+    pub fn new(round: u64) -> Result<Self, DrandClientError> {
+        NonZeroU64::new(round)
+            .map(RoundNumber)
+            .ok_or(DrandClientError::InvalidRound)
+    }
+
+    pub const fn get(self) -> u64 {
+        self.0.get()
+    }
+
+    /// the round after this one, or `None` past `u64::MAX`
+    pub fn next(self) -> Option<Self> {
+        self.0.checked_add(1).map(RoundNumber)
+    }
+
+    /// the round before this one, or `None` for round 1
+    pub fn prev(self) -> Option<Self> {
+        RoundNumber::new(self.get() - 1).ok()
+    }
+}
+
+impl TryFrom<u64> for RoundNumber {
+    type Error = DrandClientError;
+
+    fn try_from(round: u64) -> Result<Self, Self::Error> {
+        RoundNumber::new(round)
+    }
+}
+
+impl From<RoundNumber> for u64 {
+    fn from(round: RoundNumber) -> Self {
+        round.get()
+    }
+}
+
+impl fmt::Display for RoundNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::round_number::RoundNumber;
+    use crate::DrandClientError;
+
+    #[test]
+    fn round_zero_is_invalid() {
+        assert_eq!(RoundNumber::new(0), Err(DrandClientError::InvalidRound));
+        assert_eq!(RoundNumber::try_from(7).map(u64::from), Ok(7));
+        assert_eq!(RoundNumber::new(1), Ok(RoundNumber::FIRST));
+        assert_eq!(RoundNumber::new(42).unwrap().to_string(), "42");
+    }
+
+    #[test]
+    fn neighbouring_rounds_stay_in_range() {
+        let round = RoundNumber::new(2).unwrap();
+
+        assert_eq!(round.next().map(RoundNumber::get), Some(3));
+        assert_eq!(round.prev(), Some(RoundNumber::FIRST));
+        assert_eq!(RoundNumber::FIRST.prev(), None);
+        assert_eq!(RoundNumber::new(u64::MAX).unwrap().next(), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn rounds_are_plain_numbers_in_json() {
+        let round: RoundNumber = serde_json::from_str("5").unwrap();
+
+        assert_eq!(round.get(), 5);
+        assert_eq!(serde_json::to_string(&round).unwrap(), "5");
+        assert!(serde_json::from_str::<RoundNumber>("0").is_err());
+    }
+}
@@ -0,0 +1,228 @@
+//! # rounds
+//!
+//! a lazy iterator over a range of rounds, fetching and verifying each beacon only when it
+//! is asked for, so long histories can be streamed in constant memory
+//!
+
+use crate::round_number::RoundNumber;
+use crate::verify::VerifiedBeacon;
+use crate::{DrandClient, DrandClientError, Transport};
+use std::ops::RangeInclusive;
+
+/// the beacons from a starting round up to the latest round, in either direction. The
+/// latest beacon is fetched on the first call to `next` or `next_back` and fixes the end of
+/// the range. Each round is yielded once: a round that fails to fetch or verify is yielded
+/// as an error and iteration carries on with the next one. If the latest beacon can't be
+/// fetched, that error is yielded and the iterator ends.
+pub struct Rounds<'c, T: Transport + Send> {
+    client: &'c DrandClient<T>,
+    front: u64,
+    back: Option<u64>,
+    latest: Option<VerifiedBeacon>,
+    done: bool,
+}
+
+impl<T: Transport + Send> DrandClient<T> {
+    /// iterate over the beacons from round `from` up to the latest one
+    pub fn rounds(&self, from: u64) -> Rounds<'_, T> {
+        Rounds {
+            client: self,
+            front: from,
+            back: None,
+            latest: None,
+            done: false,
+        }
+    }
+
+    /// fetch and verify the beacons of rounds `start..=end` lazily, in order, one request
+    /// per round as the iterator is advanced. A failed round is yielded as an error and the
+    /// rest are still fetched. A `start` of 0 yields only `InvalidRound`, and a `start` past
+    /// `end` nothing at all.
+    pub fn fetch_range(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> impl Iterator<Item = Result<VerifiedBeacon, DrandClientError>> + '_ {
+        let invalid = (start == 0).then_some(Err(DrandClientError::InvalidRound));
+        let rounds = if start == 0 { 1..=0 } else { start..=end };
+        invalid
+            .into_iter()
+            .chain(rounds.map(|round| self.randomness(round)))
+    }
+
+    /// like `fetch_range`, for rounds that are known not to be 0
+    pub fn fetch_rounds(
+        &self,
+        rounds: RangeInclusive<RoundNumber>,
+    ) -> impl Iterator<Item = Result<VerifiedBeacon, DrandClientError>> + '_ {
+        self.fetch_range(rounds.start().get(), rounds.end().get())
+    }
+}
+
+impl<T: Transport + Send> Rounds<'_, T> {
+    // the next round from the front or back, or `None` if the range is exhausted
+    fn take_round(&mut self, from_back: bool) -> Option<Result<u64, DrandClientError>> {
+        if self.done {
+            return None;
+        }
+        let back = match self.back {
+            Some(back) => back,
+            None => match self.client.latest_randomness() {
+                Ok(latest) => {
+                    let back = latest.round_number;
+                    self.back = Some(back);
+                    self.latest = Some(latest);
+                    back
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            },
+        };
+        if self.front > back {
+            self.done = true;
+            return None;
+        }
+
+        if from_back {
+            self.back = Some(back - 1);
+            Some(Ok(back))
+        } else {
+            self.front += 1;
+            Some(Ok(self.front - 1))
+        }
+    }
+
+    // the cached latest beacon if it is `round`, so it isn't fetched twice
+    fn fetch(&mut self, round: u64) -> Result<VerifiedBeacon, DrandClientError> {
+        match self.latest.take() {
+            Some(latest) if latest.round_number == round => Ok(latest),
+            latest => {
+                self.latest = latest;
+                self.client.randomness(round)
+            }
+        }
+    }
+}
+
+impl<T: Transport + Send> Iterator for Rounds<'_, T> {
+    type Item = Result<VerifiedBeacon, DrandClientError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.take_round(false)? {
+            Ok(round) => Some(self.fetch(round)),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// walks back from the latest round towards the starting one
+impl<T: Transport + Send> DoubleEndedIterator for Rounds<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self.take_round(true)? {
+            Ok(round) => Some(self.fetch(round)),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::testkit::{TestChain, TestTransport};
+    use crate::verify::SchemeID::PedersenBlsUnchained;
+    use crate::{DrandClient, DrandClientError, RoundNumber};
+
+    #[test]
+    fn rounds_stop_at_the_latest_one() {
+        let (chain, client) = client(chain().transport(6));
+
+        let beacons: Vec<_> = client.rounds(3).collect();
+        assert_eq!(
+            beacons,
+            [3, 4, 5, 6].map(|round| Ok(chain.verified_round(round)))
+        );
+        assert_eq!(client.rounds(7).count(), 0);
+    }
+
+    #[test]
+    fn rounds_can_be_walked_backwards_and_from_both_ends() {
+        let (chain, client) = client(chain().transport(6));
+
+        let rounds: Vec<u64> = client
+            .rounds(2)
+            .rev()
+            .map(|beacon| beacon.unwrap().round_number)
+            .collect();
+        assert_eq!(rounds, [6, 5, 4, 3, 2]);
+
+        let mut both_ends = client.rounds(4);
+        assert_eq!(both_ends.next_back(), Some(Ok(chain.verified_round(6))));
+        assert_eq!(both_ends.next(), Some(Ok(chain.verified_round(4))));
+        assert_eq!(both_ends.next(), Some(Ok(chain.verified_round(5))));
+        assert_eq!(both_ends.next_back(), None);
+        assert_eq!(both_ends.next(), None);
+    }
+
+    #[test]
+    fn failed_rounds_are_yielded_and_skipped() {
+        let (chain, client) = client(chain().transport(4).serve_round(2, "not json"));
+
+        let beacons: Vec<_> = client.rounds(1).collect();
+        assert_eq!(
+            beacons,
+            [
+                Ok(chain.verified_round(1)),
+                Err(DrandClientError::InvalidBeacon),
+                Ok(chain.verified_round(3)),
+                Ok(chain.verified_round(4))
+            ]
+        );
+    }
+
+    #[test]
+    fn a_failed_latest_fetch_ends_the_iteration() {
+        // round 4 is years old, so the latest beacon is rejected as stale
+        let chain = chain();
+        let client = DrandClient::new(
+            chain.transport(4),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+
+        let mut rounds = client.rounds(1);
+        assert_eq!(rounds.next(), Some(Err(DrandClientError::InvalidBeacon)));
+        assert_eq!(rounds.next(), None);
+    }
+
+    #[test]
+    fn ranges_are_fetched_lazily() {
+        let (chain, client) = client(chain().transport(6));
+
+        let mut beacons = client.fetch_range(5, 7);
+        assert_eq!(beacons.next(), Some(Ok(chain.verified_round(5))));
+        assert_eq!(beacons.next(), Some(Ok(chain.verified_round(6))));
+        assert_eq!(beacons.next(), Some(Err(DrandClientError::NotResponding)));
+        assert_eq!(beacons.next(), None);
+
+        let rounds = RoundNumber::new(5).unwrap()..=RoundNumber::new(6).unwrap();
+        let beacons: Vec<_> = client.fetch_rounds(rounds).collect();
+        assert_eq!(beacons, [5, 6].map(|round| Ok(chain.verified_round(round))));
+
+        let beacons: Vec<_> = client.fetch_range(0, 3).collect();
+        assert_eq!(beacons, [Err(DrandClientError::InvalidRound)]);
+        assert_eq!(client.fetch_range(4, 3).count(), 0);
+    }
+
+    fn chain() -> TestChain {
+        TestChain::new(PedersenBlsUnchained, 30, 1595431050)
+    }
+
+    // a client over `transport`, accepting its latest beacon however old it is
+    fn client(transport: TestTransport) -> (TestChain, DrandClient<TestTransport>) {
+        let chain = chain();
+        let mut client = DrandClient::new(transport, "api.drand.sh", chain.chain_info().clone());
+        client.latest_tolerance = u64::MAX;
+        (chain, client)
+    }
+}
@@ -0,0 +1,118 @@
+//! # testing
+//!
+//! helpers for testing code built on the client without depending on the wall clock
+//!
+
+use crate::chain_info::ChainInfo;
+use crate::clock::Clock;
+use crate::{time_for_round, DrandClientError};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// a clock that only moves when told to. Sleeping advances it by the time slept, so code
+/// that waits for a round returns immediately. Share it in an `Arc` to keep moving it after
+/// handing it to `DrandClient::with_clock`.
+#[derive(Debug)]
+pub struct FixedClock {
+    now: Mutex<SystemTime>,
+}
+
+impl FixedClock {
+    pub fn new(now: SystemTime) -> Self {
+        FixedClock {
+            now: Mutex::new(now),
+        }
+    }
+
+    /// a clock stopped at the scheduled time of `round` on the chain of `chain_info`
+    pub fn at_round(chain_info: &ChainInfo, round: u64) -> Result<Self, DrandClientError> {
+        Ok(FixedClock::new(time_for_round(chain_info, round)?))
+    }
+
+    pub fn set(&self, now: SystemTime) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> SystemTime {
+        *self.now.lock().unwrap()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration)
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test {
+    use crate::testing::FixedClock;
+    use crate::testkit::TestChain;
+    use crate::verify::SchemeID::PedersenBlsUnchained;
+    use crate::{DrandClient, DrandClientError};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn the_client_reads_time_from_its_clock() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let clock = Arc::new(FixedClock::at_round(chain.chain_info(), 10)?);
+        let client = DrandClient::with_chain_info(
+            chain.transport(9),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        )
+        .with_clock(clock.clone());
+
+        assert_eq!(client.current_round()?, 10);
+        assert_eq!(client.latest_randomness()?, chain.sign_round(9));
+
+        clock.advance(client.period() * 2);
+        assert_eq!(client.current_round()?, 12);
+        assert_eq!(
+            client.latest_randomness(),
+            Err(DrandClientError::InvalidBeacon)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn freshness_is_judged_by_the_clock() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let clock = Arc::new(FixedClock::at_round(chain.chain_info(), 10)?);
+        let client = DrandClient::with_chain_info(
+            chain.transport(10),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        )
+        .with_clock(clock.clone());
+        let two_periods = client.period() * 2;
+
+        assert!(client.is_fresh(&chain.sign_round(8), two_periods));
+        assert!(!client.is_fresh(&chain.sign_round(7), two_periods));
+        assert!(client.is_fresh(&chain.sign_round(11), Duration::ZERO));
+        clock.advance(Duration::from_secs(1));
+        assert!(!client.is_fresh(&chain.sign_round(10), Duration::ZERO));
+        Ok(())
+    }
+
+    #[test]
+    fn waiting_for_a_round_sleeps_on_the_clock() -> Result<(), DrandClientError> {
+        let chain = TestChain::new(PedersenBlsUnchained, 30, 1595431050);
+        let clock = Arc::new(FixedClock::at_round(chain.chain_info(), 3)?);
+        let client = DrandClient::with_chain_info(
+            chain.transport(5),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        )
+        .with_clock(clock.clone());
+
+        assert_eq!(client.wait_for_round(5)?, chain.sign_round(5));
+        assert_eq!(client.current_round()?, 5);
+        Ok(())
+    }
+}
@@ -0,0 +1,336 @@
+//! # testkit
+//!
+//! a throwaway drand chain for tests: a deterministic BLS keypair, a matching `ChainInfo` and
+//! beacons that genuinely pass `verify_beacon`. Only the BLS12-381 schemes are supported.
+//!
+
+use crate::chain_info::{ChainInfo, ChainInfoMetadata};
+use crate::chains::MAINNET;
+use crate::verify::{generate_unchained_beacon, secret_key_scalar, Beacon, SchemeID};
+use crate::{Transport, TransportError};
+use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+use bls12_381::{G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+const G1_DST: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+const G2_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+
+/// the League of Entropy mainnet's `/info`, as the relays serve it
+pub const MAINNET_INFO_JSON: &str = crate::chains::MAINNET_INFO_JSON;
+
+/// mainnet's round 2, as the relays serve it
+pub const MAINNET_ROUND_TWO_JSON: &str = r#"{"round":2,"randomness":"e8fee7dac6eb2b89df97d631cfccedbada7d5d05495bb546eef462e4145fdf8f","signature":"aa18facd2d51b616511d542de6f9af8a3b920121401dad1434ed1db4a565f10e04fad8d9b2b4e3e0094364374caafe9b10478bf75650124831509c638b5a36a7a232ec70289f8751a2adb47fc32eb70b57dc81c39d48cbcac9fec46cdfc31663","previous_signature":"8d61d9100567de44682506aea1a7a6fa6e5491cd27a0a0ed349ef6910ac5ac20ff7bc3e09d7c046566c9f7f3c6f3b10104990e7cb424998203d8f7de586fb7fa5f60045417a432684f85093b06ca91c769f0e7ca19268375e659c2a2352b4655"}"#;
+
+/// the League of Entropy mainnet's chain info, from `chains::MAINNET`
+pub fn mainnet_info() -> ChainInfo {
+    MAINNET.to_chain_info()
+}
+
+/// mainnet's round 2, a real chained beacon that verifies against `mainnet_info()`
+pub fn mainnet_round_two() -> Beacon {
+    Beacon {
+        round_number: 2,
+        randomness: hex::decode("e8fee7dac6eb2b89df97d631cfccedbada7d5d05495bb546eef462e4145fdf8f").unwrap(),
+        signature: hex::decode("aa18facd2d51b616511d542de6f9af8a3b920121401dad1434ed1db4a565f10e04fad8d9b2b4e3e0094364374caafe9b10478bf75650124831509c638b5a36a7a232ec70289f8751a2adb47fc32eb70b57dc81c39d48cbcac9fec46cdfc31663").unwrap(),
+        previous_signature: hex::decode("8d61d9100567de44682506aea1a7a6fa6e5491cd27a0a0ed349ef6910ac5ac20ff7bc3e09d7c046566c9f7f3c6f3b10104990e7cb424998203d8f7de586fb7fa5f60045417a432684f85093b06ca91c769f0e7ca19268375e659c2a2352b4655").unwrap(),
+    }
+}
+
+/// a simulated chain that can sign any round
+pub struct TestChain {
+    chain_info: ChainInfo,
+    secret_key: Vec<u8>,
+    // signatures of the chained scheme, where `signatures[n]` is the signature of round `n`
+    // and `signatures[0]` is the genesis seed
+    signatures: Mutex<Vec<Vec<u8>>>,
+}
+
+impl TestChain {
+    /// create a chain with a keypair derived from a fixed seed
+    ///
+    /// # Panics
+    /// if `scheme_id` is `Bn254UnchainedOnG1`, which the testkit cannot sign for
+    pub fn new(scheme_id: SchemeID, period_seconds: usize, genesis_time: u64) -> Self {
+        Self::with_seed(
+            scheme_id,
+            period_seconds,
+            genesis_time,
+            b"drand-client-rs testkit",
+        )
+    }
+
+    /// create a chain with a keypair derived from `seed`, so different seeds give
+    /// independent chains
+    ///
+    /// # Panics
+    /// if `scheme_id` is `Bn254UnchainedOnG1`, which the testkit cannot sign for
+    pub fn with_seed(
+        scheme_id: SchemeID,
+        period_seconds: usize,
+        genesis_time: u64,
+        seed: &[u8],
+    ) -> Self {
+        let mut wide = [0u8; 64];
+        wide[..32].copy_from_slice(&Sha256::digest([b"secret key 0".as_slice(), seed].concat()));
+        wide[32..].copy_from_slice(&Sha256::digest([b"secret key 1".as_slice(), seed].concat()));
+        let scalar = Scalar::from_bytes_wide(&wide);
+        let mut secret_key = scalar.to_bytes().to_vec();
+        secret_key.reverse();
+
+        let public_key = match scheme_id {
+            SchemeID::PedersenBlsChained | SchemeID::PedersenBlsUnchained => {
+                G1Affine::from(G1Projective::generator() * scalar)
+                    .to_compressed()
+                    .to_vec()
+            }
+            SchemeID::UnchainedOnG1RFC9380 => G2Affine::from(G2Projective::generator() * scalar)
+                .to_compressed()
+                .to_vec(),
+            SchemeID::Bn254UnchainedOnG1 => panic!("the testkit cannot sign bn254 beacons"),
+        };
+        let group_hash = Sha256::digest([b"group hash".as_slice(), seed].concat()).to_vec();
+
+        let mut chain_info = ChainInfo {
+            scheme_id,
+            public_key,
+            chain_hash: Vec::new(),
+            group_hash: group_hash.clone(),
+            genesis_time,
+            period_seconds,
+            metadata: ChainInfoMetadata {
+                beacon_id: "testkit".to_string(),
+            },
+        };
+        chain_info.chain_hash = chain_info.compute_chain_hash();
+
+        TestChain {
+            chain_info,
+            secret_key,
+            signatures: Mutex::new(vec![group_hash]),
+        }
+    }
+
+    /// the chain info, including a correctly computed chain hash
+    pub fn chain_info(&self) -> &ChainInfo {
+        &self.chain_info
+    }
+
+    /// the 32-byte big-endian secret key of the chain
+    pub fn secret_key(&self) -> &[u8] {
+        &self.secret_key
+    }
+
+    /// sign a round. For the chained scheme the previous signature is chained back to the
+    /// genesis seed (the group hash), as on a real chain.
+    ///
+    /// # Panics
+    /// if `round` is 0
+    pub fn sign_round(&self, round: u64) -> Beacon {
+        assert!(round > 0, "round 0 cannot be signed");
+        match self.chain_info.scheme_id {
+            SchemeID::PedersenBlsChained => self.sign_chained(round),
+            SchemeID::PedersenBlsUnchained => generate_unchained_beacon(&self.secret_key, round)
+                .expect("testkit secret keys are always valid"),
+            SchemeID::UnchainedOnG1RFC9380 => {
+                let message = Sha256::digest(round.to_be_bytes());
+                let point =
+                    <G1Projective as HashToCurve<ExpandMsgXmd<sha2_09::Sha256>>>::hash_to_curve(
+                        message, G1_DST,
+                    );
+                let signature = G1Affine::from(point * self.scalar())
+                    .to_compressed()
+                    .to_vec();
+                beacon(round, signature, Vec::new())
+            }
+            SchemeID::Bn254UnchainedOnG1 => unreachable!("bn254 chains cannot be constructed"),
+        }
+    }
+
+    /// `sign_round` as the `VerifiedBeacon` the clients return for it
+    #[cfg(test)]
+    pub(crate) fn verified_round(&self, round: u64) -> crate::verify::VerifiedBeacon {
+        crate::verify::VerifiedBeacon::assume_verified(self.sign_round(round))
+    }
+
+    /// a transport serving this chain's `/info`, rounds `1..=latest_round` and
+    /// `/public/latest` (initially `latest_round`) under any base URL
+    pub fn transport(&self, latest_round: u64) -> TestTransport {
+        let mut responses = HashMap::new();
+        responses.insert("info".to_string(), chain_info_json(&self.chain_info));
+        for round in 1..=latest_round {
+            responses.insert(round.to_string(), beacon_json(&self.sign_round(round)));
+        }
+        if latest_round > 0 {
+            responses.insert(
+                "latest".to_string(),
+                beacon_json(&self.sign_round(latest_round)),
+            );
+        }
+        TestTransport { responses }
+    }
+
+    fn scalar(&self) -> Scalar {
+        secret_key_scalar(&self.secret_key).expect("testkit secret keys are always valid")
+    }
+
+    fn sign_chained(&self, round: u64) -> Beacon {
+        let mut signatures = self.signatures.lock().unwrap();
+        while signatures.len() as u64 <= round {
+            let next_round = signatures.len() as u64;
+            let previous = signatures.last().unwrap();
+            let message =
+                Sha256::digest([previous.as_slice(), next_round.to_be_bytes().as_slice()].concat());
+            let point = <G2Projective as HashToCurve<ExpandMsgXmd<sha2_09::Sha256>>>::hash_to_curve(
+                message, G2_DST,
+            );
+            let signature = G2Affine::from(point * self.scalar())
+                .to_compressed()
+                .to_vec();
+            signatures.push(signature);
+        }
+
+        beacon(
+            round,
+            signatures[round as usize].clone(),
+            signatures[round as usize - 1].clone(),
+        )
+    }
+}
+
+/// a transport answering requests from a fixed set of responses generated by a `TestChain`.
+/// Unknown rounds return `TransportError::NotFound`, like a relay that hasn't seen them yet.
+#[derive(Clone)]
+pub struct TestTransport {
+    responses: HashMap<String, String>,
+}
+
+impl TestTransport {
+    /// make `/public/latest` return `beacon` instead, e.g. to simulate a stale cache
+    pub fn serve_latest(mut self, beacon: &Beacon) -> Self {
+        self.responses
+            .insert("latest".to_string(), beacon_json(beacon));
+        self
+    }
+
+    /// serve a custom body for a round, e.g. to simulate a corrupted response
+    pub fn serve_round(mut self, round: u64, body: &str) -> Self {
+        self.responses.insert(round.to_string(), body.to_string());
+        self
+    }
+}
+
+impl Transport for TestTransport {
+    fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        let key = match url.rsplit_once("/public/") {
+            Some((_, tag)) => tag,
+            None if url.ends_with("/info") => "info",
+            None => return Err(TransportError::NotFound),
+        };
+        self.responses
+            .get(key)
+            .cloned()
+            .ok_or(TransportError::NotFound)
+    }
+}
+
+fn beacon(round: u64, signature: Vec<u8>, previous_signature: Vec<u8>) -> Beacon {
+    Beacon {
+        round_number: round,
+        randomness: Sha256::digest(&signature).to_vec(),
+        signature,
+        previous_signature,
+    }
+}
+
+fn beacon_json(beacon: &Beacon) -> String {
+    format!(
+        "{{\"round\":{},\"randomness\":\"{}\",\"signature\":\"{}\",\"previous_signature\":\"{}\"}}",
+        beacon.round_number,
+        hex::encode(&beacon.randomness),
+        hex::encode(&beacon.signature),
+        hex::encode(&beacon.previous_signature),
+    )
+}
+
+fn chain_info_json(chain_info: &ChainInfo) -> String {
+    format!(
+        "{{\"public_key\":\"{}\",\"period\":{},\"genesis_time\":{},\"hash\":\"{}\",\"groupHash\":\"{}\",\"schemeID\":\"{}\",\"metadata\":{{\"beaconID\":\"{}\"}}}}",
+        hex::encode(&chain_info.public_key),
+        chain_info.period_seconds,
+        chain_info.genesis_time,
+        hex::encode(&chain_info.chain_hash),
+        hex::encode(&chain_info.group_hash),
+        chain_info.scheme_id,
+        chain_info.metadata.beacon_id,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::testkit::TestChain;
+    use crate::verify::{verify_beacon, SchemeID, VerifiedBeacon};
+    #[cfg(feature = "serde")]
+    use crate::{chain_info::ChainInfo, Transport};
+
+    #[test]
+    fn signed_rounds_verify_for_every_supported_scheme() {
+        for scheme_id in [
+            SchemeID::PedersenBlsChained,
+            SchemeID::PedersenBlsUnchained,
+            SchemeID::UnchainedOnG1RFC9380,
+        ] {
+            let chain = TestChain::new(scheme_id.clone(), 3, 1_700_000_000);
+            for round in 1..=3 {
+                let beacon = chain.sign_round(round);
+                assert_eq!(
+                    verify_beacon(&scheme_id, &chain.chain_info().public_key, beacon.clone())
+                        .map(VerifiedBeacon::into_inner),
+                    Ok(beacon.clone()),
+                    "{scheme_id:?} round {round}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn chained_rounds_link_to_previous_signature() {
+        let chain = TestChain::new(SchemeID::PedersenBlsChained, 30, 1_700_000_000);
+        let first = chain.sign_round(1);
+        let second = chain.sign_round(2);
+
+        assert_eq!(first.previous_signature, chain.chain_info().group_hash);
+        assert_eq!(second.previous_signature, first.signature);
+    }
+
+    #[test]
+    fn chain_hash_is_computed() {
+        let chain = TestChain::new(SchemeID::PedersenBlsUnchained, 30, 1_700_000_000);
+        let info = chain.chain_info();
+
+        assert_eq!(info.chain_hash, info.compute_chain_hash());
+    }
+
+    #[test]
+    fn different_seeds_give_different_chains() {
+        let one = TestChain::with_seed(SchemeID::PedersenBlsUnchained, 30, 0, b"one");
+        let two = TestChain::with_seed(SchemeID::PedersenBlsUnchained, 30, 0, b"two");
+
+        assert_ne!(one.chain_info().public_key, two.chain_info().public_key);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn transport_serves_info_and_rounds() {
+        let chain = TestChain::new(SchemeID::UnchainedOnG1RFC9380, 3, 1_700_000_000);
+        let transport = chain.transport(2);
+
+        let info: ChainInfo =
+            serde_json::from_str(&transport.fetch("http://relay/info").unwrap()).unwrap();
+        assert_eq!(&info, chain.chain_info());
+        assert!(transport.fetch("http://relay/public/2").is_ok());
+        assert!(transport.fetch("http://relay/public/latest").is_ok());
+        assert!(transport.fetch("http://relay/public/3").is_err());
+    }
+}
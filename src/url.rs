@@ -0,0 +1,56 @@
+//! # url
+//!
+//! construction of the relay URLs the client requests, for relays that don't use the
+//! standard drand HTTP layout
+//!
+
+/// maps the endpoints the client needs onto full URLs for a given `base_url`
+pub trait UrlStrategy {
+    /// the URL serving the chain info
+    fn info_url(&self, base_url: &str) -> String;
+    /// the URL serving the latest beacon
+    fn latest_url(&self, base_url: &str) -> String;
+    /// the URL serving the beacon for `round_number`
+    fn round_url(&self, base_url: &str, round_number: u64) -> String;
+}
+
+/// the standard drand HTTP API: `{base_url}/info`, `{base_url}/public/latest` and
+/// `{base_url}/public/{round}`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultUrlStrategy;
+
+impl UrlStrategy for DefaultUrlStrategy {
+    fn info_url(&self, base_url: &str) -> String {
+        format!("{base_url}/info")
+    }
+
+    fn latest_url(&self, base_url: &str) -> String {
+        format!("{base_url}/public/latest")
+    }
+
+    fn round_url(&self, base_url: &str, round_number: u64) -> String {
+        format!("{base_url}/public/{round_number}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::url::{DefaultUrlStrategy, UrlStrategy};
+
+    #[test]
+    fn default_strategy_uses_drand_layout() {
+        let base_url = "https://api.drand.sh";
+        assert_eq!(
+            DefaultUrlStrategy.info_url(base_url),
+            "https://api.drand.sh/info"
+        );
+        assert_eq!(
+            DefaultUrlStrategy.latest_url(base_url),
+            "https://api.drand.sh/public/latest"
+        );
+        assert_eq!(
+            DefaultUrlStrategy.round_url(base_url, 42),
+            "https://api.drand.sh/public/42"
+        );
+    }
+}
@@ -3,27 +3,140 @@
 //! this module contains some of the cryptographic internals that some users might wish to use
 //! manually without the client
 //!
+use crate::chain_info::ChainInfo;
+use crate::{time_for_round, DrandClientError};
+#[cfg(feature = "energon")]
 use energon::drand::schemes::{
     BN254UnchainedOnG1Scheme, DefaultScheme, SigsOnG1Scheme, UnchainedScheme,
 };
+#[cfg(feature = "energon")]
 use energon::drand::traits::{BeaconDigest, DrandScheme as Scheme};
+#[cfg(feature = "energon")]
 use energon::traits::{Affine, Group};
-use serde::{Deserialize, Deserializer};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
 use thiserror::Error;
 
-#[derive(Deserialize, Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Beacon {
-    #[serde(alias = "round")]
+    #[cfg_attr(feature = "serde", serde(alias = "round"))]
     pub round_number: u64,
-    #[serde(with = "hex")]
+    #[cfg_attr(feature = "serde", serde(with = "hex"))]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "String", regex(pattern = "^([0-9a-fA-F]{2})*$"))
+    )]
     pub randomness: Vec<u8>,
-    #[serde(with = "hex")]
+    #[cfg_attr(feature = "serde", serde(with = "hex"))]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "String", regex(pattern = "^([0-9a-fA-F]{2})*$"))
+    )]
     pub signature: Vec<u8>,
-    #[serde(default, with = "hex")]
+    #[cfg_attr(feature = "serde", serde(default, with = "hex"))]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "String", regex(pattern = "^([0-9a-fA-F]{2})*$"))
+    )]
     pub previous_signature: Vec<u8>,
 }
 
+/// the case of the hex digits `Beacon::to_json_with_options` emits. Parsing always accepts
+/// both.
+#[cfg(feature = "serde")]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum HexCase {
+    Upper,
+    #[default]
+    Lower,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct BeaconSerializeOptions {
+    pub hex_case: HexCase,
+}
+
+// `Beacon`'s serialized form with the hex already encoded
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct HexBeacon {
+    round_number: u64,
+    randomness: String,
+    signature: String,
+    previous_signature: String,
+}
+
+impl Beacon {
+    /// serialize the beacon to JSON like `serde_json::to_string`, with the hex fields in
+    /// `opts.hex_case`, e.g. for systems that compare uppercase hex verbatim
+    #[cfg(feature = "serde")]
+    pub fn to_json_with_options(&self, opts: &BeaconSerializeOptions) -> String {
+        let encode = |bytes: &[u8]| match opts.hex_case {
+            HexCase::Upper => hex::encode_upper(bytes),
+            HexCase::Lower => hex::encode(bytes),
+        };
+        let json = HexBeacon {
+            round_number: self.round_number,
+            randomness: encode(&self.randomness),
+            signature: encode(&self.signature),
+            previous_signature: encode(&self.previous_signature),
+        };
+        serde_json::to_string(&json).expect("beacons always serialize to JSON")
+    }
+
+    /// the absolute number of rounds between this beacon and `other`
+    pub fn round_distance_to(&self, other: &Beacon) -> u64 {
+        round_distance(self, other)
+    }
+
+    /// how long ago this beacon's round was scheduled on the chain of `chain_info`. Fails
+    /// with `UnexpectedError` if that is still in the future, e.g. because of clock skew.
+    pub fn age(&self, chain_info: &ChainInfo) -> Result<Duration, DrandClientError> {
+        self.age_at(chain_info, SystemTime::now())
+    }
+
+    /// like `age`, as of `now`
+    pub fn age_at(
+        &self,
+        chain_info: &ChainInfo,
+        now: SystemTime,
+    ) -> Result<Duration, DrandClientError> {
+        now.duration_since(time_for_round(chain_info, self.round_number)?)
+            .map_err(|_| DrandClientError::UnexpectedError)
+    }
+
+    /// the randomness as a fixed-size array, e.g. for seeding a generator. Fails with
+    /// `InvalidRandomness` unless it is exactly 32 bytes, the length of a SHA-256 digest.
+    pub fn randomness_array(&self) -> Result<[u8; 32], VerificationError> {
+        self.randomness
+            .as_slice()
+            .try_into()
+            .map_err(|_| VerificationError::InvalidRandomness)
+    }
+
+    /// the signature, without cloning it
+    pub fn signature_ref(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// the previous round's signature, without cloning it. Empty for unchained schemes.
+    pub fn previous_signature_ref(&self) -> &[u8] {
+        &self.previous_signature
+    }
+}
+
+/// the absolute number of rounds between two beacons, e.g. for detecting gaps
+pub fn round_distance(a: &Beacon, b: &Beacon) -> u64 {
+    a.round_number.abs_diff(b.round_number)
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum SchemeID {
     PedersenBlsChained,
@@ -32,55 +145,331 @@ pub enum SchemeID {
     Bn254UnchainedOnG1,
 }
 
+impl SchemeID {
+    /// every known scheme, in the order they were introduced
+    pub fn all() -> &'static [SchemeID] {
+        &[
+            SchemeID::PedersenBlsChained,
+            SchemeID::PedersenBlsUnchained,
+            SchemeID::UnchainedOnG1RFC9380,
+            SchemeID::Bn254UnchainedOnG1,
+        ]
+    }
+
+    /// the scheme's name on the wire, e.g. `"pedersen-bls-chained"`
+    pub const fn name(&self) -> &'static str {
+        match self {
+            SchemeID::PedersenBlsChained => "pedersen-bls-chained",
+            SchemeID::PedersenBlsUnchained => "pedersen-bls-unchained",
+            SchemeID::UnchainedOnG1RFC9380 => "bls-unchained-g1-rfc9380",
+            SchemeID::Bn254UnchainedOnG1 => "bls-bn254-unchained-on-g1",
+        }
+    }
+
+    /// whether beacons of this scheme sign the previous signature, so they must carry
+    /// `previous_signature`
+    pub const fn is_chained(&self) -> bool {
+        matches!(self, SchemeID::PedersenBlsChained)
+    }
+
+    /// the group the chain's public key is on. Signatures are on the other group.
+    pub const fn key_group(&self) -> BLSGroup {
+        match self {
+            SchemeID::PedersenBlsChained | SchemeID::PedersenBlsUnchained => BLSGroup::G1,
+            SchemeID::UnchainedOnG1RFC9380 | SchemeID::Bn254UnchainedOnG1 => BLSGroup::G2,
+        }
+    }
+
+    /// the group beacon signatures are on
+    pub const fn signature_group(&self) -> BLSGroup {
+        match self.key_group() {
+            BLSGroup::G1 => BLSGroup::G2,
+            BLSGroup::G2 => BLSGroup::G1,
+        }
+    }
+}
+
+/// one of the two source groups of a pairing-friendly curve: BLS12-381 for most schemes and
+/// BN254 for `Bn254UnchainedOnG1`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BLSGroup {
+    G1,
+    G2,
+}
+
+impl fmt::Display for SchemeID {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// a scheme name that doesn't belong to any `SchemeID`
+#[derive(Error, Debug, PartialEq, Clone)]
+#[error("unknown scheme {0:?}")]
+pub struct UnknownSchemeError(pub String);
+
+impl FromStr for SchemeID {
+    type Err = UnknownSchemeError;
+
+    /// parse a scheme's wire name, as in the chain info's `schemeID`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SchemeID::all()
+            .iter()
+            .find(|scheme_id| scheme_id.name() == s)
+            .cloned()
+            .ok_or_else(|| UnknownSchemeError(s.to_string()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for SchemeID {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.name())
+    }
+}
+
+#[cfg(feature = "serde")]
 impl<'de> Deserialize<'de> for SchemeID {
     fn deserialize<D>(deserializer: D) -> Result<SchemeID, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let s = Deserialize::deserialize(deserializer)?;
-        match s {
-            "pedersen-bls-chained" => Ok(SchemeID::PedersenBlsChained),
-            "pedersen-bls-unchained" => Ok(SchemeID::PedersenBlsUnchained),
-            "bls-unchained-g1-rfc9380" => Ok(SchemeID::UnchainedOnG1RFC9380),
-            "bls-bn254-unchained-on-g1" => Ok(SchemeID::Bn254UnchainedOnG1),
-            _ => Err(serde::de::Error::unknown_variant(
-                s,
-                &[
-                    "pedersen-bls-chained",
-                    "pedersen-bls-unchained",
-                    "bls-unchained-g1-rfc9380",
-                    "bls-bn254-unchained-on-g1",
-                ],
-            )),
+        let s: &str = Deserialize::deserialize(deserializer)?;
+        s.parse()
+            .map_err(|_| serde::de::Error::unknown_variant(s, &SCHEME_ID_NAMES))
+    }
+}
+
+/// the wire names of every `SchemeID`
+#[cfg(feature = "serde")]
+const SCHEME_ID_NAMES: [&str; 4] = [
+    SchemeID::PedersenBlsChained.name(),
+    SchemeID::PedersenBlsUnchained.name(),
+    SchemeID::UnchainedOnG1RFC9380.name(),
+    SchemeID::Bn254UnchainedOnG1.name(),
+];
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for SchemeID {
+    fn schema_name() -> String {
+        "SchemeID".to_string()
+    }
+
+    fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        schemars::schema::SchemaObject {
+            instance_type: Some(schemars::schema::InstanceType::String.into()),
+            enum_values: Some(SCHEME_ID_NAMES.iter().map(|&name| name.into()).collect()),
+            ..Default::default()
         }
+        .into()
     }
 }
 
 #[derive(Error, Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum VerificationError {
     #[error("chained beacons must have a `previous_signature`")]
     ChainedBeaconNeedsPreviousSignature,
+    #[error("signature can't be empty")]
+    EmptySignature,
     #[error("invalid signature length")]
     InvalidSignatureLength,
     #[error("invalid public key")]
     InvalidPublicKey,
+    #[error("invalid secret key")]
+    InvalidSecretKey,
     #[error("message can't be empty")]
     EmptyMessage,
     #[error("signature verification failed")]
     SignatureFailedVerification,
     #[error("the randomness for the beacon did not match the signature")]
     InvalidRandomness,
+    #[error("the scheme is not supported by this build")]
+    UnsupportedScheme,
+    #[error("signature verification is disabled in this build")]
+    VerificationDisabled,
+    #[error("the beacon of round {at_round} doesn't follow the one before it")]
+    BrokenChain { at_round: u64 },
+    #[error("a signature coordinate is outside the BN254 base field")]
+    CoordinateOutsideField,
 }
 
-/// verify a randomness beacon for a given scheme and public key
+#[cfg(feature = "verify-slim")]
+pub mod slim;
+
+#[cfg(feature = "verify-slim")]
+pub use slim::verify_beacons_batch;
+
+/// verify many beacons of one chain, e.g. a downloaded range of history, failing with the
+/// error `verify_beacon` gives for the first bad beacon. Without the `verify-slim` feature
+/// the beacons are verified one by one; with it, in a single multi-Miller loop.
+#[cfg(not(feature = "verify-slim"))]
+pub fn verify_beacons_batch(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    beacons: &[Beacon],
+) -> Result<(), VerificationError> {
+    beacons
+        .iter()
+        .try_for_each(|beacon| verify_beacon_ref(scheme_id, public_key, beacon))
+}
+#[cfg(all(feature = "verify-slim", not(feature = "energon")))]
+use slim::verify_signature;
+
+// a fetch-only build, without any pairing library
+#[cfg(not(any(feature = "energon", feature = "verify-slim")))]
+fn verify_signature(_: &SchemeID, _: &[u8], _: &Beacon) -> Result<(), VerificationError> {
+    Err(VerificationError::VerificationDisabled)
+}
+
+/// verify a randomness beacon for a given scheme and public key, returning it as a
+/// `VerifiedBeacon`. In builds without a verification feature this always fails with
+/// `VerificationDisabled`.
 pub fn verify_beacon(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    beacon: Beacon,
+) -> Result<VerifiedBeacon, VerificationError> {
+    verify_beacon_ref(scheme_id, public_key, &beacon)?;
+    Ok(VerifiedBeacon(beacon))
+}
+
+// the checks of `verify_beacon`, for callers that keep the beacon itself
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(round = beacon.round_number, scheme_id = %scheme_id)
+    )
+)]
+pub(crate) fn verify_beacon_ref(
     scheme_id: &SchemeID,
     public_key: &[u8],
     beacon: &Beacon,
 ) -> Result<(), VerificationError> {
-    if Sha256::digest(&beacon.signature).to_vec() != beacon.randomness {
+    verify_randomness(beacon)?;
+    verify_signature(scheme_id, public_key, beacon)
+}
+
+/// check only that a beacon's randomness is the SHA-256 of its signature, without verifying
+/// the signature itself
+pub fn verify_randomness(beacon: &Beacon) -> Result<(), VerificationError> {
+    if Sha256::digest(beacon.signature_ref()).as_slice() != beacon.randomness {
         return Err(VerificationError::InvalidRandomness);
     }
+    Ok(())
+}
+
+/// a beacon that has passed `verify_beacon`, so code holding one doesn't need to trust
+/// whoever handed it over. Outside this crate it can only be built by `verify_beacon` or
+/// `VerifiedBeacon::verify`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct VerifiedBeacon(Beacon);
+
+impl VerifiedBeacon {
+    /// verify `beacon` like `verify_beacon`, keeping the proof that it passed
+    pub fn verify(
+        scheme_id: &SchemeID,
+        public_key: &[u8],
+        beacon: Beacon,
+    ) -> Result<Self, VerificationError> {
+        verify_beacon(scheme_id, public_key, beacon)
+    }
+
+    // for beacons the caller has already passed through `verify_beacon`
+    pub(crate) fn assume_verified(beacon: Beacon) -> Self {
+        VerifiedBeacon(beacon)
+    }
+
+    pub fn round_number(&self) -> u64 {
+        self.0.round_number
+    }
+
+    pub fn randomness(&self) -> &[u8] {
+        &self.0.randomness
+    }
+
+    pub fn signature(&self) -> &[u8] {
+        &self.0.signature
+    }
+
+    pub fn previous_signature(&self) -> &[u8] {
+        &self.0.previous_signature
+    }
+
+    /// the beacon, dropping the proof
+    pub fn into_inner(self) -> Beacon {
+        self.0
+    }
+}
+
+impl AsRef<Beacon> for VerifiedBeacon {
+    fn as_ref(&self) -> &Beacon {
+        &self.0
+    }
+}
+
+/// read access to the beacon's fields, e.g. `verified.round_number`
+impl std::ops::Deref for VerifiedBeacon {
+    type Target = Beacon;
+
+    fn deref(&self) -> &Beacon {
+        &self.0
+    }
+}
+
+impl PartialEq<Beacon> for VerifiedBeacon {
+    fn eq(&self, other: &Beacon) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<VerifiedBeacon> for Beacon {
+    fn eq(&self, other: &VerifiedBeacon) -> bool {
+        *self == other.0
+    }
+}
+
+impl From<VerifiedBeacon> for Beacon {
+    fn from(verified: VerifiedBeacon) -> Self {
+        verified.0
+    }
+}
+
+/// verify a run of consecutive beacons, e.g. ones cached earlier. Each beacon is verified on
+/// its own; for chained schemes each must also follow the one before it, with the next round
+/// number and that beacon's signature as its `previous_signature`, or this fails with
+/// `BrokenChain` at the first beacon that doesn't.
+pub fn verify_beacon_chain(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    beacons: &[Beacon],
+) -> Result<(), VerificationError> {
+    for (i, beacon) in beacons.iter().enumerate() {
+        if let Some(previous) = i.checked_sub(1).map(|i| &beacons[i]) {
+            let follows = previous.round_number.checked_add(1) == Some(beacon.round_number)
+                && previous.signature == beacon.previous_signature;
+            if scheme_id.is_chained() && !follows {
+                return Err(VerificationError::BrokenChain {
+                    at_round: beacon.round_number,
+                });
+            }
+        }
+        verify_beacon_ref(scheme_id, public_key, beacon)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "energon")]
+fn verify_signature(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    beacon: &Beacon,
+) -> Result<(), VerificationError> {
     match scheme_id {
         SchemeID::PedersenBlsChained => verify::<DefaultScheme>(public_key, beacon),
         SchemeID::PedersenBlsUnchained => verify::<UnchainedScheme>(public_key, beacon),
@@ -89,9 +478,10 @@ pub fn verify_beacon(
     }
 }
 
+#[cfg(feature = "energon")]
 pub fn verify<S: Scheme>(public_key: &[u8], beacon: &Beacon) -> Result<(), VerificationError> {
     if beacon.signature.is_empty() {
-        return Err(VerificationError::InvalidSignatureLength);
+        return Err(VerificationError::EmptySignature);
     }
 
     if S::Beacon::is_chained() && beacon.previous_signature.is_empty() {
@@ -116,14 +506,135 @@ pub fn verify<S: Scheme>(public_key: &[u8], beacon: &Beacon) -> Result<(), Verif
     Ok(())
 }
 
+#[derive(Error, Debug, PartialEq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum DetectError {
+    #[error(
+        "no scheme uses {public_key_len}-byte public keys with {signature_len}-byte signatures"
+    )]
+    UnknownSizes {
+        public_key_len: usize,
+        signature_len: usize,
+    },
+    /// not reachable with the current schemes: the only candidates that share key and
+    /// signature sizes are the two pedersen schemes, and a signature over both of their
+    /// messages would need a SHA-256 collision. Kept for schemes added later.
+    #[error("the beacon verifies under more than one scheme: {0:?}")]
+    Ambiguous(Vec<SchemeID>),
+    #[error("the beacon failed verification under every candidate scheme: {0}")]
+    FailedVerification(VerificationError),
+}
+
+/// infer the candidate schemes for a beacon from its public key and signature sizes, most
+/// likely first. Chained beacons always carry a `previous_signature`, but unchained ones
+/// may too, so a beacon with one yields both pedersen schemes.
+pub fn detect_scheme(public_key: &[u8], beacon: &Beacon) -> Result<Vec<SchemeID>, DetectError> {
+    match (public_key.len(), beacon.signature.len()) {
+        // BLS12-381 keys on G1 with signatures on G2
+        (48, 96) if beacon.previous_signature.is_empty() => {
+            Ok(vec![SchemeID::PedersenBlsUnchained])
+        }
+        (48, 96) => Ok(vec![
+            SchemeID::PedersenBlsChained,
+            SchemeID::PedersenBlsUnchained,
+        ]),
+        // BLS12-381 keys on G2 with signatures on G1
+        (96, 48) => Ok(vec![SchemeID::UnchainedOnG1RFC9380]),
+        // uncompressed BN254 keys on G2 with signatures on G1
+        (128, 64) => Ok(vec![SchemeID::Bn254UnchainedOnG1]),
+        (public_key_len, signature_len) => Err(DetectError::UnknownSizes {
+            public_key_len,
+            signature_len,
+        }),
+    }
+}
+
+/// verify a beacon without knowing its scheme, returning the scheme it verified under.
+/// Every candidate from `detect_scheme` is tried in order, and a beacon that verifies
+/// under more than one is reported as `Ambiguous` rather than settled by picking one.
+pub fn verify_beacon_autodetect(
+    public_key: &[u8],
+    beacon: &Beacon,
+) -> Result<SchemeID, DetectError> {
+    let mut verified = Vec::new();
+    let mut last_error = None;
+    for scheme_id in detect_scheme(public_key, beacon)? {
+        match verify_beacon_ref(&scheme_id, public_key, beacon) {
+            Ok(_) => verified.push(scheme_id),
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    match verified.len() {
+        0 => Err(DetectError::FailedVerification(
+            last_error.expect("detect_scheme never returns an empty list"),
+        )),
+        1 => Ok(verified.remove(0)),
+        _ => Err(DetectError::Ambiguous(verified)),
+    }
+}
+
+#[cfg(any(test, feature = "test-helpers"))]
+const UNCHAINED_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+
+#[cfg(any(test, feature = "test-helpers"))]
+pub(crate) fn secret_key_scalar(secret_key: &[u8]) -> Result<bls12_381::Scalar, VerificationError> {
+    // secret keys are big-endian, as drand serialises them, but `bls12_381` reads little-endian
+    let mut bytes: [u8; 32] = secret_key
+        .try_into()
+        .map_err(|_| VerificationError::InvalidSecretKey)?;
+    bytes.reverse();
+
+    let scalar = Option::<bls12_381::Scalar>::from(bls12_381::Scalar::from_bytes(&bytes))
+        .ok_or(VerificationError::InvalidSecretKey)?;
+    if scalar == bls12_381::Scalar::zero() {
+        return Err(VerificationError::InvalidSecretKey);
+    }
+    Ok(scalar)
+}
+
+/// derive the `pedersen-bls-unchained` public key (a compressed G1 point) for a 32-byte
+/// big-endian secret key
+#[cfg(any(test, feature = "test-helpers"))]
+pub fn unchained_public_key(secret_key: &[u8]) -> Result<Vec<u8>, VerificationError> {
+    let scalar = secret_key_scalar(secret_key)?;
+    let public_key = bls12_381::G1Affine::from(bls12_381::G1Projective::generator() * scalar);
+    Ok(public_key.to_compressed().to_vec())
+}
+
+/// sign `round` with a 32-byte big-endian secret key, producing a `pedersen-bls-unchained`
+/// beacon that passes `verify_beacon`. Intended for tests and node simulation only.
+#[cfg(any(test, feature = "test-helpers"))]
+pub fn generate_unchained_beacon(
+    secret_key: &[u8],
+    round: u64,
+) -> Result<Beacon, VerificationError> {
+    use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+    use bls12_381::{G2Affine, G2Projective};
+
+    let scalar = secret_key_scalar(secret_key)?;
+    let message = Sha256::digest(round.to_be_bytes());
+    let point = <G2Projective as HashToCurve<ExpandMsgXmd<sha2_09::Sha256>>>::hash_to_curve(
+        message,
+        UNCHAINED_DST,
+    );
+    let signature = G2Affine::from(point * scalar).to_compressed().to_vec();
+
+    Ok(Beacon {
+        round_number: round,
+        randomness: Sha256::digest(&signature).to_vec(),
+        signature,
+        previous_signature: Vec::new(),
+    })
+}
+
 // Tests might be executed with different backends
 // cargo test --package drand-client-rs --features blstrs
 // cargo test --package drand-client-rs --features arkworks
-#[cfg(test)]
+#[cfg(all(test, any(feature = "energon", feature = "verify-slim")))]
 mod test {
     use super::*;
-    use energon::points::KeyPoint;
-    use energon::traits::Affine;
+    use crate::testkit::{mainnet_info, TestChain};
 
     #[test]
     fn default_beacon_verifies() {
@@ -138,8 +649,8 @@ mod test {
         };
 
         assert!(matches!(
-            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
-            Ok(()),
+            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, beacon),
+            Ok(_),
         ));
     }
 
@@ -156,7 +667,7 @@ mod test {
         };
 
         assert_error(
-            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
+            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, beacon),
             VerificationError::SignatureFailedVerification,
         );
     }
@@ -175,7 +686,7 @@ mod test {
         };
 
         assert_error(
-            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
+            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, beacon),
             VerificationError::InvalidRandomness,
         );
     }
@@ -192,33 +703,36 @@ mod test {
         };
 
         assert_error(
-            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
+            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, beacon),
             VerificationError::ChainedBeaconNeedsPreviousSignature,
         );
     }
 
     #[test]
-    fn default_beacon_invalid_public_key_fails() {
-        // public key is not correct
-        let public_key = dehexify("78a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb");
+    fn default_beacon_empty_signature_fails() {
+        let public_key = dehexify("88a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb");
         let prev_sig = dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce");
 
         let beacon = Beacon {
             round_number: 397089,
-            randomness: dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
-            signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
+            // sha256 of the empty signature, so the randomness check passes
+            randomness: dehexify(
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            signature: Vec::new(),
             previous_signature: prev_sig,
         };
 
         assert_error(
-            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
-            VerificationError::InvalidPublicKey,
+            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, beacon),
+            VerificationError::EmptySignature,
         );
     }
 
     #[test]
-    fn default_beacon_empty_public_key_fails() {
-        let public_key = Vec::new();
+    fn default_beacon_invalid_public_key_fails() {
+        // public key is not correct
+        let public_key = dehexify("78a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb");
         let prev_sig = dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce");
 
         let beacon = Beacon {
@@ -229,15 +743,14 @@ mod test {
         };
 
         assert_error(
-            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, &beacon),
+            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, beacon),
             VerificationError::InvalidPublicKey,
         );
     }
 
     #[test]
-    fn default_beacon_infinity_public_key_fails() {
-        let public_key: KeyPoint<DefaultScheme> = Affine::identity();
-        let public_key_bytes = public_key.serialize().unwrap();
+    fn default_beacon_empty_public_key_fails() {
+        let public_key = Vec::new();
         let prev_sig = dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce");
 
         let beacon = Beacon {
@@ -248,7 +761,7 @@ mod test {
         };
 
         assert_error(
-            verify_beacon(&SchemeID::PedersenBlsChained, &public_key_bytes, &beacon),
+            verify_beacon(&SchemeID::PedersenBlsChained, &public_key, beacon),
             VerificationError::InvalidPublicKey,
         );
     }
@@ -264,7 +777,7 @@ mod test {
         };
 
         assert!(matches!(
-            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
+            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, beacon),
             Ok(_),
         ));
     }
@@ -280,7 +793,7 @@ mod test {
         };
 
         assert_error(
-            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
+            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, beacon),
             VerificationError::SignatureFailedVerification,
         );
     }
@@ -297,7 +810,7 @@ mod test {
         };
 
         assert_error(
-            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
+            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, beacon),
             VerificationError::InvalidRandomness,
         );
     }
@@ -313,31 +826,34 @@ mod test {
         };
 
         assert!(matches!(
-            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
-            Ok(())
+            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, beacon),
+            Ok(_)
         ));
     }
 
     #[test]
-    fn testnet_unchained_invalid_public_key_fails() {
-        // valid public key, but for wrong scheme
-        let public_key = dehexify("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31");
+    fn testnet_unchained_beacon_empty_signature_fails() {
+        let public_key = dehexify("8d91ae0f4e3cd277cfc46aba26680232b0d5bb4444602cdb23442d62e17f43cdffb1104909e535430c10a6a1ce680a65");
         let beacon = Beacon {
             round_number: 397092,
-            randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
-            signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
+            // sha256 of the empty signature, so the randomness check passes
+            randomness: dehexify(
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            signature: Vec::new(),
             previous_signature: Vec::new(),
         };
 
         assert_error(
-            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
-            VerificationError::SignatureFailedVerification,
+            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, beacon),
+            VerificationError::EmptySignature,
         );
     }
 
     #[test]
-    fn testnet_unchained_beacon_empty_public_key_fails() {
-        let public_key = Vec::new();
+    fn testnet_unchained_invalid_public_key_fails() {
+        // valid public key, but for wrong scheme
+        let public_key = mainnet_info().public_key;
         let beacon = Beacon {
             round_number: 397092,
             randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
@@ -346,15 +862,14 @@ mod test {
         };
 
         assert_error(
-            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, &beacon),
-            VerificationError::InvalidPublicKey,
+            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, beacon),
+            VerificationError::SignatureFailedVerification,
         );
     }
 
     #[test]
-    fn testnet_unchained_beacon_infinity_public_key_fails() {
-        let public_key: KeyPoint<UnchainedScheme> = Affine::identity();
-        let public_key_bytes = public_key.serialize().unwrap();
+    fn testnet_unchained_beacon_empty_public_key_fails() {
+        let public_key = Vec::new();
         let beacon = Beacon {
             round_number: 397092,
             randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
@@ -363,7 +878,7 @@ mod test {
         };
 
         assert_error(
-            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key_bytes, &beacon),
+            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, beacon),
             VerificationError::InvalidPublicKey,
         );
     }
@@ -379,7 +894,7 @@ mod test {
         };
 
         assert!(matches!(
-            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, &beacon),
+            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, beacon),
             Ok(_)
         ));
     }
@@ -395,24 +910,7 @@ mod test {
         };
 
         assert_error(
-            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, &beacon),
-            VerificationError::InvalidPublicKey,
-        );
-    }
-
-    #[test]
-    fn g1g2_swap_infinity_public_key_fails() {
-        let public_key: KeyPoint<SigsOnG1Scheme> = Affine::identity();
-        let public_key_bytes = public_key.serialize().unwrap();
-        let beacon = Beacon {
-            round_number: 1000,
-            randomness: dehexify("fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"),
-            signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
-            previous_signature: Vec::new(),
-        };
-
-        assert_error(
-            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key_bytes, &beacon),
+            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, beacon),
             VerificationError::InvalidPublicKey,
         );
     }
@@ -428,7 +926,7 @@ mod test {
         };
 
         assert_error(
-            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, &beacon),
+            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, beacon),
             VerificationError::SignatureFailedVerification,
         );
     }
@@ -445,7 +943,7 @@ mod test {
         };
 
         assert_error(
-            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, &beacon),
+            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, beacon),
             VerificationError::InvalidRandomness,
         );
     }
@@ -463,98 +961,212 @@ mod test {
         };
 
         assert_error(
-            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, &beacon),
+            verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key, beacon),
             VerificationError::SignatureFailedVerification,
         );
     }
 
     #[test]
-    fn bn254_unchained_on_g1_beacon_verifies() {
-        let public_key = dehexify("07e1d1d335df83fa98462005690372c643340060d205306a9aa8106b6bd0b3820557ec32c2ad488e4d4f6008f89a346f18492092ccc0d594610de2732c8b808f0095685ae3a85ba243747b1b2f426049010f6b73a0cf1d389351d5aaaa1047f6297d3a4f9749b33eb2d904c9d9ebf17224150ddd7abd7567a9bec6c74480ee0b");
-        let beacon = Beacon {
-            round_number: 1000,
-            randomness: dehexify("0e6745667465a6f9dce5d5f994656955080be14c469ff17fc4fc588c925a8504"),
-            signature: dehexify("06fd5996329504d3a56b482d9222bf7205857d0a9559ddd216ca31a286f6a8cc0a120f021aac2f13553fb164f62bc3a5ca32c76dea88a777b39bcf3cac5fdbd6"),
-            previous_signature: Vec::new(),
-        };
+    fn generated_unchained_beacon_verifies() {
+        let secret_key =
+            dehexify("1c8f2ea6a5c6e1a2e9a3b7cbd149fc7a1b6fbf3d6b196a8d8a58b2b5a6f0d1e2");
+        let public_key = unchained_public_key(&secret_key).unwrap();
+
+        for round in [1, 2, 397092] {
+            let beacon = generate_unchained_beacon(&secret_key, round).unwrap();
+            assert!(matches!(
+                verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, beacon),
+                Ok(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn generated_unchained_beacon_wrong_round_fails() {
+        let secret_key =
+            dehexify("1c8f2ea6a5c6e1a2e9a3b7cbd149fc7a1b6fbf3d6b196a8d8a58b2b5a6f0d1e2");
+        let public_key = unchained_public_key(&secret_key).unwrap();
+        let mut beacon = generate_unchained_beacon(&secret_key, 10).unwrap();
+        beacon.round_number = 11;
 
+        assert_error(
+            verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key, beacon),
+            VerificationError::SignatureFailedVerification,
+        );
+    }
+
+    #[test]
+    fn generate_with_invalid_secret_key_fails() {
         assert!(matches!(
-            verify_beacon(&SchemeID::Bn254UnchainedOnG1, &public_key, &beacon),
-            Ok(_)
+            generate_unchained_beacon(&[0u8; 32], 1),
+            Err(VerificationError::InvalidSecretKey)
+        ));
+        assert!(matches!(
+            generate_unchained_beacon(&[1u8; 31], 1),
+            Err(VerificationError::InvalidSecretKey)
         ));
     }
 
     #[test]
-    fn bn254_unchained_on_g1_empty_public_key_fails() {
-        let public_key = Vec::new();
-        let beacon = Beacon {
-            round_number: 1000,
-            randomness: dehexify("0e6745667465a6f9dce5d5f994656955080be14c469ff17fc4fc588c925a8504"),
-            signature: dehexify("06fd5996329504d3a56b482d9222bf7205857d0a9559ddd216ca31a286f6a8cc0a120f021aac2f13553fb164f62bc3a5ca32c76dea88a777b39bcf3cac5fdbd6"),
+    fn round_distance_is_symmetric_and_does_not_overflow() {
+        let beacon = |round_number| Beacon {
+            round_number,
+            randomness: Vec::new(),
+            signature: Vec::new(),
             previous_signature: Vec::new(),
         };
 
-        assert_error(
-            verify_beacon(&SchemeID::Bn254UnchainedOnG1, &public_key, &beacon),
-            VerificationError::InvalidPublicKey,
-        );
+        assert_eq!(round_distance(&beacon(3), &beacon(10)), 7);
+        assert_eq!(round_distance(&beacon(10), &beacon(3)), 7);
+        assert_eq!(beacon(5).round_distance_to(&beacon(5)), 0);
+        assert_eq!(beacon(0).round_distance_to(&beacon(u64::MAX)), u64::MAX);
     }
 
     #[test]
-    fn bn254_unchained_on_g1_infinity_public_key_fails() {
-        let public_key: KeyPoint<BN254UnchainedOnG1Scheme> = Affine::identity();
-        let public_key_bytes = public_key.serialize().unwrap();
+    fn detect_scheme_for_every_fixture() {
+        let (public_key, beacon) = chained_fixture();
+        assert_eq!(
+            verify_beacon_autodetect(&public_key, &beacon),
+            Ok(SchemeID::PedersenBlsChained)
+        );
+
+        let (public_key, mut beacon) = unchained_fixture();
+        assert_eq!(
+            detect_scheme(&public_key, &beacon),
+            Ok(vec![SchemeID::PedersenBlsUnchained])
+        );
+        assert_eq!(
+            verify_beacon_autodetect(&public_key, &beacon),
+            Ok(SchemeID::PedersenBlsUnchained)
+        );
+        beacon.round_number = 1;
+        assert_eq!(
+            verify_beacon_autodetect(&public_key, &beacon),
+            Err(DetectError::FailedVerification(
+                VerificationError::SignatureFailedVerification
+            ))
+        );
+
+        let public_key = dehexify("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
         let beacon = Beacon {
             round_number: 1000,
-            randomness: dehexify("0e6745667465a6f9dce5d5f994656955080be14c469ff17fc4fc588c925a8504"),
-            signature: dehexify("06fd5996329504d3a56b482d9222bf7205857d0a9559ddd216ca31a286f6a8cc0a120f021aac2f13553fb164f62bc3a5ca32c76dea88a777b39bcf3cac5fdbd6"),
+            randomness: dehexify("fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"),
+            signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
             previous_signature: Vec::new(),
         };
-
-        assert_error(
-            verify_beacon(&SchemeID::Bn254UnchainedOnG1, &public_key_bytes, &beacon),
-            VerificationError::InvalidPublicKey,
+        assert_eq!(
+            verify_beacon_autodetect(&public_key, &beacon),
+            Ok(SchemeID::UnchainedOnG1RFC9380)
         );
+
+        #[cfg(feature = "energon")]
+        {
+            let public_key = dehexify("07e1d1d335df83fa98462005690372c643340060d205306a9aa8106b6bd0b3820557ec32c2ad488e4d4f6008f89a346f18492092ccc0d594610de2732c8b808f0095685ae3a85ba243747b1b2f426049010f6b73a0cf1d389351d5aaaa1047f6297d3a4f9749b33eb2d904c9d9ebf17224150ddd7abd7567a9bec6c74480ee0b");
+            let beacon = Beacon {
+                round_number: 1000,
+                randomness: dehexify("0e6745667465a6f9dce5d5f994656955080be14c469ff17fc4fc588c925a8504"),
+                signature: dehexify("06fd5996329504d3a56b482d9222bf7205857d0a9559ddd216ca31a286f6a8cc0a120f021aac2f13553fb164f62bc3a5ca32c76dea88a777b39bcf3cac5fdbd6"),
+                previous_signature: Vec::new(),
+            };
+            assert_eq!(
+                verify_beacon_autodetect(&public_key, &beacon),
+                Ok(SchemeID::Bn254UnchainedOnG1)
+            );
+        }
     }
 
     #[test]
-    fn bn254_unchained_on_g1_wrong_round_fails() {
-        let public_key = dehexify("07e1d1d335df83fa98462005690372c643340060d205306a9aa8106b6bd0b3820557ec32c2ad488e4d4f6008f89a346f18492092ccc0d594610de2732c8b808f0095685ae3a85ba243747b1b2f426049010f6b73a0cf1d389351d5aaaa1047f6297d3a4f9749b33eb2d904c9d9ebf17224150ddd7abd7567a9bec6c74480ee0b");
-        let beacon = Beacon {
-            round_number: 1,
-            randomness: dehexify("0e6745667465a6f9dce5d5f994656955080be14c469ff17fc4fc588c925a8504"),
-            signature: dehexify("06fd5996329504d3a56b482d9222bf7205857d0a9559ddd216ca31a286f6a8cc0a120f021aac2f13553fb164f62bc3a5ca32c76dea88a777b39bcf3cac5fdbd6"),
-            previous_signature: Vec::new(),
-        };
+    fn detect_scheme_lists_both_pedersen_schemes_for_a_previous_signature() {
+        // an unchained beacon carrying a previous signature looks like either pedersen scheme
+        let (public_key, mut beacon) = unchained_fixture();
+        beacon.previous_signature = beacon.signature.clone();
+        assert_eq!(
+            detect_scheme(&public_key, &beacon),
+            Ok(vec![
+                SchemeID::PedersenBlsChained,
+                SchemeID::PedersenBlsUnchained
+            ])
+        );
+        // only one of the candidates can verify, so the beacon is never `Ambiguous`
+        assert_eq!(
+            verify_beacon_autodetect(&public_key, &beacon),
+            Ok(SchemeID::PedersenBlsUnchained)
+        );
+    }
 
-        assert_error(
-            verify_beacon(&SchemeID::Bn254UnchainedOnG1, &public_key, &beacon),
-            VerificationError::SignatureFailedVerification,
+    #[test]
+    fn detect_scheme_rejects_unknown_sizes() {
+        let (public_key, beacon) = unchained_fixture();
+        assert_eq!(
+            detect_scheme(&public_key[..47], &beacon),
+            Err(DetectError::UnknownSizes {
+                public_key_len: 47,
+                signature_len: 96,
+            })
         );
     }
 
+    #[cfg(feature = "serde")]
     #[test]
-    fn bn254_unchained_on_g1_invalid_randomness_fails() {
-        let public_key = dehexify("07e1d1d335df83fa98462005690372c643340060d205306a9aa8106b6bd0b3820557ec32c2ad488e4d4f6008f89a346f18492092ccc0d594610de2732c8b808f0095685ae3a85ba243747b1b2f426049010f6b73a0cf1d389351d5aaaa1047f6297d3a4f9749b33eb2d904c9d9ebf17224150ddd7abd7567a9bec6c74480ee0b");
+    fn beacon_json_hex_case_is_configurable() {
+        let (_, beacon) = chained_fixture();
+        let upper = beacon.to_json_with_options(&BeaconSerializeOptions {
+            hex_case: HexCase::Upper,
+        });
+        let lower = beacon.to_json_with_options(&BeaconSerializeOptions::default());
+
+        assert!(upper.contains(&hex::encode_upper(&beacon.signature)));
+        assert!(lower.contains(&hex::encode(&beacon.signature)));
+        assert_eq!(lower, serde_json::to_string(&beacon).unwrap());
+        // both cases parse back to the same beacon
+        assert_eq!(serde_json::from_str::<Beacon>(&upper).unwrap(), beacon);
+        assert_eq!(serde_json::from_str::<Beacon>(&lower).unwrap(), beacon);
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn beacon_json_schema_makes_previous_signature_optional() {
+        let schema = serde_json::to_value(schemars::schema_for!(Beacon)).unwrap();
+
+        let mut required: Vec<&str> = schema["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|field| field.as_str().unwrap())
+            .collect();
+        required.sort();
+        assert_eq!(required, ["randomness", "round_number", "signature"]);
+        assert!(schema["properties"]["previous_signature"].is_object());
+        assert_eq!(schema["properties"]["signature"]["type"], "string");
+    }
+
+    fn chained_fixture() -> (Vec<u8>, Beacon) {
+        let public_key = dehexify("88a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb");
         let beacon = Beacon {
-            round_number: 1000,
-            // incorrect hash for the signature
-            randomness: dehexify("1e6745667465a6f9dce5d5f994656955080be14c469ff17fc4fc588c925a8504"),
-            signature: dehexify("06fd5996329504d3a56b482d9222bf7205857d0a9559ddd216ca31a286f6a8cc0a120f021aac2f13553fb164f62bc3a5ca32c76dea88a777b39bcf3cac5fdbd6"),
-            previous_signature: Vec::new(),
+            round_number: 397089,
+            randomness: dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
+            signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
+            previous_signature: dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce"),
         };
+        (public_key, beacon)
+    }
 
-        assert_error(
-            verify_beacon(&SchemeID::Bn254UnchainedOnG1, &public_key, &beacon),
-            VerificationError::InvalidRandomness,
-        );
+    fn unchained_fixture() -> (Vec<u8>, Beacon) {
+        let public_key = dehexify("8d91ae0f4e3cd277cfc46aba26680232b0d5bb4444602cdb23442d62e17f43cdffb1104909e535430c10a6a1ce680a65");
+        let beacon = Beacon {
+            round_number: 397092,
+            randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
+            signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
+            previous_signature: Vec::new(),
+        };
+        (public_key, beacon)
     }
 
     fn dehexify(s: &str) -> Vec<u8> {
         hex::decode(s).unwrap().to_vec()
     }
 
-    fn assert_error(actual: Result<(), VerificationError>, expected: VerificationError) {
+    fn assert_error<T>(actual: Result<T, VerificationError>, expected: VerificationError) {
         match actual {
             Ok(_) => panic!("expected error but got success"),
             Err(e) => {
@@ -564,4 +1176,343 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn batches_verify_with_every_backend() {
+        let chain = TestChain::new(SchemeID::PedersenBlsUnchained, 30, 1595431050);
+        let public_key = &chain.chain_info().public_key;
+        let mut beacons: Vec<_> = (1..=4).map(|round| chain.sign_round(round)).collect();
+
+        let batch = |beacons: &[Beacon]| {
+            verify_beacons_batch(&SchemeID::PedersenBlsUnchained, public_key, beacons)
+        };
+        assert_eq!(batch(&[]), Ok(()));
+        assert_eq!(batch(&beacons[..1]), Ok(()));
+        assert_eq!(batch(&beacons), Ok(()));
+
+        beacons[2].round_number = 7;
+        assert_error(
+            batch(&beacons),
+            VerificationError::SignatureFailedVerification,
+        );
+    }
+
+    #[test]
+    fn only_verified_beacons_become_verified_beacons() {
+        let chain = crate::testkit::TestChain::new(SchemeID::PedersenBlsUnchained, 30, 1595431050);
+        let public_key = &chain.chain_info().public_key;
+        let beacon = chain.sign_round(4);
+
+        let verified =
+            VerifiedBeacon::verify(&SchemeID::PedersenBlsUnchained, public_key, beacon.clone())
+                .unwrap();
+        assert_eq!(verified.round_number(), 4);
+        assert_eq!(verified.randomness(), beacon.randomness);
+        assert_eq!(verified.as_ref(), &beacon);
+        assert_eq!(verified.signature, beacon.signature);
+        assert_eq!(verified.into_inner(), beacon);
+
+        let mut forged = beacon;
+        forged.round_number = 5;
+        assert_eq!(
+            VerifiedBeacon::verify(&SchemeID::PedersenBlsUnchained, public_key, forged),
+            Err(VerificationError::SignatureFailedVerification)
+        );
+    }
+
+    #[test]
+    fn beacon_chains_must_be_unbroken_when_chained() {
+        let chain = crate::testkit::TestChain::new(SchemeID::PedersenBlsChained, 30, 1595431050);
+        let public_key = &chain.chain_info().public_key;
+        let beacons: Vec<_> = (3..=6).map(|round| chain.sign_round(round)).collect();
+        assert_eq!(
+            verify_beacon_chain(&SchemeID::PedersenBlsChained, public_key, &beacons),
+            Ok(())
+        );
+        assert_eq!(
+            verify_beacon_chain(&SchemeID::PedersenBlsChained, public_key, &[]),
+            Ok(())
+        );
+
+        let gap = [beacons[0].clone(), beacons[2].clone()];
+        assert_eq!(
+            verify_beacon_chain(&SchemeID::PedersenBlsChained, public_key, &gap),
+            Err(VerificationError::BrokenChain { at_round: 5 })
+        );
+
+        let unchained =
+            crate::testkit::TestChain::new(SchemeID::PedersenBlsUnchained, 30, 1595431050);
+        let public_key = &unchained.chain_info().public_key;
+        let beacons = [unchained.sign_round(9), unchained.sign_round(2)];
+        assert_eq!(
+            verify_beacon_chain(&SchemeID::PedersenBlsUnchained, public_key, &beacons),
+            Ok(())
+        );
+    }
+
+    // tests that build the point at infinity with energon's types, or use the bn254 scheme,
+    // which only energon supports. `slim` has its own infinity tests on raw bytes, and
+    // fails bn254 with `UnsupportedScheme`.
+    #[cfg(feature = "energon")]
+    mod energon_only {
+        use super::*;
+        use energon::points::KeyPoint;
+        use energon::traits::Affine;
+
+        #[test]
+        fn default_beacon_infinity_public_key_fails() {
+            let public_key: KeyPoint<DefaultScheme> = Affine::identity();
+            let public_key_bytes = public_key.serialize().unwrap();
+            let prev_sig = dehexify("a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce");
+
+            let beacon = Beacon {
+                round_number: 397089,
+                randomness: dehexify("cd435675735e459fb4d9c68a9d9f7b719e59e0a9f5f86fe6bd86b730d01fba42"),
+                signature: dehexify("88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a"),
+                previous_signature: prev_sig,
+            };
+
+            assert_error(
+                verify_beacon(&SchemeID::PedersenBlsChained, &public_key_bytes, beacon),
+                VerificationError::InvalidPublicKey,
+            );
+        }
+
+        #[test]
+        fn testnet_unchained_beacon_infinity_public_key_fails() {
+            let public_key: KeyPoint<UnchainedScheme> = Affine::identity();
+            let public_key_bytes = public_key.serialize().unwrap();
+            let beacon = Beacon {
+                round_number: 397092,
+                randomness: dehexify("7731783ab8118d7484d0e8e237f3023a4c7ef4532f35016f2e56e89a7570c796"),
+                signature: dehexify("94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539"),
+                previous_signature: Vec::new(),
+            };
+
+            assert_error(
+                verify_beacon(&SchemeID::PedersenBlsUnchained, &public_key_bytes, beacon),
+                VerificationError::InvalidPublicKey,
+            );
+        }
+
+        #[test]
+        fn g1g2_swap_infinity_public_key_fails() {
+            let public_key: KeyPoint<SigsOnG1Scheme> = Affine::identity();
+            let public_key_bytes = public_key.serialize().unwrap();
+            let beacon = Beacon {
+                round_number: 1000,
+                randomness: dehexify("fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd"),
+                signature: dehexify("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"),
+                previous_signature: Vec::new(),
+            };
+
+            assert_error(
+                verify_beacon(&SchemeID::UnchainedOnG1RFC9380, &public_key_bytes, beacon),
+                VerificationError::InvalidPublicKey,
+            );
+        }
+
+        #[test]
+        fn bn254_unchained_on_g1_beacon_verifies() {
+            let public_key = dehexify("07e1d1d335df83fa98462005690372c643340060d205306a9aa8106b6bd0b3820557ec32c2ad488e4d4f6008f89a346f18492092ccc0d594610de2732c8b808f0095685ae3a85ba243747b1b2f426049010f6b73a0cf1d389351d5aaaa1047f6297d3a4f9749b33eb2d904c9d9ebf17224150ddd7abd7567a9bec6c74480ee0b");
+            let beacon = Beacon {
+                round_number: 1000,
+                randomness: dehexify("0e6745667465a6f9dce5d5f994656955080be14c469ff17fc4fc588c925a8504"),
+                signature: dehexify("06fd5996329504d3a56b482d9222bf7205857d0a9559ddd216ca31a286f6a8cc0a120f021aac2f13553fb164f62bc3a5ca32c76dea88a777b39bcf3cac5fdbd6"),
+                previous_signature: Vec::new(),
+            };
+
+            assert!(matches!(
+                verify_beacon(&SchemeID::Bn254UnchainedOnG1, &public_key, beacon),
+                Ok(_)
+            ));
+        }
+
+        #[test]
+        fn bn254_unchained_on_g1_empty_public_key_fails() {
+            let public_key = Vec::new();
+            let beacon = Beacon {
+                round_number: 1000,
+                randomness: dehexify("0e6745667465a6f9dce5d5f994656955080be14c469ff17fc4fc588c925a8504"),
+                signature: dehexify("06fd5996329504d3a56b482d9222bf7205857d0a9559ddd216ca31a286f6a8cc0a120f021aac2f13553fb164f62bc3a5ca32c76dea88a777b39bcf3cac5fdbd6"),
+                previous_signature: Vec::new(),
+            };
+
+            assert_error(
+                verify_beacon(&SchemeID::Bn254UnchainedOnG1, &public_key, beacon),
+                VerificationError::InvalidPublicKey,
+            );
+        }
+
+        #[test]
+        fn bn254_unchained_on_g1_infinity_public_key_fails() {
+            let public_key: KeyPoint<BN254UnchainedOnG1Scheme> = Affine::identity();
+            let public_key_bytes = public_key.serialize().unwrap();
+            let beacon = Beacon {
+                round_number: 1000,
+                randomness: dehexify("0e6745667465a6f9dce5d5f994656955080be14c469ff17fc4fc588c925a8504"),
+                signature: dehexify("06fd5996329504d3a56b482d9222bf7205857d0a9559ddd216ca31a286f6a8cc0a120f021aac2f13553fb164f62bc3a5ca32c76dea88a777b39bcf3cac5fdbd6"),
+                previous_signature: Vec::new(),
+            };
+
+            assert_error(
+                verify_beacon(&SchemeID::Bn254UnchainedOnG1, &public_key_bytes, beacon),
+                VerificationError::InvalidPublicKey,
+            );
+        }
+
+        #[test]
+        fn bn254_unchained_on_g1_wrong_round_fails() {
+            let public_key = dehexify("07e1d1d335df83fa98462005690372c643340060d205306a9aa8106b6bd0b3820557ec32c2ad488e4d4f6008f89a346f18492092ccc0d594610de2732c8b808f0095685ae3a85ba243747b1b2f426049010f6b73a0cf1d389351d5aaaa1047f6297d3a4f9749b33eb2d904c9d9ebf17224150ddd7abd7567a9bec6c74480ee0b");
+            let beacon = Beacon {
+                round_number: 1,
+                randomness: dehexify("0e6745667465a6f9dce5d5f994656955080be14c469ff17fc4fc588c925a8504"),
+                signature: dehexify("06fd5996329504d3a56b482d9222bf7205857d0a9559ddd216ca31a286f6a8cc0a120f021aac2f13553fb164f62bc3a5ca32c76dea88a777b39bcf3cac5fdbd6"),
+                previous_signature: Vec::new(),
+            };
+
+            assert_error(
+                verify_beacon(&SchemeID::Bn254UnchainedOnG1, &public_key, beacon),
+                VerificationError::SignatureFailedVerification,
+            );
+        }
+
+        #[test]
+        fn bn254_unchained_on_g1_invalid_randomness_fails() {
+            let public_key = dehexify("07e1d1d335df83fa98462005690372c643340060d205306a9aa8106b6bd0b3820557ec32c2ad488e4d4f6008f89a346f18492092ccc0d594610de2732c8b808f0095685ae3a85ba243747b1b2f426049010f6b73a0cf1d389351d5aaaa1047f6297d3a4f9749b33eb2d904c9d9ebf17224150ddd7abd7567a9bec6c74480ee0b");
+            let beacon = Beacon {
+                round_number: 1000,
+                // incorrect hash for the signature
+                randomness: dehexify("1e6745667465a6f9dce5d5f994656955080be14c469ff17fc4fc588c925a8504"),
+                signature: dehexify("06fd5996329504d3a56b482d9222bf7205857d0a9559ddd216ca31a286f6a8cc0a120f021aac2f13553fb164f62bc3a5ca32c76dea88a777b39bcf3cac5fdbd6"),
+                previous_signature: Vec::new(),
+            };
+
+            assert_error(
+                verify_beacon(&SchemeID::Bn254UnchainedOnG1, &public_key, beacon),
+                VerificationError::InvalidRandomness,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod scheme_id_test {
+    use crate::verify::{BLSGroup, SchemeID, UnknownSchemeError};
+
+    #[test]
+    fn every_scheme_round_trips_through_its_name() {
+        assert_eq!(SchemeID::all().len(), 4);
+        for scheme_id in SchemeID::all() {
+            assert_eq!(scheme_id.to_string().parse(), Ok(scheme_id.clone()));
+            #[cfg(feature = "serde")]
+            assert_eq!(
+                serde_json::to_string(scheme_id).unwrap(),
+                format!("\"{scheme_id}\"")
+            );
+        }
+        assert_eq!(
+            SchemeID::UnchainedOnG1RFC9380.to_string(),
+            "bls-unchained-g1-rfc9380"
+        );
+    }
+
+    #[test]
+    fn schemes_report_their_groups() {
+        let chained: Vec<_> = SchemeID::all().iter().map(SchemeID::is_chained).collect();
+        assert_eq!(chained, [true, false, false, false]);
+
+        assert_eq!(SchemeID::PedersenBlsChained.key_group(), BLSGroup::G1);
+        assert_eq!(SchemeID::PedersenBlsUnchained.key_group(), BLSGroup::G1);
+        assert_eq!(SchemeID::UnchainedOnG1RFC9380.key_group(), BLSGroup::G2);
+        assert_eq!(
+            SchemeID::UnchainedOnG1RFC9380.signature_group(),
+            BLSGroup::G1
+        );
+        assert_eq!(SchemeID::Bn254UnchainedOnG1.signature_group(), BLSGroup::G1);
+    }
+
+    #[test]
+    fn unknown_names_are_rejected() {
+        assert_eq!(
+            "bls-unknown".parse::<SchemeID>(),
+            Err(UnknownSchemeError("bls-unknown".to_string()))
+        );
+        assert_eq!(
+            "Pedersen-BLS-Chained".parse::<SchemeID>(),
+            Err(UnknownSchemeError("Pedersen-BLS-Chained".to_string()))
+        );
+    }
+}
+
+#[cfg(test)]
+mod beacon_test {
+    use crate::verify::{Beacon, VerificationError};
+    use crate::DrandClientError;
+    use std::time::Duration;
+
+    #[test]
+    fn age_is_measured_from_the_scheduled_time() {
+        let chain_info = crate::chains::MAINNET.to_chain_info();
+        let beacon = Beacon {
+            round_number: 3,
+            randomness: Vec::new(),
+            signature: Vec::new(),
+            previous_signature: Vec::new(),
+        };
+        let scheduled = crate::time_for_round(&chain_info, 3).unwrap();
+
+        assert_eq!(
+            beacon.age_at(&chain_info, scheduled + Duration::from_secs(7)),
+            Ok(Duration::from_secs(7))
+        );
+        assert_eq!(beacon.age_at(&chain_info, scheduled), Ok(Duration::ZERO));
+        assert_eq!(
+            beacon.age_at(&chain_info, scheduled - Duration::from_secs(1)),
+            Err(DrandClientError::UnexpectedError)
+        );
+        assert!(beacon.age(&chain_info).unwrap() > Duration::from_secs(86400 * 365));
+    }
+
+    #[test]
+    fn randomness_must_be_32_bytes_for_an_array() {
+        let mut beacon = Beacon {
+            round_number: 1,
+            randomness: (0..32).collect(),
+            signature: vec![1; 96],
+            previous_signature: Vec::new(),
+        };
+        let expected: [u8; 32] = core::array::from_fn(|i| i as u8);
+        assert_eq!(beacon.randomness_array(), Ok(expected));
+        assert_eq!(beacon.signature_ref(), &[1; 96]);
+        assert!(beacon.previous_signature_ref().is_empty());
+
+        beacon.randomness.pop();
+        assert_eq!(
+            beacon.randomness_array(),
+            Err(VerificationError::InvalidRandomness)
+        );
+    }
+}
+
+#[cfg(all(test, not(any(feature = "energon", feature = "verify-slim"))))]
+mod fetch_only_test {
+    use crate::verify::{verify_beacon, Beacon, SchemeID, VerificationError};
+    use sha2::{Digest, Sha256};
+
+    #[test]
+    fn verification_is_disabled() {
+        let signature = vec![1; 96];
+        let beacon = Beacon {
+            round_number: 1,
+            randomness: Sha256::digest(&signature).to_vec(),
+            signature,
+            previous_signature: Vec::new(),
+        };
+
+        assert_eq!(
+            verify_beacon(&SchemeID::PedersenBlsUnchained, &[1; 48], beacon),
+            Err(VerificationError::VerificationDisabled)
+        );
+    }
 }
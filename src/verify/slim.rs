@@ -0,0 +1,407 @@
+//! # slim
+//!
+//! signature verification for the BLS12-381 schemes implemented directly on `bls12_381`, for
+//! builds that leave out `energon` (`default-features = false, features = ["verify-slim"]`).
+//! The same checks are made in the same order as the default path, so both accept and reject
+//! the same beacons. `bls-bn254-unchained-on-g1` is not supported and always fails with
+//! `UnsupportedScheme`.
+//!
+//! it also hosts `verify_beacons_batch`, which needs `bls12_381`'s multi-Miller loop and so
+//! is only available with this feature
+//!
+
+use crate::verify::{verify_beacon_ref, verify_randomness, Beacon, SchemeID, VerificationError};
+use bls12_381::hash_to_curve::{ExpandMsgXmd, HashToCurve};
+use bls12_381::{
+    multi_miller_loop, pairing, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt,
+    Scalar,
+};
+use sha2::{Digest, Sha256};
+
+const G1_DST: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+const G2_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+
+/// verify the signature of a beacon. Unlike `verify_beacon`, the randomness is not checked.
+pub fn verify_signature(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    beacon: &Beacon,
+) -> Result<(), VerificationError> {
+    if beacon.signature.is_empty() {
+        return Err(VerificationError::EmptySignature);
+    }
+
+    let round = beacon.round_number.to_be_bytes();
+    match scheme_id {
+        SchemeID::PedersenBlsChained => {
+            if beacon.previous_signature.is_empty() {
+                return Err(VerificationError::ChainedBeaconNeedsPreviousSignature);
+            }
+            let message = Sha256::digest([beacon.previous_signature.as_slice(), &round].concat());
+            verify_on_g2(public_key, &beacon.signature, &message)
+        }
+        SchemeID::PedersenBlsUnchained => {
+            verify_on_g2(public_key, &beacon.signature, &Sha256::digest(round))
+        }
+        SchemeID::UnchainedOnG1RFC9380 => {
+            verify_on_g1(public_key, &beacon.signature, &Sha256::digest(round))
+        }
+        SchemeID::Bn254UnchainedOnG1 => Err(VerificationError::UnsupportedScheme),
+    }
+}
+
+// public keys on G1, signatures on G2
+fn verify_on_g2(
+    public_key: &[u8],
+    signature: &[u8],
+    message: &[u8],
+) -> Result<(), VerificationError> {
+    let signature = <&[u8; 96]>::try_from(signature)
+        .ok()
+        .and_then(|bytes| Option::<G2Affine>::from(G2Affine::from_compressed(bytes)))
+        .ok_or(VerificationError::SignatureFailedVerification)?;
+    let public_key = <&[u8; 48]>::try_from(public_key)
+        .ok()
+        .and_then(|bytes| Option::<G1Affine>::from(G1Affine::from_compressed(bytes)))
+        .ok_or(VerificationError::InvalidPublicKey)?;
+    if bool::from(public_key.is_identity()) {
+        return Err(VerificationError::InvalidPublicKey);
+    }
+
+    let hashed = <G2Projective as HashToCurve<ExpandMsgXmd<sha2_09::Sha256>>>::hash_to_curve(
+        message, G2_DST,
+    );
+    let hashed = G2Affine::from(hashed);
+    if pairing(&public_key, &hashed) != pairing(&G1Affine::generator(), &signature) {
+        return Err(VerificationError::SignatureFailedVerification);
+    }
+    Ok(())
+}
+
+// public keys on G2, signatures on G1
+fn verify_on_g1(
+    public_key: &[u8],
+    signature: &[u8],
+    message: &[u8],
+) -> Result<(), VerificationError> {
+    let signature = <&[u8; 48]>::try_from(signature)
+        .ok()
+        .and_then(|bytes| Option::<G1Affine>::from(G1Affine::from_compressed(bytes)))
+        .ok_or(VerificationError::SignatureFailedVerification)?;
+    let public_key = <&[u8; 96]>::try_from(public_key)
+        .ok()
+        .and_then(|bytes| Option::<G2Affine>::from(G2Affine::from_compressed(bytes)))
+        .ok_or(VerificationError::InvalidPublicKey)?;
+    if bool::from(public_key.is_identity()) {
+        return Err(VerificationError::InvalidPublicKey);
+    }
+
+    let hashed = <G1Projective as HashToCurve<ExpandMsgXmd<sha2_09::Sha256>>>::hash_to_curve(
+        message, G1_DST,
+    );
+    let hashed = G1Affine::from(hashed);
+    if pairing(&hashed, &public_key) != pairing(&signature, &G2Affine::generator()) {
+        return Err(VerificationError::SignatureFailedVerification);
+    }
+    Ok(())
+}
+
+/// verify many beacons of one chain at once, e.g. a downloaded range of history. A random
+/// linear combination of the signatures is checked with two pairings in a single
+/// multi-Miller loop, instead of two pairings per beacon. If the batch fails, the beacons are
+/// verified one by one so the error is the same `verify_beacon` would give for the first bad
+/// beacon. A single beacon is verified with `verify_beacon` directly.
+pub fn verify_beacons_batch(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    beacons: &[Beacon],
+) -> Result<(), VerificationError> {
+    if beacons.len() > 1 && verify_batch(scheme_id, public_key, beacons).is_ok() {
+        return Ok(());
+    }
+    for beacon in beacons {
+        verify_beacon_ref(scheme_id, public_key, beacon)?;
+    }
+    match beacons.len() {
+        0 | 1 => Ok(()),
+        // every beacon verifies on its own, so the batch can't have failed
+        _ => Err(VerificationError::SignatureFailedVerification),
+    }
+}
+
+// the public key is the same for every beacon, so `e(pk, H(m_i)) == e(g, s_i)` for each `i`
+// combines into `e(pk, sum r_i H(m_i)) == e(g, sum r_i s_i)`, or the mirror image for
+// signatures on G1
+fn verify_batch(
+    scheme_id: &SchemeID,
+    public_key: &[u8],
+    beacons: &[Beacon],
+) -> Result<(), VerificationError> {
+    let coefficients = coefficients(public_key, beacons);
+    let holds = match scheme_id {
+        SchemeID::PedersenBlsChained | SchemeID::PedersenBlsUnchained => {
+            let public_key = g1_public_key(public_key)?;
+            let mut signatures = G2Projective::identity();
+            let mut messages = G2Projective::identity();
+            for (beacon, coefficient) in beacons.iter().zip(&coefficients) {
+                verify_randomness(beacon)?;
+                signatures += g2_signature(&beacon.signature)? * coefficient;
+                messages += hash_to_g2(&message(scheme_id, beacon)?) * coefficient;
+            }
+            let terms = [
+                (&public_key, &G2Prepared::from(G2Affine::from(messages))),
+                (
+                    &-G1Affine::generator(),
+                    &G2Prepared::from(G2Affine::from(signatures)),
+                ),
+            ];
+            multi_miller_loop(&terms).final_exponentiation() == Gt::identity()
+        }
+        SchemeID::UnchainedOnG1RFC9380 => {
+            let public_key = G2Prepared::from(g2_public_key(public_key)?);
+            let mut signatures = G1Projective::identity();
+            let mut messages = G1Projective::identity();
+            for (beacon, coefficient) in beacons.iter().zip(&coefficients) {
+                verify_randomness(beacon)?;
+                signatures += g1_signature(&beacon.signature)? * coefficient;
+                messages += hash_to_g1(&message(scheme_id, beacon)?) * coefficient;
+            }
+            let generator = G2Prepared::from(G2Affine::generator());
+            let terms = [
+                (&G1Affine::from(messages), &public_key),
+                (&-G1Affine::from(signatures), &generator),
+            ];
+            multi_miller_loop(&terms).final_exponentiation() == Gt::identity()
+        }
+        SchemeID::Bn254UnchainedOnG1 => return Err(VerificationError::UnsupportedScheme),
+    };
+    match holds {
+        true => Ok(()),
+        false => Err(VerificationError::SignatureFailedVerification),
+    }
+}
+
+// 128-bit coefficients derived from the whole batch, so they can't be known before the
+// signatures are chosen
+fn coefficients(public_key: &[u8], beacons: &[Beacon]) -> Vec<Scalar> {
+    let mut seed = Sha256::new();
+    seed.update(public_key);
+    for beacon in beacons {
+        seed.update(beacon.round_number.to_be_bytes());
+        seed.update(Sha256::digest(&beacon.signature));
+        seed.update(Sha256::digest(&beacon.previous_signature));
+    }
+    let seed = seed.finalize();
+
+    (0..beacons.len() as u64)
+        .map(|i| {
+            let digest = Sha256::digest([seed.as_slice(), &i.to_be_bytes()].concat());
+            let low = u64::from_be_bytes(digest[..8].try_into().unwrap());
+            let high = u64::from_be_bytes(digest[8..16].try_into().unwrap());
+            Scalar::from_raw([low, high, 0, 0])
+        })
+        .collect()
+}
+
+// the digest a beacon's signature signs, after the same checks as `verify_signature`
+fn message(scheme_id: &SchemeID, beacon: &Beacon) -> Result<Vec<u8>, VerificationError> {
+    if beacon.signature.is_empty() {
+        return Err(VerificationError::EmptySignature);
+    }
+    let round = beacon.round_number.to_be_bytes();
+    if !scheme_id.is_chained() {
+        return Ok(Sha256::digest(round).to_vec());
+    }
+    if beacon.previous_signature.is_empty() {
+        return Err(VerificationError::ChainedBeaconNeedsPreviousSignature);
+    }
+    Ok(Sha256::digest([beacon.previous_signature.as_slice(), &round].concat()).to_vec())
+}
+
+fn g1_public_key(public_key: &[u8]) -> Result<G1Affine, VerificationError> {
+    <&[u8; 48]>::try_from(public_key)
+        .ok()
+        .and_then(|bytes| Option::<G1Affine>::from(G1Affine::from_compressed(bytes)))
+        .filter(|public_key| !bool::from(public_key.is_identity()))
+        .ok_or(VerificationError::InvalidPublicKey)
+}
+
+fn g2_public_key(public_key: &[u8]) -> Result<G2Affine, VerificationError> {
+    <&[u8; 96]>::try_from(public_key)
+        .ok()
+        .and_then(|bytes| Option::<G2Affine>::from(G2Affine::from_compressed(bytes)))
+        .filter(|public_key| !bool::from(public_key.is_identity()))
+        .ok_or(VerificationError::InvalidPublicKey)
+}
+
+fn g1_signature(signature: &[u8]) -> Result<G1Affine, VerificationError> {
+    <&[u8; 48]>::try_from(signature)
+        .ok()
+        .and_then(|bytes| Option::<G1Affine>::from(G1Affine::from_compressed(bytes)))
+        .ok_or(VerificationError::SignatureFailedVerification)
+}
+
+fn g2_signature(signature: &[u8]) -> Result<G2Affine, VerificationError> {
+    <&[u8; 96]>::try_from(signature)
+        .ok()
+        .and_then(|bytes| Option::<G2Affine>::from(G2Affine::from_compressed(bytes)))
+        .ok_or(VerificationError::SignatureFailedVerification)
+}
+
+fn hash_to_g1(message: &[u8]) -> G1Projective {
+    <G1Projective as HashToCurve<ExpandMsgXmd<sha2_09::Sha256>>>::hash_to_curve(message, G1_DST)
+}
+
+fn hash_to_g2(message: &[u8]) -> G2Projective {
+    <G2Projective as HashToCurve<ExpandMsgXmd<sha2_09::Sha256>>>::hash_to_curve(message, G2_DST)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::testkit::TestChain;
+    use crate::verify::slim::{verify_beacons_batch, verify_signature};
+    use crate::verify::{Beacon, SchemeID, VerificationError};
+    use bls12_381::{G2Affine, G2Projective};
+    use sha2::{Digest, Sha256};
+
+    const CHAINED_KEY: &str = "88a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb";
+    const CHAINED_SIGNATURE: &str = "88ccd9a91946bc0bbef2c6c60a09bbf4a247b1d2059522449aa1a35758feddfad85efe818bbde3e1e4ab0c852d96e65f0b1f97f239bf3fc918860ea846cbb500fcf7c9d0dd3d851320374460b5fc596b8cfd629f4c07c7507c259bf9beca850a";
+    const CHAINED_PREVIOUS: &str = "a2237ee39a1a6569cb8e02c6e979c07efe1f30be0ac501436bd325015f1cd6129dc56fd60efcdf9158d74ebfa34bfcbd17803dbca6d2ae8bc3a968e4dc582f8710c69de80b2e649663fef5742d22fff7d1619b75d5f222e8c9b8840bc2044bce";
+    const UNCHAINED_KEY: &str = "8d91ae0f4e3cd277cfc46aba26680232b0d5bb4444602cdb23442d62e17f43cdffb1104909e535430c10a6a1ce680a65";
+    const UNCHAINED_SIGNATURE: &str = "94da96b5b985a22a3d99fa3051a42feb4da9218763f6c836fca3770292dbf4b01f5d378859a113960548d167eaa144250a2c8e34c51c5270152ac2bc7a52632236f746545e0fae52f69068c017745204240d19dae2b4d038cef3c6047fcd6539";
+    const G1_KEY: &str = "83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a";
+    const G1_SIGNATURE: &str = "b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39";
+
+    #[test]
+    fn slim_path_matches_fixtures() {
+        use SchemeID::*;
+        use VerificationError::*;
+
+        let g1_infinity = infinity(48);
+        let g2_infinity = infinity(96);
+        let cases = [
+            // pedersen-bls-chained
+            (PedersenBlsChained, CHAINED_KEY, 397089, CHAINED_SIGNATURE, CHAINED_PREVIOUS, Ok(())),
+            (PedersenBlsChained, CHAINED_KEY, 1, CHAINED_SIGNATURE, CHAINED_PREVIOUS, Err(SignatureFailedVerification)),
+            (PedersenBlsChained, CHAINED_KEY, 397089, CHAINED_SIGNATURE, "", Err(ChainedBeaconNeedsPreviousSignature)),
+            (PedersenBlsChained, CHAINED_KEY, 397089, "", CHAINED_PREVIOUS, Err(EmptySignature)),
+            (PedersenBlsChained, "78a8227b75dba145599d894d33eebde3b36fef900d456ae2cc4388867adb4769c40359f783750a41b4d17e40f578bfdb", 397089, CHAINED_SIGNATURE, CHAINED_PREVIOUS, Err(InvalidPublicKey)),
+            (PedersenBlsChained, "", 397089, CHAINED_SIGNATURE, CHAINED_PREVIOUS, Err(InvalidPublicKey)),
+            (PedersenBlsChained, g1_infinity.as_str(), 397089, CHAINED_SIGNATURE, CHAINED_PREVIOUS, Err(InvalidPublicKey)),
+            // pedersen-bls-unchained
+            (PedersenBlsUnchained, UNCHAINED_KEY, 397092, UNCHAINED_SIGNATURE, "", Ok(())),
+            (PedersenBlsUnchained, UNCHAINED_KEY, 397092, UNCHAINED_SIGNATURE, UNCHAINED_SIGNATURE, Ok(())),
+            (PedersenBlsUnchained, UNCHAINED_KEY, 1, UNCHAINED_SIGNATURE, "", Err(SignatureFailedVerification)),
+            (PedersenBlsUnchained, CHAINED_KEY, 397092, UNCHAINED_SIGNATURE, "", Err(SignatureFailedVerification)),
+            (PedersenBlsUnchained, g1_infinity.as_str(), 397092, UNCHAINED_SIGNATURE, "", Err(InvalidPublicKey)),
+            // bls-unchained-g1-rfc9380
+            (UnchainedOnG1RFC9380, G1_KEY, 1000, G1_SIGNATURE, "", Ok(())),
+            (UnchainedOnG1RFC9380, G1_KEY, 1, G1_SIGNATURE, "", Err(SignatureFailedVerification)),
+            (UnchainedOnG1RFC9380, G1_KEY, 1000, "a44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39", "", Err(SignatureFailedVerification)),
+            (UnchainedOnG1RFC9380, "", 1000, G1_SIGNATURE, "", Err(InvalidPublicKey)),
+            (UnchainedOnG1RFC9380, g2_infinity.as_str(), 1000, G1_SIGNATURE, "", Err(InvalidPublicKey)),
+            // signatures of the wrong size for the scheme
+            (PedersenBlsUnchained, UNCHAINED_KEY, 1000, G1_SIGNATURE, "", Err(SignatureFailedVerification)),
+            (UnchainedOnG1RFC9380, G1_KEY, 397092, UNCHAINED_SIGNATURE, "", Err(SignatureFailedVerification)),
+        ];
+
+        for (scheme_id, public_key, round_number, signature, previous_signature, expected) in cases
+        {
+            let public_key = hex::decode(public_key).unwrap();
+            let signature = hex::decode(signature).unwrap();
+            let beacon = Beacon {
+                round_number,
+                randomness: Sha256::digest(&signature).to_vec(),
+                signature,
+                previous_signature: hex::decode(previous_signature).unwrap(),
+            };
+
+            assert_eq!(
+                verify_signature(&scheme_id, &public_key, &beacon),
+                expected,
+                "{scheme_id:?} round {round_number}"
+            );
+            // both paths must agree on every fixture
+            #[cfg(feature = "energon")]
+            assert_eq!(
+                crate::verify::verify_beacon(&scheme_id, &public_key, beacon).map(|_| ()),
+                expected,
+                "energon: {scheme_id:?} round {round_number}"
+            );
+        }
+    }
+
+    #[test]
+    fn bn254_is_unsupported() {
+        let beacon = Beacon {
+            round_number: 1000,
+            randomness: Vec::new(),
+            signature: vec![1; 64],
+            previous_signature: Vec::new(),
+        };
+
+        assert_eq!(
+            verify_signature(&SchemeID::Bn254UnchainedOnG1, &[1; 128], &beacon),
+            Err(VerificationError::UnsupportedScheme)
+        );
+    }
+
+    #[test]
+    fn batches_verify_like_single_beacons() {
+        for scheme_id in [
+            SchemeID::PedersenBlsChained,
+            SchemeID::PedersenBlsUnchained,
+            SchemeID::UnchainedOnG1RFC9380,
+        ] {
+            let chain = TestChain::new(scheme_id.clone(), 30, 1595431050);
+            let public_key = &chain.chain_info().public_key;
+            let mut beacons: Vec<_> = (1..=8).map(|round| chain.sign_round(round)).collect();
+            assert_eq!(
+                verify_beacons_batch(&scheme_id, public_key, &beacons),
+                Ok(())
+            );
+            assert_eq!(verify_beacons_batch(&scheme_id, public_key, &[]), Ok(()));
+
+            beacons[5].round_number = 9;
+            assert_eq!(
+                verify_beacons_batch(&scheme_id, public_key, &beacons),
+                Err(VerificationError::SignatureFailedVerification),
+                "{scheme_id:?}"
+            );
+            beacons[2].randomness[0] ^= 1;
+            assert_eq!(
+                verify_beacons_batch(&scheme_id, public_key, &beacons[..4]),
+                Err(VerificationError::InvalidRandomness),
+                "{scheme_id:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn signatures_that_cancel_out_fail_the_batch() {
+        let chain = TestChain::new(SchemeID::PedersenBlsUnchained, 30, 1595431050);
+        let public_key = &chain.chain_info().public_key;
+        let mut beacons = [chain.sign_round(1), chain.sign_round(2)];
+
+        // the sum of the signatures is unchanged, so an unweighted batch would pass
+        let offset = G2Projective::generator();
+        for (beacon, offset) in beacons.iter_mut().zip([offset, -offset]) {
+            let signature =
+                G2Affine::from_compressed(&beacon.signature.clone().try_into().unwrap()).unwrap();
+            beacon.signature = G2Affine::from(signature + offset).to_compressed().to_vec();
+            beacon.randomness = Sha256::digest(&beacon.signature).to_vec();
+        }
+
+        assert_eq!(
+            verify_beacons_batch(&SchemeID::PedersenBlsUnchained, public_key, &beacons),
+            Err(VerificationError::SignatureFailedVerification)
+        );
+    }
+
+    // the compressed point at infinity: the compression and infinity flags, then zeroes
+    fn infinity(len: usize) -> String {
+        let mut bytes = vec![0u8; len];
+        bytes[0] = 0xc0;
+        hex::encode(bytes)
+    }
+}
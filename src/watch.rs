@@ -0,0 +1,695 @@
+//! # watch
+//!
+//! a blocking watcher yielding every round as the chain produces it. Each time it wakes, the
+//! watcher compares the wall clock with the round it was waiting for, so a machine that slept
+//! or had its clock jumped forward is noticed instead of being mistaken for a late round, and
+//! a `GapPolicy` decides what happens to the rounds missed in between. A clock that jumps
+//! backwards only makes the watcher wait longer. Rather than polling, each round is fetched
+//! once shortly after it is due, with a short retry burst if it isn't published yet, as set
+//! by a `PollSchedule`.
+//!
+
+pub use crate::clock::{Clock, SystemClock};
+use crate::verify::{Beacon, VerifiedBeacon};
+use crate::{
+    round_for_time, time_for_round, DrandClient, DrandClientError, Transport, TransportError,
+};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+
+/// what the watcher does when it wakes more than the gap threshold after the round it was
+/// waiting for
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum GapPolicy {
+    /// emit every missed round in order, at most one per `min_interval` until caught up
+    CatchUp { min_interval: Duration },
+    /// emit only the newest round and carry on from there
+    SkipToLatest,
+    /// return `WatchError::FellBehind` once, then carry on from the newest round
+    Error,
+}
+
+impl Default for GapPolicy {
+    fn default() -> Self {
+        GapPolicy::CatchUp {
+            min_interval: Duration::from_millis(250),
+        }
+    }
+}
+
+/// when a watcher fetches each round: once, `initial_offset` after the round is scheduled,
+/// then up to `retry_attempts` more times `retry_interval` apart while the relay isn't
+/// serving it yet. Together these bound the requests per round, instead of polling until the
+/// round appears.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PollSchedule {
+    /// how long after its scheduled time to fetch a round. `None` uses the client's publish
+    /// delay estimate, or no offset until there is one.
+    pub initial_offset: Option<Duration>,
+    /// how many more fetches to make if the first doesn't return the round
+    pub retry_attempts: usize,
+    pub retry_interval: Duration,
+}
+
+impl Default for PollSchedule {
+    fn default() -> Self {
+        PollSchedule {
+            initial_offset: None,
+            retry_attempts: 3,
+            retry_interval: Duration::from_millis(500),
+        }
+    }
+}
+
+impl PollSchedule {
+    /// the offset to use given the current publish delay estimate
+    pub(crate) fn offset(&self, publish_delay: Option<Duration>) -> Duration {
+        self.initial_offset.or(publish_delay).unwrap_or_default()
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum WatchError {
+    #[error(transparent)]
+    Client(#[from] DrandClientError),
+    #[error("the watcher fell behind from round {expected_round} to round {current_round}")]
+    FellBehind {
+        expected_round: u64,
+        current_round: u64,
+    },
+}
+
+/// yields each round of the client's chain in turn, starting with the current one. Each round
+/// is fetched according to a `PollSchedule`. Failed fetches are returned without advancing,
+/// so the next call retries the same round.
+pub struct Watcher<'c, T: Transport + Send, C: Clock = Arc<dyn Clock + Send + Sync>> {
+    client: &'c DrandClient<T>,
+    clock: C,
+    poll_schedule: PollSchedule,
+    gap_policy: GapPolicy,
+    gap_threshold: Option<Duration>,
+    next_round: Option<u64>,
+    last_emitted_at: Option<SystemTime>,
+}
+
+impl<'c, T: Transport + Send> Watcher<'c, T> {
+    /// a watcher on the client's clock, like `DrandClient::wait_for_round`
+    pub fn new(client: &'c DrandClient<T>) -> Self {
+        Watcher::with_clock(client, client.clock.clone())
+    }
+}
+
+impl<'c, T: Transport + Send, C: Clock> Watcher<'c, T, C> {
+    pub fn with_clock(client: &'c DrandClient<T>, clock: C) -> Self {
+        Watcher {
+            client,
+            clock,
+            poll_schedule: PollSchedule::default(),
+            gap_policy: GapPolicy::default(),
+            gap_threshold: None,
+            next_round: None,
+            last_emitted_at: None,
+        }
+    }
+
+    /// when to fetch each round, and how often to retry it. Defaults to
+    /// `PollSchedule::default()`.
+    pub fn with_poll_schedule(mut self, poll_schedule: PollSchedule) -> Self {
+        self.poll_schedule = poll_schedule;
+        self
+    }
+
+    /// how to handle rounds missed while the watcher wasn't running. Defaults to catching up
+    /// at up to four rounds a second.
+    pub fn with_gap_policy(mut self, gap_policy: GapPolicy) -> Self {
+        self.gap_policy = gap_policy;
+        self
+    }
+
+    /// how far behind the round it is waiting for the watcher may wake before the gap policy
+    /// applies. Defaults to two periods, so a slow relay or a short stall isn't a gap.
+    pub fn with_gap_threshold(mut self, gap_threshold: Duration) -> Self {
+        self.gap_threshold = Some(gap_threshold);
+        self
+    }
+
+    /// block until the next round is available and return its verified beacon
+    pub fn next_beacon(&mut self) -> Result<VerifiedBeacon, WatchError> {
+        let info = self.client.chain_info();
+        let gap_threshold = self.gap_threshold.unwrap_or(2 * info.period_as_duration());
+
+        loop {
+            let now = self.clock.now();
+            let current_round = round_for_time(info, now)?;
+            let next_round = *self.next_round.get_or_insert(current_round);
+
+            if next_round > current_round {
+                // the relay publishes a little after the scheduled time
+                let offset = self
+                    .poll_schedule
+                    .offset(self.client.publish_delay_estimate());
+                let wait = time_for_round(info, next_round)?
+                    .duration_since(now)
+                    .unwrap_or_default();
+                self.clock.sleep(wait + offset);
+                continue;
+            }
+
+            let lag = now
+                .duration_since(time_for_round(info, next_round)?)
+                .unwrap_or_default();
+            if lag >= gap_threshold {
+                match self.gap_policy {
+                    GapPolicy::CatchUp { min_interval } => {
+                        let since_last = self
+                            .last_emitted_at
+                            .and_then(|at| now.duration_since(at).ok())
+                            .unwrap_or(min_interval);
+                        if since_last < min_interval {
+                            self.clock.sleep(min_interval - since_last);
+                        }
+                    }
+                    GapPolicy::SkipToLatest => self.next_round = Some(current_round),
+                    GapPolicy::Error => {
+                        self.next_round = Some(current_round);
+                        return Err(WatchError::FellBehind {
+                            expected_round: next_round,
+                            current_round,
+                        });
+                    }
+                }
+            }
+
+            let round = self.next_round.unwrap_or(current_round);
+            let beacon = self.fetch_round(round)?;
+            let fetched_at = self.clock.now();
+            self.next_round = Some(round + 1);
+            self.last_emitted_at = Some(fetched_at);
+            self.client.observe_publish_delay(round, fetched_at);
+            return Ok(beacon);
+        }
+    }
+
+    // the first fetch and the retry burst of the poll schedule. A round that isn't published
+    // yet and an unreachable relay look the same here, so both are retried. The round cache
+    // is bypassed, as the time of the fetch is recorded as when the round appeared.
+    fn fetch_round(&self, round: u64) -> Result<VerifiedBeacon, DrandClientError> {
+        let mut retries = 0;
+        loop {
+            match self.client.fetch_round(round) {
+                Err(DrandClientError::NotResponding)
+                    if retries < self.poll_schedule.retry_attempts =>
+                {
+                    retries += 1;
+                    self.clock.sleep(self.poll_schedule.retry_interval);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// an endless iterator of `next_beacon` results
+impl<T: Transport + Send, C: Clock> Iterator for Watcher<'_, T, C> {
+    type Item = Result<VerifiedBeacon, WatchError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_beacon())
+    }
+}
+
+/// how long `DrandClient::wait_for_round` keeps polling past a round's scheduled time before
+/// giving up
+pub const DEFAULT_MAX_WAIT: Duration = Duration::from_secs(60);
+
+// the delay between the first two polls for a round that is due but not yet published,
+// doubled after each poll up to a period
+const FIRST_POLL_DELAY: Duration = Duration::from_millis(250);
+
+impl<T: Transport + Send> DrandClient<T> {
+    /// block until `round` is published and return its verified beacon, like
+    /// `wait_for_round_with` with `DEFAULT_MAX_WAIT` and the client's clock
+    pub fn wait_for_round(&self, round: u64) -> Result<VerifiedBeacon, DrandClientError> {
+        self.wait_for_round_with(round, DEFAULT_MAX_WAIT, &*self.clock)
+    }
+
+    /// sleep on `clock` until `round` is scheduled, then poll with backoff until the relay
+    /// serves it. A round in the past is fetched straight away. While the relay doesn't have
+    /// the round yet polling continues, failing with `RoundNotYetAvailable` once `max_wait`
+    /// past the scheduled time has passed; any other failure, like an unreachable relay, is
+    /// returned at once.
+    pub fn wait_for_round_with<C: Clock>(
+        &self,
+        round: u64,
+        max_wait: Duration,
+        clock: C,
+    ) -> Result<VerifiedBeacon, DrandClientError> {
+        let scheduled = time_for_round(&self.chain_info, round)?;
+        let deadline = scheduled
+            .checked_add(max_wait)
+            .ok_or(DrandClientError::UnexpectedError)?;
+        let url = self.url_strategy.round_url(&self.base_url, round);
+        if let Ok(wait) = scheduled.duration_since(clock.now()) {
+            clock.sleep(wait);
+        }
+
+        let mut delay = FIRST_POLL_DELAY;
+        loop {
+            let body = match self.fetch(&url) {
+                Ok(body) => body,
+                // the relay answered, it just doesn't have the round yet
+                Err(TransportError::NotFound) => match deadline.duration_since(clock.now()) {
+                    Ok(remaining) if !remaining.is_zero() => {
+                        clock.sleep(delay.min(remaining));
+                        delay = (delay * 2).min(self.chain_info.period_as_duration());
+                        continue;
+                    }
+                    _ => return Err(DrandClientError::RoundNotYetAvailable),
+                },
+                Err(e) => return Err(e.into()),
+            };
+
+            let beacon = serde_json::from_str::<Beacon>(&body)
+                .map_err(|_| DrandClientError::InvalidBeacon)?;
+            if beacon.round_number != round {
+                return Err(DrandClientError::InvalidBeacon);
+            }
+            return self.verify_fetched(beacon);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chain_info::ChainInfo;
+    use crate::testing::FixedClock;
+    use crate::testkit::{TestChain, TestTransport};
+    use crate::verify::SchemeID::PedersenBlsUnchained;
+    use crate::watch::{Clock, GapPolicy, PollSchedule, WatchError, Watcher};
+    use crate::{time_for_round, DrandClient, DrandClientError, Transport, TransportError};
+    use std::cell::{Cell, RefCell};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    const GENESIS: u64 = 1595431050;
+    const PERIOD: u64 = 30;
+
+    /// a clock that only moves when slept on or jumped
+    struct SimulatedClock {
+        now: Cell<SystemTime>,
+        sleeps: RefCell<Vec<Duration>>,
+    }
+
+    impl SimulatedClock {
+        /// a clock one second into `round`
+        fn at_round(round: u64) -> Self {
+            SimulatedClock {
+                now: Cell::new(
+                    UNIX_EPOCH + Duration::from_secs(GENESIS + (round - 1) * PERIOD + 1),
+                ),
+                sleeps: RefCell::new(Vec::new()),
+            }
+        }
+
+        /// move the clock without the watcher sleeping, like a suspended machine waking up
+        fn jump(&self, duration: Duration) {
+            self.now.set(self.now.get() + duration);
+        }
+    }
+
+    impl Clock for SimulatedClock {
+        fn now(&self) -> SystemTime {
+            self.now.get()
+        }
+
+        fn sleep(&self, duration: Duration) {
+            self.sleeps.borrow_mut().push(duration);
+            self.jump(duration);
+        }
+    }
+
+    #[test]
+    fn watchers_use_the_clock_of_their_client() {
+        let chain = TestChain::new(PedersenBlsUnchained, PERIOD as usize, GENESIS);
+        let clock = Arc::new(FixedClock::at_round(chain.chain_info(), 10).unwrap());
+        let client = DrandClient::new(
+            chain.transport(11),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        )
+        .with_clock(clock.clone());
+        let mut watcher = Watcher::new(&client);
+
+        assert_eq!(watcher.next_beacon().unwrap().round_number, 10);
+        assert_eq!(watcher.next_beacon().unwrap().round_number, 11);
+        assert!(clock.now() >= time_for_round(chain.chain_info(), 11).unwrap());
+    }
+
+    #[test]
+    fn consecutive_rounds_wait_a_period() {
+        let client = client();
+        let clock = SimulatedClock::at_round(10);
+        let mut watcher = Watcher::with_clock(&client, &clock);
+
+        let rounds: Vec<u64> = (0..3)
+            .map(|_| watcher.next_beacon().unwrap().round_number)
+            .collect();
+
+        assert_eq!(rounds, [10, 11, 12]);
+        assert_eq!(
+            *clock.sleeps.borrow(),
+            [Duration::from_secs(PERIOD - 1), Duration::from_secs(PERIOD)]
+        );
+    }
+
+    #[test]
+    fn catch_up_emits_every_missed_round_rate_limited() {
+        let client = client();
+        let clock = SimulatedClock::at_round(10);
+        let mut watcher =
+            Watcher::with_clock(&client, &clock).with_gap_policy(GapPolicy::CatchUp {
+                min_interval: Duration::from_secs(1),
+            });
+        watcher.next_beacon().unwrap();
+        clock.jump(Duration::from_secs(3600));
+
+        let rounds: Vec<u64> = (0..3)
+            .map(|_| watcher.next_beacon().unwrap().round_number)
+            .collect();
+
+        assert_eq!(rounds, [11, 12, 13]);
+        // the first missed round goes out at once, the rest one per `min_interval`
+        assert_eq!(
+            *clock.sleeps.borrow(),
+            [Duration::from_secs(1), Duration::from_secs(1)]
+        );
+    }
+
+    #[test]
+    fn skip_to_latest_emits_only_the_newest_round() {
+        let client = client();
+        let clock = SimulatedClock::at_round(10);
+        let mut watcher =
+            Watcher::with_clock(&client, &clock).with_gap_policy(GapPolicy::SkipToLatest);
+        watcher.next_beacon().unwrap();
+        clock.jump(Duration::from_secs(3600));
+
+        assert_eq!(watcher.next_beacon().unwrap().round_number, 130);
+        assert_eq!(watcher.next_beacon().unwrap().round_number, 131);
+    }
+
+    #[test]
+    fn error_policy_reports_the_gap_then_resumes_at_the_newest_round() {
+        let client = client();
+        let clock = SimulatedClock::at_round(10);
+        let mut watcher = Watcher::with_clock(&client, &clock).with_gap_policy(GapPolicy::Error);
+        watcher.next_beacon().unwrap();
+        clock.jump(Duration::from_secs(3600));
+
+        assert_eq!(
+            watcher.next_beacon(),
+            Err(WatchError::FellBehind {
+                expected_round: 11,
+                current_round: 130
+            })
+        );
+        assert_eq!(watcher.next_beacon().unwrap().round_number, 130);
+    }
+
+    #[test]
+    fn gaps_below_the_threshold_are_not_gaps() {
+        let client = client();
+        let clock = SimulatedClock::at_round(10);
+        let mut watcher = Watcher::with_clock(&client, &clock)
+            .with_gap_policy(GapPolicy::Error)
+            .with_gap_threshold(Duration::from_secs(5 * PERIOD));
+        watcher.next_beacon().unwrap();
+        clock.jump(Duration::from_secs(3 * PERIOD));
+
+        assert_eq!(watcher.next_beacon().unwrap().round_number, 11);
+
+        clock.jump(Duration::from_secs(10 * PERIOD));
+        assert!(matches!(
+            watcher.next_beacon(),
+            Err(WatchError::FellBehind { .. })
+        ));
+    }
+
+    #[test]
+    fn rounds_past_the_end_of_system_time_are_an_error() {
+        let genesis = i64::MAX as u64 - PERIOD;
+        let chain = TestChain::new(PedersenBlsUnchained, PERIOD as usize, genesis);
+        let client = DrandClient::new(
+            chain.transport(1),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+        let clock = SimulatedClock {
+            now: Cell::new(UNIX_EPOCH + Duration::from_secs(genesis + 1)),
+            sleeps: RefCell::new(Vec::new()),
+        };
+        let mut watcher = Watcher::with_clock(&client, &clock);
+
+        assert_eq!(watcher.next_beacon().unwrap().round_number, 1);
+        // round 2 would be scheduled a second after the last representable time
+        assert_eq!(
+            watcher.next_beacon(),
+            Err(WatchError::Client(DrandClientError::UnexpectedError))
+        );
+    }
+
+    #[test]
+    fn each_round_takes_one_or_two_requests() {
+        let chain = TestChain::new(PedersenBlsUnchained, PERIOD as usize, GENESIS);
+        let clock = Arc::new(FixedClock::at_round(chain.chain_info(), 10).unwrap());
+        clock.advance(Duration::from_secs(5));
+        let client = DrandClient::new(
+            Publishing::new(&chain, &clock, Duration::from_millis(300)),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+        let mut watcher = Watcher::with_clock(&client, clock.clone());
+
+        for round in 10..=20 {
+            assert_eq!(watcher.next_beacon().unwrap().round_number, round);
+            // after the first, every round arrives within a retry interval of being published
+            let published =
+                time_for_round(chain.chain_info(), round).unwrap() + Duration::from_millis(300);
+            assert!(round == 10 || clock.now() <= published + Duration::from_millis(500));
+        }
+
+        // round 11 is fetched before it is published and once more; from then on the publish
+        // delay estimate puts the first fetch after publication
+        assert_eq!(client.transport.requests_for(10), 1);
+        assert_eq!(client.transport.requests_for(11), 2);
+        for round in 12..=20 {
+            assert_eq!(client.transport.requests_for(round), 1, "round {round}");
+        }
+    }
+
+    #[test]
+    fn cached_rounds_are_fetched_again_to_sample_the_publish_delay() {
+        let chain = TestChain::new(PedersenBlsUnchained, PERIOD as usize, GENESIS);
+        let clock = Arc::new(FixedClock::at_round(chain.chain_info(), 11).unwrap());
+        clock.advance(Duration::from_secs(5));
+        let client = DrandClient::new(
+            Publishing::new(&chain, &clock, Duration::from_millis(300)),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        )
+        .with_round_cache(8);
+
+        // round 11 is already in the round cache when the watcher waits for it
+        client.randomness(11).unwrap();
+        clock.set(time_for_round(chain.chain_info(), 10).unwrap() + Duration::from_secs(5));
+        let mut watcher = Watcher::with_clock(&client, clock.clone());
+        assert_eq!(watcher.next_beacon().unwrap().round_number, 10);
+        assert_eq!(watcher.next_beacon().unwrap().round_number, 11);
+
+        // the relay is asked again, and the retry that found round 11 is the only sample
+        assert_eq!(client.transport.requests_for(11), 3);
+        assert_eq!(
+            client.publish_delay_estimate(),
+            Some(Duration::from_millis(500))
+        );
+    }
+
+    #[test]
+    fn the_initial_offset_and_retry_burst_are_configurable() {
+        let chain = TestChain::new(PedersenBlsUnchained, PERIOD as usize, GENESIS);
+        let clock = Arc::new(FixedClock::at_round(chain.chain_info(), 10).unwrap());
+        clock.advance(Duration::from_secs(5));
+        let client = DrandClient::new(
+            Publishing::new(&chain, &clock, Duration::from_secs(2)),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        );
+        let mut watcher =
+            Watcher::with_clock(&client, clock.clone()).with_poll_schedule(PollSchedule {
+                initial_offset: Some(Duration::from_secs(1)),
+                retry_attempts: 1,
+                retry_interval: Duration::from_secs(1),
+            });
+
+        watcher.next_beacon().unwrap();
+        assert_eq!(watcher.next_beacon().unwrap().round_number, 11);
+        assert_eq!(client.transport.requests_for(11), 2);
+
+        let no_retries = PollSchedule {
+            initial_offset: Some(Duration::ZERO),
+            retry_attempts: 0,
+            ..PollSchedule::default()
+        };
+        let mut watcher = watcher.with_poll_schedule(no_retries);
+        assert_eq!(
+            watcher.next_beacon(),
+            Err(WatchError::Client(DrandClientError::NotResponding))
+        );
+        assert_eq!(client.transport.requests_for(12), 1);
+    }
+
+    #[test]
+    fn waiting_sleeps_until_the_round_then_polls_until_it_is_published() {
+        let chain = TestChain::new(PedersenBlsUnchained, PERIOD as usize, GENESIS);
+        let transport = Unpublished {
+            inner: chain.transport(140),
+            misses: Cell::new(3),
+        };
+        let client = DrandClient::new(transport, "api.drand.sh", chain.chain_info().clone());
+        let clock = SimulatedClock::at_round(10);
+
+        assert_eq!(
+            client.wait_for_round_with(12, Duration::from_secs(60), &clock),
+            Ok(chain.verified_round(12))
+        );
+        assert_eq!(
+            *clock.sleeps.borrow(),
+            [
+                Duration::from_secs(2 * PERIOD - 1),
+                Duration::from_millis(250),
+                Duration::from_millis(500),
+                Duration::from_secs(1)
+            ]
+        );
+    }
+
+    #[test]
+    fn past_rounds_are_fetched_straight_away() {
+        let client = client();
+        let clock = SimulatedClock::at_round(10);
+
+        assert_eq!(
+            client
+                .wait_for_round_with(5, Duration::from_secs(60), &clock)
+                .unwrap()
+                .round_number,
+            5
+        );
+        assert!(clock.sleeps.borrow().is_empty());
+        assert_eq!(
+            client.wait_for_round_with(0, Duration::from_secs(60), &clock),
+            Err(DrandClientError::InvalidRound)
+        );
+    }
+
+    #[test]
+    fn waiting_gives_up_after_max_wait() {
+        let client = client();
+        // one second after round 141 is due, which the relay never publishes
+        let clock = SimulatedClock::at_round(141);
+
+        assert_eq!(
+            client.wait_for_round_with(141, Duration::from_secs(2), &clock),
+            Err(DrandClientError::RoundNotYetAvailable)
+        );
+        let waited: Duration = clock.sleeps.borrow().iter().sum();
+        assert_eq!(waited, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn network_failures_are_not_waited_out() {
+        struct Unreachable;
+
+        impl Transport for Unreachable {
+            fn fetch(&self, _url: &str) -> Result<String, TransportError> {
+                Err(TransportError::Unexpected { status_code: None })
+            }
+        }
+
+        let chain = TestChain::new(PedersenBlsUnchained, PERIOD as usize, GENESIS);
+        let client = DrandClient::new(Unreachable, "api.drand.sh", chain.chain_info().clone());
+        let clock = SimulatedClock::at_round(10);
+
+        assert_eq!(
+            client.wait_for_round_with(10, Duration::from_secs(60), &clock),
+            Err(DrandClientError::NotResponding)
+        );
+        assert!(clock.sleeps.borrow().is_empty());
+    }
+
+    // answers the first `misses` requests with `NotFound`, like a relay that hasn't published
+    // the round yet
+    struct Unpublished {
+        inner: TestTransport,
+        misses: Cell<usize>,
+    }
+
+    impl Transport for Unpublished {
+        fn fetch(&self, url: &str) -> Result<String, TransportError> {
+            match self.misses.get() {
+                0 => self.inner.fetch(url),
+                misses => {
+                    self.misses.set(misses - 1);
+                    Err(TransportError::NotFound)
+                }
+            }
+        }
+    }
+
+    // serves each round `delay` after its scheduled time by `clock`, counting the requests
+    struct Publishing {
+        inner: TestTransport,
+        chain_info: ChainInfo,
+        clock: Arc<FixedClock>,
+        delay: Duration,
+        rounds_requested: Mutex<Vec<u64>>,
+    }
+
+    impl Publishing {
+        fn new(chain: &TestChain, clock: &Arc<FixedClock>, delay: Duration) -> Self {
+            Publishing {
+                inner: chain.transport(140),
+                chain_info: chain.chain_info().clone(),
+                clock: clock.clone(),
+                delay,
+                rounds_requested: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn requests_for(&self, round: u64) -> usize {
+            let requested = self.rounds_requested.lock().unwrap();
+            requested.iter().filter(|r| **r == round).count()
+        }
+    }
+
+    impl Transport for Publishing {
+        fn fetch(&self, url: &str) -> Result<String, TransportError> {
+            let round: u64 = url.rsplit('/').next().unwrap().parse().unwrap();
+            self.rounds_requested.lock().unwrap().push(round);
+            if self.clock.now() < time_for_round(&self.chain_info, round).unwrap() + self.delay {
+                return Err(TransportError::NotFound);
+            }
+            self.inner.fetch(url)
+        }
+    }
+
+    fn client() -> DrandClient<TestTransport> {
+        let chain = TestChain::new(PedersenBlsUnchained, PERIOD as usize, GENESIS);
+        DrandClient::new(
+            chain.transport(140),
+            "api.drand.sh",
+            chain.chain_info().clone(),
+        )
+    }
+}
@@ -0,0 +1,55 @@
+//! builds without the `serde` feature keep the wire types and verification
+
+#![cfg(not(feature = "serde"))]
+
+use drand_client_rs::chain_info::{ChainInfo, ChainInfoMetadata};
+use drand_client_rs::verify::{verify_beacon, Beacon, SchemeID};
+
+const PUBLIC_KEY: &str = "83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a";
+
+fn quicknet() -> ChainInfo {
+    ChainInfo {
+        scheme_id: SchemeID::UnchainedOnG1RFC9380,
+        public_key: hex::decode(PUBLIC_KEY).unwrap(),
+        chain_hash: hex::decode("52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971")
+            .unwrap(),
+        group_hash: hex::decode("f477d5c89f21a17c863a7f937c6a6d15859414d2be09cd448d4279af331c5d3e")
+            .unwrap(),
+        genesis_time: 1692803367,
+        period_seconds: 3,
+        metadata: ChainInfoMetadata {
+            beacon_id: "quicknet".to_string(),
+        },
+    }
+}
+
+#[test]
+fn chain_info_hashes_without_serde() {
+    let info = quicknet();
+
+    assert_eq!(info.compute_chain_hash(), info.chain_hash);
+}
+
+#[test]
+fn scheme_ids_parse_without_serde() {
+    assert_eq!(
+        "bls-unchained-g1-rfc9380".parse(),
+        Ok(SchemeID::UnchainedOnG1RFC9380)
+    );
+    assert!("bls-unknown".parse::<SchemeID>().is_err());
+}
+
+#[cfg(any(feature = "energon", feature = "verify-slim"))]
+#[test]
+fn beacons_verify_without_serde() {
+    let info = quicknet();
+    let beacon = Beacon {
+        round_number: 1000,
+        randomness: hex::decode("fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd")
+            .unwrap(),
+        signature: hex::decode("b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39").unwrap(),
+        previous_signature: Vec::new(),
+    };
+
+    assert!(verify_beacon(&info.scheme_id, &info.public_key, beacon).is_ok());
+}
@@ -0,0 +1,9 @@
+// the expected compiler output is for the toolchain pinned in CI's trybuild job
+#[test]
+#[ignore = "run with the pinned toolchain: cargo test --test pin_chain -- --ignored"]
+fn pin_chain_checks_chain_info_at_compile_time() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/mainnet.rs");
+    t.compile_fail("tests/ui/hash_mismatch.rs");
+    t.compile_fail("tests/ui/invalid_json.rs");
+}
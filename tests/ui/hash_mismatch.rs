@@ -0,0 +1,6 @@
+use drand_client_rs::pin_chain;
+use drand_client_rs::pinned::PinnedChainInfo;
+
+const MAINNET: PinnedChainInfo = pin_chain!(file = "hash_mismatch.json");
+
+fn main() {}
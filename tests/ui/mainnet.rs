@@ -0,0 +1,20 @@
+use drand_client_rs::pin_chain;
+use drand_client_rs::pinned::PinnedChainInfo;
+
+const MAINNET: PinnedChainInfo = pin_chain!(
+    r#"{
+        "public_key": "868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31",
+        "period": 30,
+        "genesis_time": 1595431050,
+        "hash": "8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce",
+        "groupHash": "176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a",
+        "schemeID": "pedersen-bls-chained",
+        "metadata": { "beaconID": "default" }
+    }"#
+);
+
+fn main() {
+    let info = MAINNET.to_chain_info();
+    assert_eq!(info.genesis_time, 1595431050);
+    assert_eq!(info.compute_chain_hash(), info.chain_hash);
+}
@@ -0,0 +1,116 @@
+//! the chain-info and beacon flows over an in-memory `Transport`, the way a host without the
+//! HTTP transport, e.g. a `wasi:http` plugin on `wasm32-wasip2`, drives them. CI checks that
+//! `--no-default-features --features serde,verify-slim` builds for that target and runs these
+//! with the same features.
+
+#![cfg(feature = "serde")]
+
+use drand_client_rs::chains::QUICKNET;
+use drand_client_rs::url::{DefaultUrlStrategy, UrlStrategy};
+use drand_client_rs::verify::Beacon;
+use drand_client_rs::{fetch_chain_info, DrandClientError, Transport, TransportError};
+use std::collections::HashMap;
+
+const BASE_URL: &str =
+    "https://relay/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971";
+
+// quicknet's round 1000, as served by the relays
+const ROUND_1000: &str = r#"{"round":1000,"randomness":"fe290beca10872ef2fb164d2aa4442de4566183ec51c56ff3cd603d930e54fdd","signature":"b44679b9a59af2ec876b1a6b1ad52ea9b1615fc3982b19576350f93447cb1125e342b73a8dd2bacbe47e4b6b63ed5e39"}"#;
+
+// answers from a fixed set of URLs, like a host handing back `wasi:http` responses
+struct InMemoryTransport(HashMap<String, String>);
+
+impl InMemoryTransport {
+    fn quicknet() -> Self {
+        let info = serde_json::to_string(&QUICKNET.to_chain_info()).unwrap();
+        InMemoryTransport(HashMap::from([
+            (DefaultUrlStrategy.info_url(BASE_URL), info),
+            (
+                DefaultUrlStrategy.latest_url(BASE_URL),
+                ROUND_1000.to_string(),
+            ),
+            (
+                DefaultUrlStrategy.round_url(BASE_URL, 1000),
+                ROUND_1000.to_string(),
+            ),
+        ]))
+    }
+}
+
+impl Transport for InMemoryTransport {
+    fn fetch(&self, url: &str) -> Result<String, TransportError> {
+        self.0.get(url).cloned().ok_or(TransportError::NotFound)
+    }
+}
+
+#[test]
+fn urls_are_built_under_the_chain_base_url() {
+    assert_eq!(
+        DefaultUrlStrategy.info_url(BASE_URL),
+        format!("{BASE_URL}/info")
+    );
+    assert_eq!(
+        DefaultUrlStrategy.latest_url(BASE_URL),
+        format!("{BASE_URL}/public/latest")
+    );
+    assert_eq!(
+        DefaultUrlStrategy.round_url(BASE_URL, u64::MAX),
+        format!("{BASE_URL}/public/18446744073709551615")
+    );
+}
+
+#[test]
+fn chain_info_is_fetched_parsed_and_validated() -> Result<(), DrandClientError> {
+    let transport = InMemoryTransport::quicknet();
+
+    assert_eq!(
+        fetch_chain_info(&transport, BASE_URL)?,
+        QUICKNET.to_chain_info()
+    );
+    assert_eq!(
+        fetch_chain_info(&transport, "https://relay/unknown"),
+        Err(DrandClientError::NotResponding)
+    );
+    Ok(())
+}
+
+#[test]
+fn unparseable_responses_are_rejected() {
+    let mut transport = InMemoryTransport::quicknet();
+    transport
+        .0
+        .insert(DefaultUrlStrategy.info_url(BASE_URL), "{}".to_string());
+
+    assert_eq!(
+        fetch_chain_info(&transport, BASE_URL),
+        Err(DrandClientError::InvalidChainInfo)
+    );
+    assert!(serde_json::from_str::<Beacon>(r#"{"round":1000,"randomness":"zz"}"#).is_err());
+}
+
+#[test]
+fn beacons_parse_without_a_previous_signature() {
+    let beacon: Beacon = serde_json::from_str(ROUND_1000).unwrap();
+
+    assert_eq!(beacon.round_number, 1000);
+    assert_eq!(beacon.signature.len(), 48);
+    assert!(beacon.previous_signature.is_empty());
+}
+
+#[cfg(any(feature = "energon", feature = "verify-slim"))]
+#[test]
+fn beacons_are_fetched_and_verified() -> Result<(), DrandClientError> {
+    use drand_client_rs::testing::FixedClock;
+    use drand_client_rs::DrandClient;
+
+    let transport = InMemoryTransport::quicknet();
+    let info = fetch_chain_info(&transport, BASE_URL)?;
+    let clock = FixedClock::at_round(&info, 1000)?;
+    let client = DrandClient::with_chain_info(transport, BASE_URL, info).with_clock(clock);
+    let expected: Beacon = serde_json::from_str(ROUND_1000).unwrap();
+
+    assert_eq!(client.latest_randomness()?, expected);
+    assert_eq!(client.randomness(1000)?, expected);
+    assert_eq!(client.randomness(999), Err(DrandClientError::NotResponding));
+    Ok(())
+}